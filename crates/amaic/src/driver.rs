@@ -0,0 +1,97 @@
+//! Runs the front-end of the compiler over a file, collecting its diagnostics.
+
+use std::path::Path;
+
+use amaic_analyzer::{Lint, LintLevel, Reference, SemanticChecker};
+use amaic_ast::ASTModule;
+use amaic_core::{Diagnostic, FileId, SourceMap};
+use amaic_lexer::tokenize;
+use amaic_parser::{Parser, lex_error};
+use anyhow::Context as _;
+
+/// The result of running the front-end over a single file.
+pub struct Analysis {
+    /// Every diagnostic produced, including warnings.
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// The checked module, if the file could be parsed.
+    pub module: Option<ASTModule>,
+
+    /// Every place a symbol is defined or used in the module.
+    pub references: Vec<Reference>,
+}
+
+/// A file that has been run through the front-end.
+pub struct Checked {
+    /// Every diagnostic produced, including warnings.
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Every file loaded while checking.
+    pub sources: SourceMap,
+}
+
+impl Analysis {
+    /// The analysis of a file that couldn't be parsed.
+    const fn failed(diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            diagnostics,
+            module: None,
+            references: Vec::new(),
+        }
+    }
+}
+
+/// Analyzes a parsed module. Later levels in `lints` override earlier ones for
+/// the same lint.
+pub fn analyze(mut module: ASTModule, lints: &[(Lint, LintLevel)]) -> Analysis {
+    let mut checker = SemanticChecker::new();
+    for &(lint, level) in lints {
+        checker.set_lint_level(lint, level);
+    }
+    let diagnostics = checker
+        .validate(&mut module)
+        .unwrap_or_else(|errors| errors);
+
+    Analysis {
+        diagnostics,
+        module: Some(module),
+        references: checker.references().to_vec(),
+    }
+}
+
+/// Reads, lexes, parses and analyzes the file at `path`, with the levels of
+/// `lints` overriding their defaults.
+pub fn check(path: &Path, lints: &[(Lint, LintLevel)]) -> anyhow::Result<Checked> {
+    let mut sources = SourceMap::default();
+    let file = sources
+        .load(path)
+        .with_context(|| format!("Couldn't read `{}`", path.display()))?;
+    let Analysis { diagnostics, .. } = check_file(&sources, file, lints);
+
+    Ok(Checked {
+        diagnostics,
+        sources,
+    })
+}
+
+/// Lexes, parses and analyzes a loaded file, stopping at the first stage that
+/// fails. Later levels in `lints` override earlier ones for the same lint.
+pub fn check_file(sources: &SourceMap, file: FileId, lints: &[(Lint, LintLevel)]) -> Analysis {
+    match parse_file(sources, file) {
+        Ok(module) => analyze(module, lints),
+        Err(diagnostics) => Analysis::failed(diagnostics),
+    }
+}
+
+/// Lexes and parses a loaded file.
+pub fn parse_file(sources: &SourceMap, file: FileId) -> Result<ASTModule, Vec<Diagnostic>> {
+    let source_file = sources.get(file);
+    let tokens = tokenize(source_file.source()).map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|(err, range)| lex_error(file, &err, range))
+            .collect::<Vec<_>>()
+    })?;
+
+    Parser::new(source_file.path(), file, &tokens).parse()
+}
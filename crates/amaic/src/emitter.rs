@@ -0,0 +1,376 @@
+//! Prints diagnostics in each of the [`ErrorFormat`]s.
+
+use std::{
+    env,
+    fmt::Write as _,
+    io::{self, IsTerminal as _},
+};
+
+use amaic_core::{
+    Applicability, Diagnostic, LineColumn, PositionEncoding, Severity, SourceMap, Span, error_codes,
+};
+use colored::control;
+use serde_json::{Value, json};
+
+use crate::parser::{ColorChoice, ErrorFormat};
+
+/// Prints `diagnostics` in the given format. Human-readable output goes to
+/// stderr, while machine-readable output goes to stdout so it can be piped into
+/// other tools.
+pub fn emit(format: ErrorFormat, sources: &SourceMap, diagnostics: &[Diagnostic]) {
+    match format {
+        ErrorFormat::Human => {
+            for diagnostic in diagnostics {
+                eprintln!("{}", diagnostic.display(sources));
+            }
+        }
+        ErrorFormat::Json => {
+            for diagnostic in diagnostics {
+                println!("{}", to_json(sources, diagnostic));
+            }
+        }
+        ErrorFormat::Sarif => println!("{:#}", to_sarif(sources, diagnostics)),
+    }
+}
+
+/// How an applicability is spelled in JSON.
+const fn applicability_str(applicability: Applicability) -> &'static str {
+    match applicability {
+        Applicability::HasPlaceholders => "has-placeholders",
+        Applicability::MachineApplicable => "machine-applicable",
+        Applicability::MaybeIncorrect => "maybe-incorrect",
+    }
+}
+
+/// The path of the file `span` points into.
+fn path_of(sources: &SourceMap, span: Span) -> String {
+    sources.file_of(span).path().display().to_string()
+}
+
+/// The 1-based line and column of `offset` in the file of `span`, with columns
+/// counted in `char`s.
+fn position(sources: &SourceMap, span: Span, offset: usize) -> (usize, usize) {
+    let LineColumn { line, column } = sources
+        .file_of(span)
+        .line_column(offset, PositionEncoding::Char);
+    (line.strict_add(1), column.strict_add(1))
+}
+
+/// Decides whether diagnostics printed to stderr are colored.
+pub fn set_color(choice: ColorChoice) {
+    let colored = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Auto => {
+            env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && io::stderr().is_terminal()
+        }
+        ColorChoice::Never => false,
+    };
+    control::set_override(colored);
+}
+
+/// A SARIF location pointing at `span`, with an optional message.
+fn sarif_location(sources: &SourceMap, span: Span, message: Option<&str>) -> Value {
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": path_of(sources, span) },
+            "region": sarif_region(sources, span),
+        },
+    });
+    if let Some(text) = message {
+        location["message"] = json!({ "text": text });
+    }
+    location
+}
+
+/// A SARIF region covering `span`.
+fn sarif_region(sources: &SourceMap, span: Span) -> Value {
+    let (start_line, start_column) = position(sources, span, span.start());
+    let (end_line, end_column) = position(sources, span, span.end());
+    json!({
+        "startLine": start_line,
+        "startColumn": start_column,
+        "endLine": end_line,
+        "endColumn": end_column,
+        "byteOffset": span.start(),
+        "byteLength": span.len(),
+    })
+}
+
+/// `span` as a JSON object, with both its byte-range and its lines and
+/// columns.
+fn span_to_json(sources: &SourceMap, span: Span) -> Value {
+    let (line_start, column_start) = position(sources, span, span.start());
+    let (line_end, column_end) = position(sources, span, span.end());
+    json!({
+        "byte_start": span.start(),
+        "byte_end": span.end(),
+        "line_start": line_start,
+        "column_start": column_start,
+        "line_end": line_end,
+        "column_end": column_end,
+    })
+}
+
+/// A diagnostic as a JSON object.
+fn to_json(sources: &SourceMap, diagnostic: &Diagnostic) -> Value {
+    json!({
+        "code": diagnostic.code_or_lint(),
+        "severity": diagnostic.severity.as_str(),
+        "message": diagnostic.primary_err,
+        "file": path_of(sources, diagnostic.primary_span),
+        "span": span_to_json(sources, diagnostic.primary_span),
+        "label": diagnostic.primary_label,
+        "labels": diagnostic.secondary_labels.iter().map(|label| json!({
+            "span": span_to_json(sources, label.span),
+            "message": label.message,
+        })).collect::<Vec<_>>(),
+        "children": diagnostic.children.iter().map(|child| json!({
+            "severity": child.severity.as_str(),
+            "message": child.message,
+        })).collect::<Vec<_>>(),
+        "suggestions": diagnostic.suggestions.iter().map(|suggestion| json!({
+            "message": suggestion.message,
+            "span": span_to_json(sources, suggestion.span),
+            "replacement": suggestion.replacement,
+            "applicability": applicability_str(suggestion.applicability),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Every diagnostic as a single SARIF log, with a rule for each error code or
+/// lint they carry.
+fn to_sarif(sources: &SourceMap, diagnostics: &[Diagnostic]) -> Value {
+    let mut ids = diagnostics
+        .iter()
+        .filter_map(Diagnostic::code_or_lint)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids.dedup();
+    let rules = ids
+        .iter()
+        .map(|id| {
+            error_codes::find(id).map_or_else(
+                || json!({ "id": id }),
+                |error_code| {
+                    json!({
+                        "id": error_code.code,
+                        "shortDescription": { "text": error_code.title },
+                        "fullDescription": { "text": error_code.explanation },
+                    })
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut message = diagnostic.primary_err.clone();
+            for child in &diagnostic.children {
+                write!(message, "\n{}: {}", child.severity.as_str(), child.message)
+                    .expect("writing to a `String` never fails");
+            }
+
+            let mut result = json!({
+                "level": match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Help | Severity::Note => "note",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": message },
+                "locations": [sarif_location(
+                    sources,
+                    diagnostic.primary_span,
+                    diagnostic.primary_label.as_deref(),
+                )],
+                "relatedLocations": diagnostic.secondary_labels.iter().map(|label| sarif_location(
+                    sources,
+                    label.span,
+                    label.message.as_deref(),
+                )).collect::<Vec<_>>(),
+                "fixes": diagnostic.suggestions.iter().map(|suggestion| json!({
+                    "description": { "text": suggestion.message },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": path_of(sources, suggestion.span) },
+                        "replacements": [{
+                            "deletedRegion": sarif_region(sources, suggestion.span),
+                            "insertedContent": { "text": suggestion.replacement },
+                        }],
+                    }],
+                })).collect::<Vec<_>>(),
+            });
+            if let Some(id) = diagnostic.code_or_lint() {
+                result["ruleId"] = json!(id);
+            }
+            result
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "amaic",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver;
+
+    /// An error and a lint, with their diagnostics.
+    fn checked() -> (SourceMap, Vec<Diagnostic>) {
+        let mut sources = SourceMap::default();
+        let file = sources.add(
+            "test.amai",
+            "let f(): int = {\n    let x = 1;\n    nope\n};\n",
+        );
+        let diagnostics = driver::check_file(&sources, file, &[]).diagnostics;
+        (sources, diagnostics)
+    }
+
+    #[test]
+    fn json_reports_codes_and_lint_names() {
+        let (sources, diagnostics) = checked();
+        let lines: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| to_json(&sources, diagnostic))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                json!({
+                    "code": "unused_variables",
+                    "severity": "warning",
+                    "message": "Variable `x` is never used",
+                    "file": "test.amai",
+                    "span": {
+                        "byte_start": 21_usize,
+                        "byte_end": 30_usize,
+                        "line_start": 2_usize,
+                        "column_start": 5_usize,
+                        "line_end": 2_usize,
+                        "column_end": 14_usize,
+                    },
+                    "label": null,
+                    "labels": [],
+                    "children": [
+                        {
+                            "severity": "help",
+                            "message": "if this is intentional, prefix it with an underscore: `_x`",
+                        },
+                        {
+                            "severity": "note",
+                            "message": "`unused_variables` is set to `warn`",
+                        },
+                    ],
+                    "suggestions": [],
+                }),
+                json!({
+                    "code": "E0003",
+                    "severity": "error",
+                    "message": "Couldn't find variable `nope` in scope",
+                    "file": "test.amai",
+                    "span": {
+                        "byte_start": 36_usize,
+                        "byte_end": 40_usize,
+                        "line_start": 3_usize,
+                        "column_start": 5_usize,
+                        "line_end": 3_usize,
+                        "column_end": 9_usize,
+                    },
+                    "label": null,
+                    "labels": [],
+                    "children": [],
+                    "suggestions": [],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn sarif_has_a_rule_for_each_code_and_lint() {
+        let (sources, diagnostics) = checked();
+        assert_eq!(
+            to_sarif(&sources, &diagnostics),
+            json!({
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "amaic",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "rules": [
+                                {
+                                    "id": "E0003",
+                                    "shortDescription": { "text": error_codes::E0003.title },
+                                    "fullDescription": { "text": error_codes::E0003.explanation },
+                                },
+                                { "id": "unused_variables" },
+                            ],
+                        },
+                    },
+                    "columnKind": "unicodeCodePoints",
+                    "results": [
+                        {
+                            "ruleId": "unused_variables",
+                            "level": "warning",
+                            "message": {
+                                "text": "Variable `x` is never used\n\
+                                    help: if this is intentional, prefix it with an underscore: `_x`\n\
+                                    note: `unused_variables` is set to `warn`",
+                            },
+                            "locations": [{
+                                "physicalLocation": {
+                                    "artifactLocation": { "uri": "test.amai" },
+                                    "region": {
+                                        "startLine": 2_usize,
+                                        "startColumn": 5_usize,
+                                        "endLine": 2_usize,
+                                        "endColumn": 14_usize,
+                                        "byteOffset": 21_usize,
+                                        "byteLength": 9_usize,
+                                    },
+                                },
+                            }],
+                            "relatedLocations": [],
+                            "fixes": [],
+                        },
+                        {
+                            "ruleId": "E0003",
+                            "level": "error",
+                            "message": { "text": "Couldn't find variable `nope` in scope" },
+                            "locations": [{
+                                "physicalLocation": {
+                                    "artifactLocation": { "uri": "test.amai" },
+                                    "region": {
+                                        "startLine": 3_usize,
+                                        "startColumn": 5_usize,
+                                        "endLine": 3_usize,
+                                        "endColumn": 9_usize,
+                                        "byteOffset": 36_usize,
+                                        "byteLength": 4_usize,
+                                    },
+                                },
+                            }],
+                            "relatedLocations": [],
+                            "fixes": [],
+                        },
+                    ],
+                }],
+            })
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! See [`explain`].
+
+use amaic_core::error_codes;
+use anyhow::bail;
+
+/// Prints the long-form explanation of an error code.
+pub fn explain(code: &str) -> anyhow::Result<()> {
+    let Some(error_code) = error_codes::find(code) else {
+        bail!("`{code}` is not a valid error code");
+    };
+
+    println!("{}: {}\n", error_code.code, error_code.title);
+    print!("{}", error_code.explanation);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use amaic_analyzer::{Lint, LintLevel};
+    use amaic_core::{SourceMap, error_codes::ErrorCode};
+
+    use super::*;
+    use crate::driver;
+
+    /// The `amai` code blocks in an explanation, the first of which is the
+    /// erroneous example and the rest of which are corrected ones.
+    fn examples(error_code: &ErrorCode) -> Vec<String> {
+        let mut examples = Vec::new();
+        let mut lines = error_code.explanation.lines();
+        while lines.by_ref().any(|line| line == "```amai") {
+            let mut example = String::new();
+            for line in lines.by_ref().take_while(|line| *line != "```") {
+                example.push_str(line);
+                example.push('\n');
+            }
+            examples.push(example);
+        }
+        examples
+    }
+
+    /// Checks `source` with every lint allowed, returning the codes of the
+    /// diagnostics reported.
+    fn check(source: &str) -> Vec<&'static str> {
+        let mut sources = SourceMap::default();
+        let file = sources.add("example.amai", source);
+        let lints = Lint::ALL.map(|lint| (lint, LintLevel::Allow));
+        driver::check_file(&sources, file, &lints)
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code.unwrap_or("none"))
+            .collect()
+    }
+
+    #[test]
+    fn erroneous_examples_report_their_code() {
+        for error_code in error_codes::ALL {
+            let examples = examples(error_code);
+            let erroneous = examples.first().expect("every code has an example");
+            let codes = check(erroneous);
+            assert!(
+                codes.contains(&error_code.code),
+                "{} reports {codes:?} for:\n{erroneous}",
+                error_code.code,
+            );
+        }
+    }
+
+    #[test]
+    fn corrected_examples_check_cleanly() {
+        for error_code in error_codes::ALL {
+            for corrected in examples(error_code).iter().skip(1) {
+                assert_eq!(
+                    check(corrected),
+                    [] as [&str; 0],
+                    "in {}'s corrected example:\n{corrected}",
+                    error_code.code,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! See [`fix`].
+
+use std::{fs, path::Path};
+
+use amaic_analyzer::{Lint, LintLevel};
+use amaic_core::{Applicability, Diagnostic, SourceMap, Suggestion};
+use anyhow::Context as _;
+
+use crate::{driver, emitter, parser::ErrorFormat};
+
+/// How many times a file is re-checked for fixes that only appear once
+/// earlier ones were applied.
+const MAX_PASSES: usize = 4;
+
+/// Applies every machine-applicable suggestion to the file at `path`, with the
+/// levels of `lints` overriding their defaults, then reports the diagnostics
+/// that remain. The fixed file is written in place or, with `dry_run`, printed
+/// instead.
+///
+/// Returns whether no errors remain.
+pub fn fix(
+    path: &Path,
+    lints: &[(Lint, LintLevel)],
+    dry_run: bool,
+    format: ErrorFormat,
+) -> anyhow::Result<bool> {
+    let mut sources = SourceMap::default();
+    let file = sources
+        .load(path)
+        .with_context(|| format!("Couldn't read `{}`", path.display()))?;
+    let mut total: usize = 0;
+    let mut diagnostics = driver::check_file(&sources, file, lints).diagnostics;
+
+    for _ in 0..MAX_PASSES {
+        let suggestions = diagnostics
+            .iter()
+            .flat_map(|diagnostic| &diagnostic.suggestions)
+            .filter(|suggestion| {
+                suggestion.applicability == Applicability::MachineApplicable
+                    && suggestion.span.file() == file
+            });
+        let (fixed, applied) = Suggestion::apply_all(sources.get(file).source(), suggestions);
+        if applied == 0 {
+            break;
+        }
+
+        sources.replace(file, fixed);
+        total = total.strict_add(applied);
+        diagnostics = driver::check_file(&sources, file, lints).diagnostics;
+    }
+
+    let fixes = if total == 1 { "fix" } else { "fixes" };
+    if dry_run {
+        print!("{}", sources.get(file).source());
+        eprintln!("Would apply {total} {fixes} to `{}`", path.display());
+    } else {
+        if total > 0 {
+            fs::write(path, sources.get(file).source())
+                .with_context(|| format!("Couldn't write `{}`", path.display()))?;
+        }
+        eprintln!("Applied {total} {fixes} to `{}`", path.display());
+    }
+    emitter::emit(format, &sources, &diagnostics);
+    Ok(!diagnostics.iter().any(Diagnostic::is_error))
+}
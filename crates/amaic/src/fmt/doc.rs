@@ -0,0 +1,192 @@
+//! A document algebra for pretty-printing, in the style of Wadler's "A prettier
+//! printer": a [`Doc`] describes every way some text may be laid out, and
+//! [`render`] picks the layout that fits in a given width.
+
+use std::iter;
+
+/// How many spaces each level of nesting indents by.
+const INDENT: usize = 4;
+
+/// Text along with the places it may be broken across lines.
+#[derive(Clone, Debug)]
+pub enum Doc {
+    /// Documents printed one after another.
+    Concat(Vec<Self>),
+
+    /// A document that's printed on a single line if it fits, or with all of
+    /// its own lines broken otherwise.
+    Group(Box<Self>),
+
+    /// A newline, which breaks every group enclosing it.
+    HardLine,
+
+    /// Text that's only printed if the enclosing group is broken, such as a
+    /// trailing comma.
+    IfBreak(&'static str),
+
+    /// A space, or a newline if the enclosing group is broken.
+    Line,
+
+    /// Text that doesn't count towards the width of the line it's on, such as
+    /// a comment at the end of a line.
+    LineSuffix(String),
+
+    /// Indents the lines of a document by one more level.
+    Nest(Box<Self>),
+
+    /// Nothing, or a newline if the enclosing group is broken.
+    SoftLine,
+
+    /// Text, which may only contain newlines if it's taken verbatim from the
+    /// source-text, such as a multi-line string literal.
+    Text(String),
+}
+
+/// Whether a group is printed on a single line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Each line of the group is broken.
+    Break,
+
+    /// The group is printed on a single line.
+    Flat,
+}
+
+/// Writes rendered text, keeping track of the column.
+#[derive(Default)]
+struct Writer {
+    /// The column of the end of the text written so far.
+    column: usize,
+
+    /// The text written so far.
+    out: String,
+
+    /// The indentation of the last line, which is only written once there's
+    /// text on it, so that blank lines are empty.
+    pending_indent: Option<usize>,
+}
+
+impl Writer {
+    /// The column the next text is written at.
+    fn column(&self) -> usize {
+        self.pending_indent.unwrap_or(self.column)
+    }
+
+    /// Starts a new line, indented by `indent` once there's text on it.
+    fn newline(&mut self, indent: usize) {
+        self.out.push('\n');
+        self.column = 0;
+        self.pending_indent = Some(indent);
+    }
+
+    /// Writes `text` at the end of the current line.
+    fn write(&mut self, text: &str) {
+        if let Some(indent) = self.pending_indent.take() {
+            self.out.extend(iter::repeat_n(' ', indent));
+            self.column = indent;
+        }
+        self.out.push_str(text);
+        match text.rsplit_once('\n') {
+            Some((_, last_line)) => self.column = last_line.chars().count(),
+            None => self.column = self.column.strict_add(text.chars().count()),
+        }
+    }
+}
+
+/// Prints `docs` one after another.
+pub const fn concat(docs: Vec<Doc>) -> Doc {
+    Doc::Concat(docs)
+}
+
+/// Whether `doc` fits in `width` columns on a single line, along with what
+/// follows it up to the next line break.
+fn fits(width: usize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut remaining = width;
+    let mut following = rest.iter().rev().map(|&(_, mode, next)| (mode, next));
+    let mut stack = vec![(Mode::Flat, doc)];
+    // whether the group being measured has been measured, and only what
+    // follows it is left
+    let mut in_rest = false;
+
+    loop {
+        let Some((mode, current)) = stack.pop().or_else(|| {
+            in_rest = true;
+            following.next()
+        }) else {
+            return true;
+        };
+
+        match *current {
+            Doc::Concat(ref docs) => stack.extend(docs.iter().rev().map(|inner| (mode, inner))),
+            Doc::Group(ref inner) | Doc::Nest(ref inner) => stack.push((mode, inner)),
+            Doc::HardLine => return in_rest,
+            Doc::IfBreak(text) if mode == Mode::Break => {
+                let Some(left) = remaining.checked_sub(text.len()) else {
+                    return false;
+                };
+                remaining = left;
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => {
+                let Some(left) = remaining.checked_sub(1) else {
+                    return false;
+                };
+                remaining = left;
+            }
+            Doc::IfBreak(_) | Doc::LineSuffix(_) | Doc::SoftLine => {}
+            Doc::Text(ref text) => {
+                let first_line = text.split('\n').next().unwrap_or_default();
+                let Some(left) = remaining.checked_sub(first_line.chars().count()) else {
+                    return false;
+                };
+                remaining = left;
+                if text.contains('\n') {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// Prints `doc` on a single line if it fits.
+pub fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+/// Indents the lines of `doc` by one more level.
+pub fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+/// Lays `doc` out in `width` columns, breaking the outermost groups first.
+pub fn render(doc: &Doc, width: usize) -> String {
+    let mut writer = Writer::default();
+    let mut stack = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, current)) = stack.pop() {
+        match *current {
+            Doc::Concat(ref docs) => {
+                stack.extend(docs.iter().rev().map(|inner| (indent, mode, inner)));
+            }
+            Doc::Group(ref inner) => {
+                let fits_flat = mode == Mode::Flat
+                    || fits(width.saturating_sub(writer.column()), inner, &stack);
+                let inner_mode = if fits_flat { Mode::Flat } else { Mode::Break };
+                stack.push((indent, inner_mode, inner));
+            }
+            Doc::IfBreak(_) | Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::IfBreak(text) => writer.write(text),
+            Doc::Line if mode == Mode::Flat => writer.write(" "),
+            Doc::HardLine | Doc::Line | Doc::SoftLine => writer.newline(indent),
+            Doc::LineSuffix(ref text) | Doc::Text(ref text) => writer.write(text),
+            Doc::Nest(ref inner) => stack.push((indent.strict_add(INDENT), mode, inner)),
+        }
+    }
+
+    writer.out
+}
+
+/// Text.
+pub fn text(text: impl Into<String>) -> Doc {
+    Doc::Text(text.into())
+}
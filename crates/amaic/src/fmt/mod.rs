@@ -0,0 +1,126 @@
+//! See [`fmt`].
+
+mod doc;
+mod print;
+
+use std::{fs, path::PathBuf};
+
+use amaic_ast::ASTModule;
+use amaic_core::SourceMap;
+use anyhow::Context as _;
+
+use crate::{driver, emitter, parser::ErrorFormat};
+
+/// The width that formatted lines are kept within, where possible.
+const WIDTH: usize = 100;
+
+/// Formats each of the files at `paths` in place or, with `check`, only reports
+/// those that aren't formatted. Files that can't be parsed are left untouched,
+/// and their diagnostics reported.
+///
+/// Returns whether every file could be parsed and, with `check`, was already
+/// formatted.
+pub fn fmt(paths: &[PathBuf], check: bool, format: ErrorFormat) -> anyhow::Result<bool> {
+    let mut sources = SourceMap::default();
+    let mut diagnostics = Vec::new();
+    let mut unformatted = false;
+
+    for path in paths {
+        let file = sources
+            .load(path)
+            .with_context(|| format!("Couldn't read `{}`", path.display()))?;
+        let module = match driver::parse_file(&sources, file) {
+            Ok(module) => module,
+            Err(errors) => {
+                diagnostics.extend(errors);
+                continue;
+            }
+        };
+
+        let source = sources.get(file).source();
+        let formatted = format_module(&module, source);
+        if formatted == source {
+            continue;
+        }
+        if check {
+            eprintln!("`{}` isn't formatted", path.display());
+            unformatted = true;
+        } else {
+            fs::write(path, formatted)
+                .with_context(|| format!("Couldn't write `{}`", path.display()))?;
+        }
+    }
+
+    emitter::emit(format, &sources, &diagnostics);
+    Ok(diagnostics.is_empty() && !unformatted)
+}
+
+/// Formats a module parsed from `source`, keeping its comments.
+///
+/// Formatting is idempotent: formatting the output again leaves it unchanged.
+pub fn format_module(module: &ASTModule, source: &str) -> String {
+    let doc = print::Printer::new(source).module(module);
+    doc::render(&doc, WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// Parses and formats `source`.
+    fn format(source: &str) -> String {
+        let mut sources = SourceMap::default();
+        let file = sources.add("test.amai", source);
+        let module = driver::parse_file(&sources, file).expect("the source should parse");
+        format_module(&module, source)
+    }
+
+    #[test]
+    fn comments_in_lists_stay_at_their_items() {
+        assert_eq!(
+            format(
+                "/// Docs.\nlet f(a: int /* c */, /* d */ b: int): int = g(/* e */ a, b /* f */);\n"
+            ),
+            "/// Docs.\nlet f(a: int /* c */, /* d */ b: int): int = g(/* e */ a, b /* f */);\n"
+        );
+        assert_eq!(
+            format(
+                "let f(\n    a: int, // first\n    // before b\n    b: int // last\n): int = a;\n"
+            ),
+            "let f(\n    a: int, // first\n    // before b\n    b: int, // last\n): int = a;\n"
+        );
+        assert_eq!(
+            format("let f(): int = g(/* none */);\n"),
+            "let f(): int = g(/* none */);\n"
+        );
+        assert_eq!(
+            format("let f(): int = g(\n    1,\n    // dangling\n);\n"),
+            "let f(): int = g(\n    1,\n    // dangling\n);\n"
+        );
+    }
+
+    #[test]
+    fn formatting_the_corpus_is_idempotent() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+        let mut paths = fs::read_dir(&corpus)
+            .expect("the corpus should be readable")
+            .map(|entry| entry.expect("the corpus should be readable").path())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert!(!paths.is_empty(), "the corpus shouldn't be empty");
+
+        for path in paths {
+            let source = fs::read_to_string(&path).expect("the corpus should be readable");
+            let once = format(&source);
+            let twice = format(&once);
+            assert_eq!(
+                once,
+                twice,
+                "formatting `{}` twice changed it",
+                path.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! See [`Printer`].
+
+use std::ops::Range;
+
+use amaic_ast::{ASTModule, ASTNode, ASTNodeType};
+use amaic_core::Span;
+use amaic_lexer::{LosslessStream, Operator, TriviaKind};
+
+use super::doc::{Doc, concat, group, nest, text};
+
+/// Converts a parsed module into a [`Doc`], putting back the comments of its
+/// source-text.
+///
+/// Comments aren't part of the syntax tree, so they're kept between the
+/// statements they were found between: one on the same line as the end of a
+/// statement stays at the end of that line, and any other is put on its own
+/// line. Comments in a list of parameters or arguments stay next to the item
+/// they were written at, while any other comment within a statement but outside
+/// of the blocks in it is moved before the statement.
+pub struct Printer<'src> {
+    /// The byte-ranges of the comments in the source-text, in source order.
+    comments: Vec<Range<usize>>,
+
+    /// The index in `comments` of the first comment that hasn't been printed.
+    next_comment: usize,
+
+    /// The source-text, lexed.
+    stream: LosslessStream<'src>,
+}
+
+impl<'src> Printer<'src> {
+    /// An infix operation.
+    fn binary_op(&mut self, lhs: &ASTNode, op: Operator, rhs: &ASTNode) -> Doc {
+        let lhs_doc = self.parenthesized(lhs, lhs.needs_parens_as_lhs(op));
+        let op_text = text(format!(" {op}"));
+        let rhs_parens = rhs.needs_parens_as_rhs(op);
+        if is_assignment(op) {
+            group(concat(vec![lhs_doc, op_text, self.body(rhs, rhs_parens)]))
+        } else {
+            // the operands of a chain of operators line up, indented by
+            // whatever the chain is the body of
+            let rhs_doc = self.parenthesized(rhs, rhs_parens);
+            group(concat(vec![lhs_doc, op_text, Doc::Line, rhs_doc]))
+        }
+    }
+
+    /// A block of statements, each on its own line.
+    fn block(&mut self, stmts: &[ASTNode], span: Span) -> Doc {
+        let lines = self.stmts(stmts, span.end());
+        if lines.is_empty() {
+            return text("{}");
+        }
+        concat(vec![
+            text("{"),
+            nest(concat(vec![Doc::HardLine, concat(lines)])),
+            Doc::HardLine,
+            text("}"),
+        ])
+    }
+
+    /// An expression that follows a keyword or assignment, such as the
+    /// initializer of a `let`. Blocks, and expressions that open one, stay on
+    /// the same line, while anything else moves to the next line if it doesn't
+    /// fit.
+    fn body(&mut self, node: &ASTNode, parens: bool) -> Doc {
+        let doc = self.parenthesized(node, parens);
+        if hugs(node) && !parens {
+            concat(vec![text(" "), doc])
+        } else {
+            group(nest(concat(vec![Doc::Line, doc])))
+        }
+    }
+
+    /// The byte-offset of the first `)` at or after the byte-offset `pos`.
+    fn close_paren(&self, pos: usize) -> usize {
+        self.stream
+            .tokens()
+            .iter()
+            .filter(|token| token.span.start >= pos)
+            .find(|token| self.stream.text(token) == ")")
+            .map_or(pos, |token| token.span.start)
+    }
+
+    /// The source-text of the comment at `comment`.
+    fn comment_text(&self, comment: Range<usize>) -> &'src str {
+        self.stream.source().get(comment).unwrap_or_default()
+    }
+
+    /// Adds the comments that haven't been printed and start before the
+    /// byte-offset `pos` to `lines`.
+    fn comments_before(&mut self, pos: usize, lines: &mut Vec<Doc>) {
+        while let Some(comment) = self.take_comment(|comment| comment.start < pos) {
+            let line = text(self.comment_text(comment.clone()));
+            self.push_line(lines, comment.start, line);
+        }
+    }
+
+    /// Converts an expression or statement.
+    #[expect(
+        clippy::too_many_lines,
+        reason = "Each kind of node is converted in a single arm."
+    )]
+    fn expr(&mut self, node: &ASTNode) -> Doc {
+        match node.ty {
+            ASTNodeType::BinaryOp {
+                ref lhs,
+                op,
+                ref rhs,
+                ..
+            } => self.binary_op(lhs, op, rhs),
+            ASTNodeType::Block(ref stmts) => self.block(stmts, node.span),
+            ASTNodeType::Boolean(value) => text(value.to_string()),
+            ASTNodeType::FloatLit(_)
+            | ASTNodeType::IntLit(_)
+            | ASTNodeType::Interpolation(_)
+            | ASTNodeType::StringLit(_) => text(self.literal(node.span)),
+            ASTNodeType::FunCall {
+                ref args,
+                ref callee,
+            } => {
+                // a call ends with its `)`
+                let close = node.span.end().strict_sub(1);
+                let arg_list = self.list(args, close, |arg| arg.span, Self::expr);
+                concat(vec![text(callee), arg_list])
+            }
+            ASTNodeType::FunDef {
+                ref body,
+                ref docs,
+                ref name,
+                ref params,
+                ref return_ty,
+            } => {
+                let after = params
+                    .last()
+                    .map_or_else(|| node.span.start(), |param| param.2.end());
+                let param_list = self.list(
+                    params,
+                    self.close_paren(after),
+                    |param| param.2,
+                    |_, param| text(format!("{}: {}", param.0, param.1)),
+                );
+                let mut parts = vec![text(format!("let {name}")), param_list];
+                if let Some(ref ty) = *return_ty {
+                    parts.push(text(format!(": {ty}")));
+                }
+                parts.extend([text(" ="), self.body(body, false)]);
+                documented(docs.as_deref(), parts)
+            }
+            ASTNodeType::Identifier(ref name) => text(name),
+            ASTNodeType::If {
+                ref condition,
+                ref else_body,
+                ref then_body,
+            } => {
+                let then_parens = then_body.needs_parens_as_then_body(else_body.is_some());
+                let mut parts = vec![
+                    text("if "),
+                    self.expr(condition),
+                    text(" then"),
+                    self.body(then_body, then_parens),
+                ];
+                if let Some(ref otherwise) = *else_body {
+                    let after_block = matches!(then_body.ty, ASTNodeType::Block(_)) && !then_parens;
+                    parts.push(if after_block { text(" ") } else { Doc::Line });
+                    parts.extend([text("else"), self.body(otherwise, false)]);
+                }
+                group(concat(parts))
+            }
+            ASTNodeType::LetDecl {
+                ref docs,
+                ref init,
+                ref name,
+                ref ty,
+            } => {
+                let mut parts = vec![text(format!("let {name}"))];
+                if let Some(ref annotation) = *ty {
+                    parts.push(text(format!(": {annotation}")));
+                }
+                if let Some(ref value) = *init {
+                    parts.extend([text(" ="), self.body(value, false)]);
+                }
+                documented(docs.as_deref(), parts)
+            }
+            ASTNodeType::Semi(ref inner) => concat(vec![self.expr(inner), text(";")]),
+            ASTNodeType::UnaryOp {
+                op, ref operand, ..
+            } => {
+                let parens = operand.needs_parens_as_operand(op);
+                let space = !parens && operand.needs_space_as_operand(op);
+                concat(vec![
+                    text(op.to_string()),
+                    text(if space { " " } else { "" }),
+                    self.parenthesized(operand, parens),
+                ])
+            }
+            ASTNodeType::Unit => text("()"),
+            ASTNodeType::While {
+                ref body,
+                ref condition,
+            } => group(concat(vec![
+                text("while "),
+                self.expr(condition),
+                text(" do"),
+                self.body(body, false),
+            ])),
+        }
+    }
+
+    /// Where a statement starts in the source-text, including its doc
+    /// comments.
+    fn item_start(&self, stmt: &ASTNode) -> usize {
+        let Some(docs) = docs_of(stmt) else {
+            return stmt.span.start();
+        };
+        let tokens = self.stream.tokens();
+        let first = tokens.partition_point(|token| token.span.start < stmt.span.start());
+        first
+            .checked_sub(docs.split('\n').count())
+            .and_then(|index| tokens.get(index))
+            .map_or_else(|| stmt.span.start(), |token| token.span.start)
+    }
+
+    /// Takes the comments that start before the byte-offset `pos`, each
+    /// followed by a space, or by a newline if it's a line comment.
+    fn leading_comments(&mut self, pos: usize) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        while let Some(range) = self.take_comment(|comment| comment.start < pos) {
+            let comment = self.comment_text(range);
+            let after = if is_line_comment(comment) {
+                Doc::HardLine
+            } else {
+                text(" ")
+            };
+            docs.extend([text(comment), after]);
+        }
+        docs
+    }
+
+    /// Comma-separated `items` between parentheses, all on one line if they
+    /// fit, or each on its own line with a trailing comma otherwise. The list
+    /// ends at the `)` at the byte-offset `close`.
+    ///
+    /// A comment on the same line as the end of an item stays after it, and
+    /// any other comment stays before the item after it.
+    fn list<T>(
+        &mut self,
+        items: &[T],
+        close: usize,
+        span_of: impl Fn(&T) -> Span,
+        mut convert: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        let mut inner = vec![Doc::SoftLine];
+        let mut last_line = Doc::SoftLine;
+        for (index, item) in items.iter().enumerate() {
+            let span = span_of(item);
+            inner.extend(self.leading_comments(span.start()));
+            inner.push(convert(self, item));
+
+            // block comments before the `,` or `)` after the item stay before it
+            let separator = self.next_token_start(span.end());
+            let source = self.stream.source();
+            while let Some(range) = self.take_comment(|comment| {
+                comment.start < separator
+                    && !is_line_comment(source.get(comment.clone()).unwrap_or_default())
+            }) {
+                inner.push(text(format!(" {}", self.comment_text(range))));
+            }
+
+            let next = items.get(index.strict_add(1));
+            inner.push(if next.is_some() {
+                text(",")
+            } else {
+                Doc::IfBreak(",")
+            });
+            let next_start = next.map_or(close, |next_item| span_of(next_item).start());
+            let mut line = Doc::Line;
+            while let Some(range) = self.take_comment(|comment| {
+                comment.start < next_start && on_same_line(source, span.end(), comment.start)
+            }) {
+                let comment = self.comment_text(range);
+                if is_line_comment(comment) {
+                    inner.push(Doc::LineSuffix(format!(" {comment}")));
+                    line = Doc::HardLine;
+                } else {
+                    inner.push(text(format!(" {comment}")));
+                }
+            }
+            match (next, line) {
+                (Some(_), break_after) => inner.push(break_after),
+                (None, Doc::HardLine) => last_line = Doc::HardLine,
+                (None, _) => {}
+            }
+        }
+        // the comments left are on lines of their own, unless the list is empty
+        while let Some(range) = self.take_comment(|comment| comment.start < close) {
+            let comment = self.comment_text(range);
+            if inner.len() > 1 {
+                inner.push(Doc::HardLine);
+            }
+            inner.push(text(comment));
+            if !items.is_empty() || is_line_comment(comment) {
+                last_line = Doc::HardLine;
+            }
+        }
+
+        if inner.len() == 1 {
+            return text("()");
+        }
+        group(concat(vec![
+            text("("),
+            nest(concat(inner)),
+            last_line,
+            text(")"),
+        ]))
+    }
+
+    /// The source-text of a literal. Literals are printed as they were
+    /// written, so that the base of an integer or the form of a string is
+    /// kept, but without any space after the `-` of a negative literal.
+    fn literal(&self, span: Span) -> String {
+        let tokens = self.stream.tokens();
+        let last = tokens.partition_point(|token| token.span.end < span.end());
+        match tokens.get(last) {
+            Some(token) if token.span.start > span.start() => {
+                format!("-{}", self.stream.text(token))
+            }
+            Some(token) => self.stream.text(token).to_owned(),
+            None => self
+                .stream
+                .source()
+                .get(span.start()..span.end())
+                .unwrap_or_default()
+                .to_owned(),
+        }
+    }
+
+    /// Converts a whole module, ending with a newline unless it's empty.
+    pub fn module(&mut self, module: &ASTModule) -> Doc {
+        let mut docs = Vec::new();
+        if let Some(ref module_docs) = module.docs {
+            docs.push(doc_comments("//!", module_docs));
+        }
+
+        let lines = self.stmts(&module.nodes, self.stream.source().len());
+        if !lines.is_empty() {
+            if !docs.is_empty() {
+                docs.extend([Doc::HardLine, Doc::HardLine]);
+            }
+            docs.extend(lines);
+        }
+
+        if !docs.is_empty() {
+            docs.push(Doc::HardLine);
+        }
+        concat(docs)
+    }
+
+    /// Prepares to print a module parsed from `source`.
+    pub fn new(source: &'src str) -> Self {
+        let stream = LosslessStream::new(source);
+        let comments = stream
+            .tokens()
+            .iter()
+            .flat_map(|token| token.leading.iter().chain(&token.trailing))
+            .chain(stream.end_trivia())
+            .filter(|trivia| trivia.kind == TriviaKind::Comment)
+            .map(|trivia| trivia.span.clone())
+            .collect();
+
+        Self {
+            comments,
+            next_comment: 0,
+            stream,
+        }
+    }
+
+    /// The byte-offset of the first token at or after the byte-offset `pos`.
+    fn next_token_start(&self, pos: usize) -> usize {
+        let tokens = self.stream.tokens();
+        let next = tokens.partition_point(|token| token.span.start < pos);
+        tokens.get(next).map_or(pos, |token| token.span.start)
+    }
+
+    /// Converts `node`, in parentheses if `parens` is set.
+    fn parenthesized(&mut self, node: &ASTNode, parens: bool) -> Doc {
+        let doc = self.expr(node);
+        if parens {
+            concat(vec![text("("), doc, text(")")])
+        } else {
+            doc
+        }
+    }
+
+    /// Adds a line to `lines`, keeping a blank line before it if there's one
+    /// before the byte-offset `start` in the source-text.
+    fn push_line(&self, lines: &mut Vec<Doc>, start: usize, line: Doc) {
+        if !lines.is_empty() {
+            lines.push(Doc::HardLine);
+            let before = self.stream.source().get(..start).unwrap_or_default();
+            let whitespace = before.get(before.trim_end().len()..).unwrap_or_default();
+            if whitespace.matches('\n').count() > 1 {
+                lines.push(Doc::HardLine);
+            }
+        }
+        lines.push(line);
+    }
+
+    /// The statements of a module or block, each on its own line along with
+    /// the comments between them, up to the byte-offset `end`.
+    fn stmts(&mut self, stmts: &[ASTNode], end: usize) -> Vec<Doc> {
+        let mut lines = Vec::new();
+        for stmt in stmts {
+            let start = self.item_start(stmt);
+            self.comments_before(start, &mut lines);
+
+            let mut doc = self.expr(stmt);
+            self.comments_before(stmt.span.end(), &mut lines);
+            if let Some(comment) = self.trailing_comment(stmt.span.end()) {
+                doc = concat(vec![doc, Doc::LineSuffix(format!(" {comment}"))]);
+            }
+            self.push_line(&mut lines, start, doc);
+        }
+        self.comments_before(end, &mut lines);
+
+        lines
+    }
+
+    /// Takes the next comment that hasn't been printed if `take` accepts its
+    /// byte-range.
+    fn take_comment(&mut self, take: impl FnOnce(&Range<usize>) -> bool) -> Option<Range<usize>> {
+        let comment = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| take(comment))?
+            .clone();
+        self.next_comment = self.next_comment.strict_add(1);
+        Some(comment)
+    }
+
+    /// Takes the next comment if it's on the same line as the byte-offset
+    /// `end`.
+    fn trailing_comment(&mut self, end: usize) -> Option<&'src str> {
+        let source = self.stream.source();
+        let comment = self.take_comment(|comment| on_same_line(source, end, comment.start))?;
+        Some(self.comment_text(comment))
+    }
+}
+
+/// Doc comments with the given marker, one per line of `docs`.
+fn doc_comments(marker: &str, docs: &str) -> Doc {
+    let mut lines = Vec::new();
+    for line in docs.split('\n') {
+        if !lines.is_empty() {
+            lines.push(Doc::HardLine);
+        }
+        lines.push(if line.is_empty() {
+            text(marker)
+        } else {
+            text(format!("{marker} {line}"))
+        });
+    }
+    concat(lines)
+}
+
+/// A declaration made up of `parts`, after its doc comments, if any.
+fn documented(docs: Option<&str>, parts: Vec<Doc>) -> Doc {
+    match docs {
+        Some(comments) => concat(vec![
+            doc_comments("///", comments),
+            Doc::HardLine,
+            concat(parts),
+        ]),
+        None => concat(parts),
+    }
+}
+
+/// The doc comments of a declaration, which may be followed by a `;`.
+fn docs_of(node: &ASTNode) -> Option<&str> {
+    match node.ty {
+        ASTNodeType::FunDef { ref docs, .. } | ASTNodeType::LetDecl { ref docs, .. } => {
+            docs.as_deref()
+        }
+        ASTNodeType::Semi(ref inner) => docs_of(inner),
+        _ => None,
+    }
+}
+
+/// Whether `node` stays on the line of the keyword or assignment before it,
+/// which is the case if it's a block, starts one on its first line, or is a
+/// call, whose arguments are broken across lines instead.
+const fn hugs(node: &ASTNode) -> bool {
+    match node.ty {
+        ASTNodeType::Block(_) | ASTNodeType::FunCall { .. } => true,
+        ASTNodeType::If {
+            then_body: ref body,
+            ..
+        }
+        | ASTNodeType::While { ref body, .. } => matches!(body.ty, ASTNodeType::Block(_)),
+        _ => false,
+    }
+}
+
+/// Whether `comment` runs to the end of its line.
+fn is_line_comment(comment: &str) -> bool {
+    comment.starts_with("//")
+}
+
+/// Whether the byte-offsets `start` and `end` of `source` are on the same line.
+fn on_same_line(source: &str, start: usize, end: usize) -> bool {
+    source
+        .get(start..end)
+        .is_some_and(|between| !between.contains('\n'))
+}
+
+/// Whether `op` assigns to its left-hand side.
+const fn is_assignment(op: Operator) -> bool {
+    matches!(
+        op,
+        Operator::Assign
+            | Operator::MinusAssign
+            | Operator::ModuloAssign
+            | Operator::PlusAssign
+            | Operator::SlashAssign
+            | Operator::StarAssign
+    )
+}
@@ -0,0 +1,675 @@
+//! See [`serve`].
+
+mod rpc;
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use amaic_analyzer::{Lint, LintLevel, Reference, SymbolKind};
+use amaic_ast::{ASTModule, ASTNode, ASTNodeType, Visitor, visit};
+use amaic_core::{
+    Diagnostic, FileId, LineColumn, PositionEncoding, Severity, SourceFile, SourceMap, Span,
+};
+use amaic_lexer::{LexedSource, TextEdit, TokenKind, tokenize};
+use anyhow::bail;
+use serde_json::{Value, json};
+
+use crate::driver::{self, Analysis};
+
+/// The result of handling a request: either its result, or an error code and
+/// message.
+type Response = Result<Value, (i64, &'static str)>;
+
+/// An open document.
+struct Document {
+    /// The result of checking the document's current text.
+    analysis: Analysis,
+
+    /// The document's file in the server's source map.
+    file: FileId,
+
+    /// The document's current text and its tokens.
+    lexed: LexedSource,
+}
+
+/// Converts between byte-offsets and protocol positions within a document.
+#[derive(Clone, Copy)]
+struct Positions<'file> {
+    /// The unit positions' characters are counted in.
+    encoding: PositionEncoding,
+
+    /// The document's file.
+    file: &'file SourceFile,
+}
+
+/// The state of the language server.
+#[derive(Default)]
+struct Server {
+    /// The documents currently open, by URI.
+    documents: HashMap<String, Document>,
+
+    /// The unit positions' characters are counted in, as agreed with the
+    /// client.
+    encoding: PositionEncoding,
+
+    /// The file of every document that's been opened, by URI.
+    files: HashMap<String, FileId>,
+
+    /// Whether `initialize` has been received.
+    initialized: bool,
+
+    /// The levels of lints the client overrode when initializing, as in
+    /// `{ "lints": { "unused_variables": "allow" } }`.
+    lints: Vec<(Lint, LintLevel)>,
+
+    /// Whether `shutdown` has been received.
+    shut_down: bool,
+
+    /// Every document that's been opened.
+    sources: SourceMap,
+}
+
+impl Positions<'_> {
+    /// The byte-offset of a protocol position, clamped to the document.
+    fn offset(&self, position: &Value) -> usize {
+        let line_column = LineColumn {
+            line: index_of(&position["line"]),
+            column: index_of(&position["character"]),
+        };
+        self.file.offset(line_column, self.encoding)
+    }
+
+    /// The protocol position of a byte-offset.
+    fn position(&self, offset: usize) -> Value {
+        let LineColumn { line, column } = self.file.line_column(offset, self.encoding);
+        json!({ "line": line, "character": column })
+    }
+
+    /// The protocol range of a span.
+    fn range(&self, span: Span) -> Value {
+        json!({
+            "start": self.position(span.start()),
+            "end": self.position(span.end()),
+        })
+    }
+}
+
+impl Server {
+    /// Applies a document's changes, reparsing only the items they touch while
+    /// the document keeps parsing.
+    fn change(&mut self, uri: &str, changes: &[Value]) {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return;
+        };
+        let file = document.file;
+        let mut module = document.analysis.module.take();
+
+        for change in changes {
+            let replacement = change["text"].as_str().unwrap_or_default().to_owned();
+            let positions = Positions {
+                encoding: self.encoding,
+                file: self.sources.get(file),
+            };
+            let range = change.get("range").map_or_else(
+                || 0..positions.file.source().len(),
+                |range| positions.offset(&range["start"])..positions.offset(&range["end"]),
+            );
+            let edit = TextEdit { range, replacement };
+
+            module = if let Some(mut edited) = module {
+                amaic_parser::reparse(&mut edited, file, &mut document.lexed, &edit).map(|_| edited)
+            } else {
+                document.lexed.edit(&edit);
+                None
+            };
+            self.sources.replace(file, document.lexed.source());
+        }
+
+        document.analysis = match module {
+            Some(edited) => driver::analyze(edited, &self.lints),
+            None => driver::check_file(&self.sources, file, &self.lints),
+        };
+    }
+
+    /// Completes the names in scope at the cursor, along with keywords.
+    fn completion(&self, params: &Value) -> Response {
+        let (_, document, cursor) = self.document_at(params)?;
+
+        let mut items = Vec::new();
+        if let Some(ref module) = document.analysis.module {
+            for span in names_in_scope(module, cursor) {
+                let Some(definition) = document
+                    .analysis
+                    .references
+                    .iter()
+                    .find(|reference| reference.span == span)
+                else {
+                    continue;
+                };
+                items.push(json!({
+                    "label": definition.name,
+                    "kind": match definition.symbol.kind {
+                        SymbolKind::Function => 3_i32,
+                        SymbolKind::Parameter | SymbolKind::Variable => 6_i32,
+                        _ => 1_i32,
+                    },
+                    "detail": definition.symbol.ty.display(),
+                }));
+            }
+        }
+        items.extend(
+            amaic_parser::KEYWORDS
+                .iter()
+                .map(|keyword| json!({ "label": keyword.to_string(), "kind": 14_i32 })),
+        );
+
+        Ok(json!(items))
+    }
+
+    /// Finds where the symbol under the cursor is defined.
+    fn definition(&self, params: &Value) -> Response {
+        let (uri, document, cursor) = self.document_at(params)?;
+        let Some(reference) = reference_at(&document.analysis, cursor) else {
+            return Ok(Value::Null);
+        };
+
+        Ok(json!({
+            "uri": uri,
+            "range": self.positions(document.file).range(reference.symbol.defined_at),
+        }))
+    }
+
+    /// The document a request is about, and the cursor as an empty span.
+    fn document_at<'params>(
+        &self,
+        params: &'params Value,
+    ) -> Result<(&'params str, &Document, Span), (i64, &'static str)> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((rpc::INVALID_PARAMS, "Missing document URI"))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or((rpc::INVALID_PARAMS, "Document isn't open"))?;
+        let offset = self.positions(document.file).offset(&params["position"]);
+        Ok((uri, document, Span::new(document.file, offset, offset)))
+    }
+
+    /// Lists the functions in a document, with those defined inside them as
+    /// their children.
+    fn document_symbols(&self, params: &Value) -> Response {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((rpc::INVALID_PARAMS, "Missing document URI"))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or((rpc::INVALID_PARAMS, "Document isn't open"))?;
+        let Some(ref module) = document.analysis.module else {
+            return Ok(json!([]));
+        };
+
+        Ok(json!(function_symbols(
+            self.positions(document.file),
+            &document.analysis,
+            module
+        )))
+    }
+
+    /// Shows the type of the symbol under the cursor, along with its docs.
+    fn hover(&self, params: &Value) -> Response {
+        let (_, document, cursor) = self.document_at(params)?;
+        let Some(reference) = reference_at(&document.analysis, cursor) else {
+            return Ok(Value::Null);
+        };
+
+        let mut contents = format!(
+            "```amai\n{}: {}\n```",
+            reference.name,
+            reference.symbol.ty.display()
+        );
+        if let Some(docs) = document
+            .analysis
+            .module
+            .as_ref()
+            .and_then(|module| docs_of(module, reference.symbol.defined_at))
+        {
+            contents.push_str("\n\n");
+            contents.push_str(docs);
+        }
+
+        Ok(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": self.positions(document.file).range(reference.span),
+        }))
+    }
+
+    /// Handles a notification, returning the notifications to send back.
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match method {
+            "textDocument/didChange" => {
+                let Some(changes) = params["contentChanges"].as_array() else {
+                    return Vec::new();
+                };
+                self.change(uri, changes);
+                vec![self.publish_diagnostics(uri)]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                vec![rpc::notification(
+                    "textDocument/publishDiagnostics",
+                    &json!({ "uri": uri, "diagnostics": [] }),
+                )]
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.open(uri, text);
+                vec![self.publish_diagnostics(uri)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks a newly opened document.
+    fn open(&mut self, uri: &str, text: &str) {
+        let file = if let Some(&file) = self.files.get(uri) {
+            self.sources.replace(file, text);
+            file
+        } else {
+            let file = self.sources.add(path_of_uri(uri), text);
+            self.files.insert(uri.to_owned(), file);
+            file
+        };
+        let analysis = driver::check_file(&self.sources, file, &self.lints);
+        let lexed = LexedSource::new(text.to_owned());
+        self.documents.insert(
+            uri.to_owned(),
+            Document {
+                analysis,
+                file,
+                lexed,
+            },
+        );
+    }
+
+    /// Converts positions within the file of a document.
+    fn positions(&self, file: FileId) -> Positions<'_> {
+        Positions {
+            encoding: self.encoding,
+            file: self.sources.get(file),
+        }
+    }
+
+    /// The notification publishing a document's diagnostics.
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = self.documents.get(uri).map_or_else(Vec::new, |document| {
+            let positions = self.positions(document.file);
+            document
+                .analysis
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.primary_span.file() == document.file)
+                .map(|diagnostic| to_lsp_diagnostic(uri, positions, diagnostic))
+                .collect()
+        });
+        rpc::notification(
+            "textDocument/publishDiagnostics",
+            &json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Handles a request, returning its response.
+    fn request(&mut self, method: &str, params: &Value) -> Response {
+        if self.shut_down {
+            return Err((rpc::INVALID_REQUEST, "The server has been shut down"));
+        }
+        if method == "initialize" {
+            self.initialized = true;
+            // the client lists the encodings it supports by preference, and
+            // UTF-16 is always supported
+            let encodings = params["capabilities"]["general"]["positionEncodings"].as_array();
+            self.encoding = encodings
+                .into_iter()
+                .flatten()
+                .find_map(|encoding| match encoding.as_str()? {
+                    "utf-16" => Some(PositionEncoding::Utf16),
+                    "utf-32" => Some(PositionEncoding::Char),
+                    "utf-8" => Some(PositionEncoding::Utf8),
+                    _ => None,
+                })
+                .unwrap_or(PositionEncoding::Utf16);
+            // unknown lints and levels are ignored, like unknown options
+            let lints = params["initializationOptions"]["lints"].as_object();
+            self.lints = lints
+                .into_iter()
+                .flatten()
+                .filter_map(|(lint, level)| {
+                    Some((
+                        Lint::from_name(lint)?,
+                        LintLevel::from_name(level.as_str()?)?,
+                    ))
+                })
+                .collect();
+            return Ok(json!({
+                "capabilities": {
+                    "positionEncoding": match self.encoding {
+                        PositionEncoding::Char => "utf-32",
+                        PositionEncoding::Utf16 => "utf-16",
+                        PositionEncoding::Utf8 => "utf-8",
+                    },
+                    "textDocumentSync": { "openClose": true, "change": 2 },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {},
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "amaic", "version": env!("CARGO_PKG_VERSION") },
+            }));
+        }
+        if !self.initialized {
+            return Err((
+                rpc::SERVER_NOT_INITIALIZED,
+                "The server hasn't been initialized",
+            ));
+        }
+
+        match method {
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "textDocument/hover" => self.hover(params),
+            _ => Err((rpc::METHOD_NOT_FOUND, "Unsupported method")),
+        }
+    }
+}
+
+/// Runs a language server over stdio.
+pub fn run() -> anyhow::Result<()> {
+    serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+/// Serves the Language Server Protocol, reading messages from `reader` and
+/// writing them to `writer`, until the client sends `exit`.
+///
+/// # Errors
+///
+/// Errors if a message can't be read or written, or if the client exits
+/// without shutting the server down first.
+pub fn serve(reader: &mut impl BufRead, writer: &mut impl Write) -> anyhow::Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = rpc::read(reader)? {
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let Some(method) = message["method"].as_str() else {
+            // a response to a request from the server, which never sends any
+            continue;
+        };
+
+        if let Some(id) = message.get("id") {
+            let response = match server.request(method, params) {
+                Ok(result) => rpc::response(id, &result),
+                Err((code, error)) => rpc::error_response(id, code, error),
+            };
+            rpc::write(writer, &response)?;
+        } else if method == "exit" {
+            if !server.shut_down {
+                bail!("The client exited without shutting the server down");
+            }
+            return Ok(());
+        } else {
+            for notification in server.notification(method, params) {
+                rpc::write(writer, &notification)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The doc comments of the declaration at `span`.
+fn docs_of(module: &ASTModule, span: Span) -> Option<&str> {
+    /// Finds the node at a span.
+    struct Find<'ast> {
+        /// The node at the span, once it's been visited.
+        found: Option<&'ast ASTNode>,
+
+        /// The span of the node to find.
+        span: Span,
+    }
+
+    impl<'ast> Visitor<'ast> for Find<'ast> {
+        fn visit_node(&mut self, node: &'ast ASTNode) {
+            if self.found.is_some() {
+                return;
+            }
+            if node.span == self.span {
+                self.found = Some(node);
+            } else {
+                visit::walk_node(self, node);
+            }
+        }
+    }
+
+    let mut find = Find { found: None, span };
+    find.visit_module(module);
+    match find.found?.ty {
+        ASTNodeType::FunDef { ref docs, .. } | ASTNodeType::LetDecl { ref docs, .. } => {
+            docs.as_deref()
+        }
+        _ => None,
+    }
+}
+
+/// The document symbols of the functions in `module`, each with the functions
+/// inside it as its children.
+fn function_symbols(positions: Positions, analysis: &Analysis, module: &ASTModule) -> Vec<Value> {
+    /// Collects the symbols of the outermost functions it visits.
+    struct Symbols<'doc> {
+        /// The analysis of the document, which has the functions' types.
+        analysis: &'doc Analysis,
+
+        /// The symbols collected so far.
+        found: Vec<Value>,
+
+        /// Converts the functions' spans into ranges.
+        positions: Positions<'doc>,
+    }
+
+    impl Visitor<'_> for Symbols<'_> {
+        fn visit_node(&mut self, node: &ASTNode) {
+            let ASTNodeType::FunDef { ref name, .. } = node.ty else {
+                visit::walk_node(self, node);
+                return;
+            };
+
+            let mut children = Symbols {
+                found: Vec::new(),
+                ..*self
+            };
+            visit::walk_node(&mut children, node);
+            let ty = self
+                .analysis
+                .references
+                .iter()
+                .find(|reference| reference.span == node.span)
+                .map(|reference| reference.symbol.ty.display());
+            self.found.push(json!({
+                "name": name,
+                "detail": ty,
+                "kind": 12_i32,
+                "range": self.positions.range(node.span),
+                "selectionRange": self.positions.range(name_span(self.positions.file, node)),
+                "children": children.found,
+            }));
+        }
+    }
+
+    let mut symbols = Symbols {
+        analysis,
+        found: Vec::new(),
+        positions,
+    };
+    symbols.visit_module(module);
+    symbols.found
+}
+
+/// A protocol position's field as a `usize`, or 0 if it's missing.
+fn index_of(value: &Value) -> usize {
+    value
+        .as_u64()
+        .and_then(|index| usize::try_from(index).ok())
+        .unwrap_or_default()
+}
+
+/// The span of the name `node` declares, which is the first identifier in it,
+/// or the whole of `node` if it can't be found.
+fn name_span(file: &SourceFile, node: &ASTNode) -> Span {
+    let start = node.span.start();
+    file.source()
+        .get(start..node.span.end())
+        .and_then(|text| tokenize(text).ok())
+        .and_then(|tokens| {
+            tokens
+                .into_iter()
+                .find(|token| token.kind == TokenKind::Identifier)
+        })
+        .map_or(node.span, |name| {
+            Span::from_range(
+                node.span.file(),
+                start.strict_add(name.span.start)..start.strict_add(name.span.end),
+            )
+        })
+}
+
+/// The definition spans of the names in scope at the cursor.
+fn names_in_scope(module: &ASTModule, cursor: Span) -> Vec<Span> {
+    /// Collects the names in scope in the nodes around the cursor.
+    struct Names {
+        /// The span of the cursor.
+        cursor: Span,
+
+        /// The definition spans of the names found so far.
+        found: Vec<Span>,
+    }
+
+    impl Names {
+        /// Adds the names `stmts` define that are in scope at the cursor.
+        ///
+        /// Functions are in scope throughout the block they're defined in,
+        /// while variables are only in scope after their declaration.
+        fn add_block(&mut self, stmts: &[ASTNode]) {
+            for stmt in stmts.iter().map(without_semi) {
+                match stmt.ty {
+                    ASTNodeType::FunDef { .. } => self.found.push(stmt.span),
+                    ASTNodeType::LetDecl { .. } if stmt.span.end() <= self.cursor.start() => {
+                        self.found.push(stmt.span);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    impl Visitor<'_> for Names {
+        fn visit_module(&mut self, module: &ASTModule) {
+            self.add_block(&module.nodes);
+            visit::walk_module(self, module);
+        }
+
+        fn visit_node(&mut self, node: &ASTNode) {
+            if !node.span.touches(&self.cursor) {
+                return;
+            }
+            match node.ty {
+                ASTNodeType::Block(ref stmts) => self.add_block(stmts),
+                ASTNodeType::FunDef { ref params, .. } => {
+                    self.found.extend(params.iter().map(|param| param.2));
+                }
+                _ => {}
+            }
+            visit::walk_node(self, node);
+        }
+    }
+
+    let mut names = Names {
+        cursor,
+        found: Vec::new(),
+    };
+    names.visit_module(module);
+    names.found
+}
+
+/// The path a `file://` URI refers to, decoding its percent-escapes.
+fn path_of_uri(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+        if let Some(escaped) = escaped {
+            bytes.push(escaped);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The innermost reference at the cursor.
+fn reference_at(analysis: &Analysis, cursor: Span) -> Option<&Reference> {
+    analysis
+        .references
+        .iter()
+        .filter(|reference| reference.span.touches(&cursor))
+        .min_by_key(|reference| reference.span.len())
+}
+
+/// Converts a diagnostic into the protocol's form.
+fn to_lsp_diagnostic(uri: &str, positions: Positions, diagnostic: &Diagnostic) -> Value {
+    let mut message = diagnostic.primary_err.clone();
+    if let Some(ref label) = diagnostic.primary_label {
+        message.push('\n');
+        message.push_str(label);
+    }
+    for child in &diagnostic.children {
+        // writing to a `String` can't fail
+        _ = write!(message, "\n{}: {}", child.severity.as_str(), child.message);
+    }
+
+    json!({
+        "range": positions.range(diagnostic.primary_span),
+        "severity": match diagnostic.severity {
+            Severity::Error => 1,
+            Severity::Help => 4,
+            Severity::Note => 3,
+            Severity::Warning => 2,
+        },
+        "code": diagnostic.code_or_lint(),
+        "source": "amaic",
+        "message": message,
+        "relatedInformation": diagnostic.secondary_labels.iter().map(|label| json!({
+            "location": { "uri": uri, "range": positions.range(label.span) },
+            "message": label.message.as_deref().unwrap_or_default(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// `node` without the `;` after it, if any.
+fn without_semi(node: &ASTNode) -> &ASTNode {
+    match node.ty {
+        ASTNodeType::Semi(ref inner) => inner,
+        _ => node,
+    }
+}
@@ -0,0 +1,80 @@
+//! Reads and writes JSON-RPC messages framed by `Content-Length` headers, as
+//! the Language Server Protocol sends them over stdio.
+
+use std::io::{self, BufRead, Write};
+
+use anyhow::{Context as _, bail};
+use serde_json::{Value, json};
+
+/// The error code for a request that can't be handled in the server's state.
+pub const INVALID_REQUEST: i64 = -32600;
+
+/// The error code for a request whose method isn't supported.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The error code for a request whose parameters are malformed.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// The error code for a request received before `initialize`.
+pub const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+/// A failed response to the request `id`.
+pub fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// A notification, which expects no response.
+pub fn notification(method: &str, params: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// Reads the next message, or `None` once the input is closed.
+pub fn read(reader: &mut impl BufRead) -> anyhow::Result<Option<Value>> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .context("Invalid `Content-Length` header")?,
+            );
+        }
+    }
+
+    let Some(length) = length else {
+        bail!("Message is missing a `Content-Length` header");
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(
+        serde_json::from_slice(&body).context("Message isn't valid JSON")?,
+    ))
+}
+
+/// A successful response to the request `id`.
+pub fn response(id: &Value, result: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Writes a message.
+pub fn write(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
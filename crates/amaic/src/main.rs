@@ -1,12 +1,54 @@
 //! The CLI for the Amai compiler.
 
+#![allow(
+    clippy::print_stderr,
+    clippy::print_stdout,
+    reason = "The CLI's output is printed."
+)]
+
+mod driver;
+mod emitter;
+mod explain;
+mod fix;
+mod fmt;
+mod lsp;
 mod parser;
 
+use std::process;
+
+use amaic_core::Diagnostic;
 use anyhow::Ok;
 use clap::Parser as _;
+use parser::AmaicCommand;
 
 fn main() -> anyhow::Result<()> {
     let args = parser::AmaiParser::parse();
-    _ = args;
+    emitter::set_color(args.color);
+    match args.command {
+        AmaicCommand::Check { file, lints } => {
+            let checked = driver::check(&file, &lints.levels())?;
+            emitter::emit(args.error_format, &checked.sources, &checked.diagnostics);
+            if checked.diagnostics.iter().any(Diagnostic::is_error) {
+                process::exit(1);
+            }
+        }
+        AmaicCommand::Explain { code } => explain::explain(&code)?,
+        AmaicCommand::Fix {
+            dry_run,
+            file,
+            lints,
+        } => {
+            if !fix::fix(&file, &lints.levels(), dry_run, args.error_format)? {
+                process::exit(1);
+            }
+        }
+        AmaicCommand::Fmt { check, files } => {
+            if !fmt::fmt(&files, check, args.error_format)? {
+                process::exit(1);
+            }
+        }
+        AmaicCommand::Lsp => lsp::run()?,
+        AmaicCommand::Inspect { .. } | AmaicCommand::Run { .. } => {}
+    }
     Ok(())
 }
@@ -2,7 +2,8 @@
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand, ValueHint};
+use amaic_analyzer::{Lint, LintLevel};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
 
 /// Parses command-line arguments into commands understood by the
 /// Amai compiler.
@@ -13,14 +14,95 @@ use clap::{Parser, Subcommand, ValueHint};
     version
 )]
 pub struct AmaiParser {
+    /// When to color human-readable diagnostics.
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub color: ColorChoice,
+
     /// The command to execute.
     #[command(subcommand)]
-    command: AmaicCommand,
+    pub command: AmaicCommand,
+
+    /// How to print diagnostics.
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub error_format: ErrorFormat,
+}
+
+/// When output is colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Always color.
+    Always,
+
+    /// Color when printing to a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+
+    /// Never color.
+    Never,
+}
+
+/// The format diagnostics are printed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// Human-readable, with source snippets.
+    #[default]
+    Human,
+
+    /// One JSON object per diagnostic, each on its own line.
+    Json,
+
+    /// A single SARIF 2.1.0 log.
+    Sarif,
 }
 
 /// A possible subcommand that can be run by Amaic.
 #[derive(Clone, Debug, Subcommand)]
 pub enum AmaicCommand {
+    /// Checks a file for errors without running it.
+    Check {
+        /// The file to check.
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// The levels to report lints at.
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+
+    /// Prints a detailed explanation of an error code.
+    Explain {
+        /// The error code to explain, such as `E0004`.
+        code: String,
+    },
+
+    /// Applies the machine-applicable suggestions of a file's diagnostics in
+    /// place.
+    Fix {
+        /// Print the fixed file instead of writing it.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// The file to fix.
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// The levels to report lints at.
+        #[command(flatten)]
+        lints: LintArgs,
+    },
+
+    /// Formats files in place.
+    Fmt {
+        /// Only check that the files are formatted, failing if any aren't,
+        /// without writing to them.
+        #[arg(long)]
+        check: bool,
+
+        /// The files to format.
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+    },
+
     /// Inspects the output at various stages of the compilation process.
     Inspect {
         /// Inspect the lexed output.
@@ -40,6 +122,9 @@ pub enum AmaicCommand {
         file: PathBuf,
     },
 
+    /// Runs a language server over stdio, for editor integration.
+    Lsp,
+
     /// Compiles and runs a given file.
     Run {
         /// The file to compile and run.
@@ -47,3 +132,38 @@ pub enum AmaicCommand {
         file: PathBuf,
     },
 }
+
+/// Overrides of the levels lints are reported at.
+#[derive(Clone, Debug, Args)]
+pub struct LintArgs {
+    /// Don't report a lint.
+    #[arg(short = 'A', long = "allow", value_name = "LINT", value_parser = parse_lint)]
+    pub allow: Vec<Lint>,
+
+    /// Report a lint as an error.
+    #[arg(short = 'D', long = "deny", value_name = "LINT", value_parser = parse_lint)]
+    pub deny: Vec<Lint>,
+
+    /// Report a lint as a warning.
+    #[arg(short = 'W', long = "warn", value_name = "LINT", value_parser = parse_lint)]
+    pub warn: Vec<Lint>,
+}
+
+impl LintArgs {
+    /// The level of every lint given, in increasing order of precedence: a lint
+    /// that's both warned and denied is denied.
+    pub fn levels(&self) -> Vec<(Lint, LintLevel)> {
+        let allow = self.allow.iter().map(|&lint| (lint, LintLevel::Allow));
+        let warn = self.warn.iter().map(|&lint| (lint, LintLevel::Warn));
+        let deny = self.deny.iter().map(|&lint| (lint, LintLevel::Deny));
+        allow.chain(warn).chain(deny).collect()
+    }
+}
+
+/// Parses the name of a lint, listing every lint if it isn't one.
+fn parse_lint(name: &str) -> Result<Lint, String> {
+    Lint::from_name(name).ok_or_else(|| {
+        let names = Lint::ALL.map(Lint::name).join(", ");
+        format!("unknown lint, expected one of: {names}")
+    })
+}
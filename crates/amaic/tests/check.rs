@@ -0,0 +1,81 @@
+//! Runs `amaic check` on files with lints in them.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use std::{
+    env, fs,
+    process::{self, Command, Output},
+};
+
+const UNUSED: &str = "\
+let f(): int = {
+    let x = 1;
+    2
+};
+";
+
+/// Checks [`UNUSED`] with the given flags.
+fn check(flags: &[&str]) -> Output {
+    let path = env::temp_dir().join(format!(
+        "amaic-check-{}{}.amai",
+        process::id(),
+        flags.join("")
+    ));
+    fs::write(&path, UNUSED).expect("the temporary file should be writable");
+    let output = Command::new(env!("CARGO_BIN_EXE_amaic"))
+        .args(["--color", "never", "check"])
+        .args(flags)
+        .arg(&path)
+        .output()
+        .expect("amaic should run");
+    fs::remove_file(&path).expect("the temporary file should be removable");
+    output
+}
+
+/// What amaic printed to stderr.
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn lints_warn_by_default() {
+    let output = check(&[]);
+    assert!(output.status.success());
+    assert!(stderr(&output).starts_with("warning: Variable `x` is never used"));
+}
+
+#[test]
+fn allowed_lints_are_not_reported() {
+    let output = check(&["-A", "unused_variables"]);
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn denied_lints_fail_the_check() {
+    let output = check(&["--deny", "unused_variables"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).starts_with("error: Variable `x` is never used"));
+}
+
+#[test]
+fn deny_beats_warn_beats_allow() {
+    let denied = check(&["-D", "unused_variables", "-W", "unused_variables"]);
+    assert!(!denied.status.success());
+    let warned = check(&["-W", "unused_variables", "-A", "unused_variables"]);
+    assert!(stderr(&warned).starts_with("warning:"));
+}
+
+#[test]
+fn unknown_lints_are_rejected() {
+    let output = check(&["-W", "unused_everything"]);
+    assert_eq!(output.status.code(), Some(2_i32));
+    assert!(
+        stderr(&output).contains("unknown lint"),
+        "{}",
+        stderr(&output)
+    );
+}
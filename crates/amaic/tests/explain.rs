@@ -0,0 +1,45 @@
+//! Runs `amaic explain` on error codes.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use std::process::{Command, Output};
+
+/// Explains `code`.
+fn explain(code: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_amaic"))
+        .args(["explain", code])
+        .output()
+        .expect("amaic should run")
+}
+
+#[test]
+fn prints_the_title_and_explanation() {
+    let output = explain("E0004");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("E0004: Mismatched types\n\nA value's type differs"),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with("```\n"), "{stdout}");
+}
+
+#[test]
+fn accepts_abbreviated_codes() {
+    let full = explain("E0004").stdout;
+    assert_eq!(explain("e4").stdout, full);
+    assert_eq!(explain("0004").stdout, full);
+}
+
+#[test]
+fn rejects_unknown_codes() {
+    for code in ["E9999", "nonsense"] {
+        let output = explain(code);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("is not a valid error code"), "{stderr}");
+    }
+}
@@ -0,0 +1,86 @@
+//! Runs `amaic fix` on temporary files.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use std::{
+    env, fs,
+    process::{self, Command, Output},
+};
+
+/// Writes `source` to a temporary file named after `test`, fixes it with the
+/// given flags, and returns amaic's output along with what the file holds
+/// afterwards.
+fn fix(test: &str, source: &str, flags: &[&str]) -> (Output, String) {
+    let path = env::temp_dir().join(format!("amaic-fix-{}-{test}.amai", process::id()));
+    fs::write(&path, source).expect("the temporary file should be writable");
+    let output = Command::new(env!("CARGO_BIN_EXE_amaic"))
+        .args(["--color", "never", "fix"])
+        .args(flags)
+        .arg(&path)
+        .output()
+        .expect("amaic should run");
+    let fixed = fs::read_to_string(&path).expect("the temporary file should be readable");
+    fs::remove_file(&path).expect("the temporary file should be removable");
+    (output, fixed)
+}
+
+/// What amaic printed to stderr.
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn applies_fixes_in_place() {
+    let (output, fixed) = fix(
+        "in-place",
+        "let f(count: float): float = { let x: float = 1; x + Count };\n",
+        &[],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fixed,
+        "let f(count: float): float = { let x: float = 1.0; x + count };\n"
+    );
+    assert!(stderr(&output).starts_with("Applied 2 fixes to "));
+}
+
+#[test]
+fn remaining_errors_fail() {
+    let source = "let f(count: int): int = Count + nope;\n";
+    let (output, fixed) = fix("remaining", source, &[]);
+    assert!(!output.status.success());
+    assert_eq!(fixed, "let f(count: int): int = count + nope;\n");
+    assert!(stderr(&output).contains("error[E0003]: Couldn't find variable `nope` in scope"));
+}
+
+#[test]
+fn dry_runs_print_the_fixes_without_writing_them() {
+    let source = "let f(count: int): int = Count;\n";
+    let (output, fixed) = fix("dry-run", source, &["--dry-run"]);
+    assert!(output.status.success());
+    assert_eq!(fixed, source);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "let f(count: int): int = count;\n"
+    );
+    assert!(stderr(&output).starts_with("Would apply 1 fix to "));
+}
+
+#[test]
+fn lint_levels_apply() {
+    let source = "let f(count: int): int = { let x = 1; Count };\n";
+    let fixed_source = "let f(count: int): int = { let x = 1; count };\n";
+
+    let (allowed, allowed_fix) = fix("allow", source, &["-A", "unused_variables"]);
+    assert!(allowed.status.success());
+    assert_eq!(allowed_fix, fixed_source);
+    assert!(!stderr(&allowed).contains("never used"));
+
+    let (denied, denied_fix) = fix("deny", source, &["-D", "unused_variables"]);
+    assert!(!denied.status.success());
+    assert_eq!(denied_fix, fixed_source);
+    assert!(stderr(&denied).contains("error: Variable `x` is never used"));
+}
@@ -0,0 +1,394 @@
+//! Drives `amaic lsp` over stdio the way an editor would.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use std::{
+    io::{BufRead as _, BufReader, Read as _, Write as _},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{Value, json};
+
+/// The URI the test document is opened at.
+const URI: &str = "file:///test.amai";
+
+/// The test document.
+const SOURCE: &str = "\
+/// Adds one.
+let inc(n: int): int = n + 1;
+let main(): int = {
+    let x = inc(1);
+    x
+};
+";
+
+/// A client talking to a language server in another process.
+struct Client {
+    /// The id of the next request.
+    next_id: u64,
+
+    /// The server process.
+    server: Child,
+
+    /// Where messages to the server are written.
+    stdin: ChildStdin,
+
+    /// Where messages from the server are read.
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Client {
+    /// Sends a request about the document at a position.
+    fn at(&mut self, method: &str, line: u64, character: u64) -> Value {
+        self.request(
+            method,
+            &json!({
+                "textDocument": { "uri": URI },
+                "position": { "line": line, "character": character },
+            }),
+        )
+    }
+
+    /// Reads the next notification, which should publish the document's
+    /// diagnostics.
+    fn diagnostics(&mut self) -> Value {
+        let notification = self.read();
+        assert_eq!(
+            notification["method"], "textDocument/publishDiagnostics",
+            "the server should publish diagnostics"
+        );
+        assert_eq!(
+            notification["params"]["uri"], URI,
+            "the diagnostics should be for the document"
+        );
+        notification["params"]["diagnostics"].clone()
+    }
+
+    /// Has the server exit, returning whether it exited successfully.
+    fn exit(mut self) -> bool {
+        self.notify("exit", &Value::Null);
+        self.server
+            .wait()
+            .expect("the server should exit")
+            .success()
+    }
+
+    /// Sends a notification.
+    fn notify(&mut self, method: &str, params: &Value) {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Opens the document, returning the diagnostics published for it.
+    fn open(&mut self, text: &str) -> Value {
+        self.notify(
+            "textDocument/didOpen",
+            &json!({
+                "textDocument": { "uri": URI, "languageId": "amai", "version": 1_i32, "text": text },
+            }),
+        );
+        self.diagnostics()
+    }
+
+    /// Reads the next message from the server.
+    fn read(&mut self) -> Value {
+        let mut length = None;
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .expect("the server's output should be readable");
+            assert_ne!(read, 0, "server hung up");
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse().expect("the length should be a number"));
+            }
+        }
+        let mut body = vec![0; length.expect("missing Content-Length")];
+        self.stdout
+            .read_exact(&mut body)
+            .expect("the server should send the whole message");
+        serde_json::from_slice(&body).expect("the message should be JSON")
+    }
+
+    /// Sends a request and returns the whole response.
+    fn request(&mut self, method: &str, params: &Value) -> Value {
+        self.next_id = self.next_id.strict_add(1);
+        let id = self.next_id;
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        let response = self.read();
+        assert_eq!(response["id"], id, "the response should answer the request");
+        response
+    }
+
+    /// Sends a message.
+    fn send(&mut self, message: &Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .expect("the server should accept messages");
+        self.stdin
+            .flush()
+            .expect("the server should accept messages");
+    }
+
+    /// Shuts the server down and has it exit, returning whether it exited
+    /// successfully.
+    fn shut_down(mut self) -> bool {
+        assert_eq!(
+            self.request("shutdown", &Value::Null)["result"],
+            Value::Null,
+            "the server should shut down"
+        );
+        self.exit()
+    }
+
+    /// Spawns a server.
+    fn spawn() -> Self {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_amaic"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("the server should start");
+        let stdin = server.stdin.take().expect("stdin should be piped");
+        let stdout = BufReader::new(server.stdout.take().expect("stdout should be piped"));
+        Self {
+            next_id: 0,
+            server,
+            stdin,
+            stdout,
+        }
+    }
+
+    /// Spawns a server, initializes it and opens [`SOURCE`] in it.
+    fn with_document() -> Self {
+        let mut client = Self::spawn();
+        client.request("initialize", &json!({ "capabilities": {} }));
+        client.notify("initialized", &json!({}));
+        client.open(SOURCE);
+        client
+    }
+}
+
+/// A protocol range from `(line, character)` pairs.
+fn range(start: (u64, u64), end: (u64, u64)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+#[test]
+fn initialize_advertises_capabilities() {
+    let mut client = Client::spawn();
+    let response = client.request(
+        "initialize",
+        &json!({ "capabilities": { "general": { "positionEncodings": ["utf-8", "utf-16"] } } }),
+    );
+    let capabilities = &response["result"]["capabilities"];
+    assert_eq!(capabilities["positionEncoding"], "utf-8");
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["documentSymbolProvider"], true);
+    assert_eq!(response["result"]["serverInfo"]["name"], "amaic");
+    assert!(client.shut_down());
+}
+
+#[test]
+fn requests_before_initialize_are_rejected() {
+    let mut client = Client::spawn();
+    let response = client.request("textDocument/hover", &json!({}));
+    assert_eq!(response["error"]["code"], -32002_i32);
+    client.request("initialize", &json!({ "capabilities": {} }));
+    assert!(client.shut_down());
+}
+
+#[test]
+fn diagnostics_follow_changes() {
+    let mut client = Client::spawn();
+    client.request("initialize", &json!({ "capabilities": {} }));
+    assert_eq!(client.open(SOURCE), json!([]));
+
+    // `x` -> `y`, which isn't in scope
+    client.notify(
+        "textDocument/didChange",
+        &json!({
+            "textDocument": { "uri": URI, "version": 2_i32 },
+            "contentChanges": [{ "range": range((4, 4), (4, 5)), "text": "y" }],
+        }),
+    );
+    let diagnostics = client.diagnostics();
+    let severities: Vec<_> = diagnostics
+        .as_array()
+        .expect("diagnostics should be a list")
+        .iter()
+        .filter_map(|diagnostic| diagnostic["severity"].as_u64())
+        .collect();
+    // `x` is now unused, and `y` isn't in scope
+    assert_eq!(severities, [2, 1]);
+    assert_eq!(diagnostics[0]["range"], range((3, 4), (3, 18)));
+    assert_eq!(diagnostics[1]["code"], "E0003");
+    assert_eq!(diagnostics[1]["range"], range((4, 4), (4, 5)));
+
+    // and back, replacing the whole document
+    client.notify(
+        "textDocument/didChange",
+        &json!({
+            "textDocument": { "uri": URI, "version": 3_i32 },
+            "contentChanges": [{ "text": SOURCE }],
+        }),
+    );
+    assert_eq!(client.diagnostics(), json!([]));
+    assert!(client.shut_down());
+}
+
+#[test]
+fn initialization_options_set_lint_levels() {
+    let unused = "let f(): int = {\n    let x = 1;\n    2\n};\n";
+    let mut denying = Client::spawn();
+    denying.request(
+        "initialize",
+        &json!({
+            "capabilities": {},
+            "initializationOptions": {
+                "lints": { "unused_variables": "deny", "unknown_lint": "allow" },
+            },
+        }),
+    );
+    let diagnostics = denying.open(unused);
+    assert_eq!(diagnostics.as_array().map(Vec::len), Some(1));
+    assert_eq!(diagnostics[0]["severity"], 1_i32);
+    assert!(denying.shut_down());
+
+    let mut allowing = Client::spawn();
+    allowing.request(
+        "initialize",
+        &json!({
+            "capabilities": {},
+            "initializationOptions": { "lints": { "unused_variables": "allow" } },
+        }),
+    );
+    assert_eq!(allowing.open(unused), json!([]));
+    assert!(allowing.shut_down());
+}
+
+#[test]
+fn hover_shows_the_type_and_docs() {
+    let mut client = Client::with_document();
+    let hover = client.at("textDocument/hover", 3, 13);
+    let contents = hover["result"]["contents"]["value"]
+        .as_str()
+        .expect("the hover should have contents");
+    assert!(contents.contains("inc: "), "{contents}");
+    assert!(contents.contains("Adds one."), "{contents}");
+    assert_eq!(hover["result"]["range"], range((3, 12), (3, 18)));
+
+    // the doc comment isn't a reference to anything
+    assert_eq!(client.at("textDocument/hover", 0, 4)["result"], Value::Null);
+    assert!(client.shut_down());
+}
+
+#[test]
+fn definition_points_at_the_declaration() {
+    let mut client = Client::with_document();
+    let definition = client.at("textDocument/definition", 4, 4);
+    assert_eq!(definition["result"]["uri"], URI);
+    assert_eq!(
+        definition["result"]["range"]["start"],
+        json!({ "line": 3_i32, "character": 4_i32 })
+    );
+    assert!(client.shut_down());
+}
+
+#[test]
+fn completion_lists_names_in_scope_and_keywords() {
+    let mut client = Client::with_document();
+    let completion = client.at("textDocument/completion", 4, 4);
+    let labels: Vec<_> = completion["result"]
+        .as_array()
+        .expect("completions should be a list")
+        .iter()
+        .filter_map(|item| item["label"].as_str())
+        .collect();
+    for label in ["inc", "main", "x", "let", "if"] {
+        assert!(
+            labels.contains(&label),
+            "{label} is missing from {labels:?}"
+        );
+    }
+    // `inc`'s parameter isn't in scope in `main`
+    assert!(!labels.contains(&"n"), "{labels:?}");
+    assert!(client.shut_down());
+}
+
+#[test]
+fn document_symbols_select_the_name() {
+    let mut client = Client::with_document();
+    let response = client.request(
+        "textDocument/documentSymbol",
+        &json!({ "textDocument": { "uri": URI } }),
+    );
+    let symbols = response["result"]
+        .as_array()
+        .expect("symbols should be a list");
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0]["name"], "inc");
+    assert_eq!(symbols[0]["range"], range((1, 0), (1, 28)));
+    assert_eq!(symbols[0]["selectionRange"], range((1, 4), (1, 7)));
+    assert_eq!(symbols[1]["name"], "main");
+    assert_eq!(symbols[1]["selectionRange"], range((2, 4), (2, 8)));
+    assert!(client.shut_down());
+}
+
+#[test]
+fn document_symbols_nest_functions() {
+    let mut client = Client::spawn();
+    client.request("initialize", &json!({ "capabilities": {} }));
+    client.open("let outer(): int = {\n    let inner(): int = 1;\n    inner()\n};\n");
+    let response = client.request(
+        "textDocument/documentSymbol",
+        &json!({ "textDocument": { "uri": URI } }),
+    );
+    let symbols = response["result"]
+        .as_array()
+        .expect("symbols should be a list");
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0]["name"], "outer");
+    let children = symbols[0]["children"]
+        .as_array()
+        .expect("children should be a list");
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0]["name"], "inner");
+    assert_eq!(children[0]["selectionRange"], range((1, 8), (1, 13)));
+    assert_eq!(children[0]["children"], json!([]));
+    assert!(client.shut_down());
+}
+
+#[test]
+fn requests_after_shutdown_are_rejected() {
+    let mut client = Client::with_document();
+    assert_eq!(
+        client.request("shutdown", &Value::Null)["result"],
+        Value::Null
+    );
+    let hover = client.at("textDocument/hover", 3, 13);
+    assert_eq!(hover["error"]["code"], -32600_i32);
+    assert_eq!(
+        client.request("shutdown", &Value::Null)["error"]["code"],
+        -32600_i32
+    );
+    assert!(client.exit());
+}
+
+#[test]
+fn exiting_without_shutting_down_fails() {
+    let client = Client::with_document();
+    assert!(!client.exit());
+}
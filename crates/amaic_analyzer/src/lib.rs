@@ -1,57 +1,295 @@
-use amaic_ast::*;
-use amaic_core::{Diagnostic, Span};
-use amaic_lexer::Operator;
+//! The semantic checker, which resolves names and types in an [`ASTModule`].
 
-use std::{collections::HashMap, path::PathBuf};
+mod lint;
+mod suggest;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use amaic_ast::{
+    ASTModule, ASTNode, ASTNodeType, FrontendType, FrontendTypeType, InterpolationPart, Type,
+    Visitor, visit,
+};
+use amaic_core::{Applicability, Diagnostic, Span, error_codes};
+use amaic_lexer::{Operator, ascii_lookalike, confusable};
+
+use std::{collections::HashMap, mem};
+
+pub use lint::{Lint, LintLevel};
+
+/// Where the node being checked is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Context {
-    Root,
+    /// Inside a function's body.
     FunctionDecl,
+
+    /// At the top level of a file, where only declarations may be.
+    Root,
+}
+
+/// What defined a [`Symbol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SymbolKind {
+    /// A function definition.
+    Function,
+
+    /// A function's parameter.
+    Parameter,
+
+    /// A variable declaration.
+    Variable,
 }
 
+/// A place a symbol is named, either where it's defined or where it's used.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Reference {
+    /// The name of the symbol.
+    pub name: String,
+
+    /// Where the name is.
+    pub span: Span,
+
+    /// The symbol, as it was when it was named.
+    pub symbol: Symbol,
+}
+
+/// A variable or function in scope.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct Symbol {
-    pub ty: Type,
+    /// Where the symbol was defined.
+    pub defined_at: Span,
+
+    /// Whether the symbol was declared without a value.
     pub is_unitialized: bool,
-    defined_at: Span,
+
+    /// What defined the symbol.
+    pub kind: SymbolKind,
+
+    /// The symbol's type.
+    pub ty: Type,
+
+    /// Whether the symbol was referred to.
+    pub used: bool,
 }
 
+/// Checks that a module's names resolve and its types line up, recording the
+/// type of each operation in the tree.
 pub struct SemanticChecker {
-    path: PathBuf,
+    /// Where the node being checked is.
+    context: Context,
+
+    /// The problems found so far.
+    diagnostics: Vec<Diagnostic>,
+
+    /// The levels set for lints, which otherwise have their default level.
+    lint_levels: HashMap<Lint, LintLevel>,
+
+    /// Every place a symbol was defined or used, in the order they were
+    /// checked.
+    references: Vec<Reference>,
+
+    /// The scopes, innermost last.
     symbols: Vec<HashMap<String, Symbol>>,
+
+    /// The types that can be named.
     type_registry: HashMap<String, Type>,
-    context: Context,
 }
 
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "Symbol handling comes first, then the checks that use it."
+)]
 impl SemanticChecker {
-    pub fn new(path: PathBuf) -> Self {
-        let mut t = SemanticChecker {
-            path,
+    /// Creates a checker with the built-in types in scope.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut checker = Self {
+            context: Context::Root,
+            diagnostics: Vec::new(),
+            lint_levels: HashMap::new(),
+            references: Vec::new(),
             symbols: vec![HashMap::new()],
             type_registry: HashMap::new(),
-            context: Context::Root,
         };
 
-        t.type_registry.insert("int".to_string(), Type::Int);
-        t.type_registry.insert("float".to_string(), Type::Float);
-        t.type_registry.insert("string".to_string(), Type::String);
-        t.type_registry.insert("bool".to_string(), Type::Bool);
+        checker.type_registry.insert("int".to_owned(), Type::Int);
+        checker
+            .type_registry
+            .insert("float".to_owned(), Type::Float);
+        checker
+            .type_registry
+            .insert("string".to_owned(), Type::String);
+        checker.type_registry.insert("bool".to_owned(), Type::Bool);
 
-        t
+        checker
     }
 
-    pub fn define_symbol(&mut self, name: &str, ty: Type, is_unitialized: bool, defined_at: Span) {
-        self.symbols.last_mut().unwrap().insert(
-            name.to_string(),
-            Symbol {
-                ty,
-                is_unitialized,
-                defined_at,
-            },
+    /// Runs `check` inside a fresh lexical scope. The scope is popped however
+    /// `check` returns, so an error inside a block never leaks its symbols
+    /// outwards.
+    ///
+    /// Symbols defined in the new scope shadow those of enclosing scopes, and
+    /// redefining a name within the same scope shadows the previous definition.
+    pub fn scoped<T>(&mut self, check: impl FnOnce(&mut Self) -> T) -> T {
+        self.symbols.push(HashMap::new());
+        let result = check(self);
+        self.pop_scope();
+        result
+    }
+
+    /// Pops the innermost scope, linting the symbols in it that were never used.
+    ///
+    /// The root scope is never popped, since its functions are the module's API.
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.symbols.pop() else {
+            return;
+        };
+        let mut unused = scope
+            .into_iter()
+            .filter(|entry| !entry.1.used)
+            .collect::<Vec<_>>();
+        unused.sort_by_key(|entry| entry.1.defined_at.start());
+        for (name, symbol) in unused {
+            self.lint_unused(&name, &symbol);
+        }
+    }
+
+    /// Every place a symbol was defined or used in the validated module, in the
+    /// order they were validated.
+    #[must_use]
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// Records that `symbol`, called `name`, is named at `span`.
+    fn record_reference(&mut self, name: &str, span: Span, symbol: Symbol) {
+        self.references.push(Reference {
+            name: name.to_owned(),
+            span,
+            symbol,
+        });
+    }
+
+    /// Sets the level that `lint` is reported at.
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lint_levels.insert(lint, level);
+    }
+
+    /// Reports `diagnostic` at the level set for `lint`.
+    fn lint(&mut self, lint: Lint, diagnostic: Diagnostic) {
+        let level = self
+            .lint_levels
+            .get(&lint)
+            .copied()
+            .unwrap_or_else(|| lint.default_level());
+        let diagnostic = diagnostic.with_lint(lint.name()).with_note(format!(
+            "`{}` is set to `{}`",
+            lint.name(),
+            level.name()
+        ));
+        if let Some(linted) = level.apply(diagnostic) {
+            self.diagnostics.push(linted);
+        }
+    }
+
+    /// Lints a name that's made up of ASCII and characters that look like ASCII,
+    /// since it's easily mistaken for the ASCII name it looks like.
+    fn lint_confusable(&mut self, name: &str, defined_at: Span) {
+        let Some(lookalike) = ascii_lookalike(name) else {
+            return;
+        };
+        let mut diagnostic = Diagnostic::new(
+            format!("`{name}` looks like `{lookalike}`, but isn't written in ASCII"),
+            defined_at,
         );
+        for ch in name.chars().filter(|ch| !ch.is_ascii()) {
+            if let Some(ascii) = confusable(ch) {
+                diagnostic = diagnostic.with_note(format!(
+                    "{ch:?} (U+{:04X}) is easily confused with {ascii:?}",
+                    u32::from(ch)
+                ));
+            }
+        }
+        self.lint(Lint::ConfusableIdents, diagnostic);
     }
 
+    /// Reports that `symbol`, called `name`, is never used, unless its name
+    /// starts with `_`.
+    fn lint_unused(&mut self, name: &str, symbol: &Symbol) {
+        if name.starts_with('_') {
+            return;
+        }
+        let (lint, what) = match symbol.kind {
+            SymbolKind::Function => (Lint::UnusedFunctions, "Function"),
+            SymbolKind::Parameter => (Lint::UnusedParameters, "Parameter"),
+            SymbolKind::Variable => (Lint::UnusedVariables, "Variable"),
+        };
+        self.lint(
+            lint,
+            Diagnostic::new(format!("{what} `{name}` is never used"), symbol.defined_at).with_help(
+                format!("if this is intentional, prefix it with an underscore: `_{name}`"),
+            ),
+        );
+    }
+
+    /// Defines `name` in the innermost scope, shadowing any definition of it
+    /// there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no scope, which can't happen as the root scope is
+    /// never popped.
+    pub fn define_symbol(
+        &mut self,
+        name: &str,
+        kind: SymbolKind,
+        ty: Type,
+        is_unitialized: bool,
+        defined_at: Span,
+    ) {
+        self.lint_confusable(name, defined_at);
+        let symbol = Symbol {
+            defined_at,
+            is_unitialized,
+            kind,
+            ty,
+            used: false,
+        };
+        self.record_reference(name, defined_at, symbol.clone());
+        let previous = self
+            .symbols
+            .last_mut()
+            .expect("the root scope should never be popped")
+            .insert(name.to_owned(), symbol);
+
+        if let Some(shadowed) = previous {
+            if !shadowed.used {
+                self.lint_unused(name, &shadowed);
+            }
+            self.lint(
+                Lint::SameScopeShadowing,
+                Diagnostic::new(
+                    format!("`{name}` shadows an earlier definition in the same scope"),
+                    defined_at,
+                )
+                .with_secondary_label(Some("previously defined here"), shadowed.defined_at),
+            );
+        }
+    }
+
+    /// Looks `name` up, from the innermost scope outwards, without marking it
+    /// as used.
+    fn lookup_symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Records that `name` was assigned a value of type `ty`, which gives it
+    /// that type if it didn't have one yet.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `name` isn't in scope or has a different type.
     pub fn mutate_symbol(&mut self, name: &str, ty: &Type, span: Span) -> Result<(), Diagnostic> {
         for scope in self.symbols.iter_mut().rev() {
             if let Some(symbol) = scope.get_mut(name) {
@@ -59,9 +297,8 @@ impl SemanticChecker {
                     symbol.ty = ty.clone();
                     symbol.is_unitialized = false;
                 }
-                if symbol.ty != *ty {
+                if symbol.ty != *ty && *ty != Type::Error && symbol.ty != Type::Error {
                     return Err(Diagnostic::new(
-                        self.path.display(),
                         format!(
                             "Variable `{name}` is defined as `{}` but found `{}`",
                             symbol.ty.display(),
@@ -69,230 +306,381 @@ impl SemanticChecker {
                         ),
                         span,
                     )
-                    .with_secondary_message(
-                        Some("Variable was defined here:"),
-                        symbol.defined_at.clone(),
-                    ));
+                    .with_code(error_codes::E0004)
+                    .with_secondary_label(Some("variable was defined here"), symbol.defined_at));
                 }
                 return Ok(());
             }
         }
 
-        return Err(Diagnostic::new(
-            self.path.display(),
-            format!("Couldn't find variable `{name}` in scope"),
-            span,
-        ));
+        Err(self.unknown_variable(name, span))
     }
 
+    /// Looks `name` up, from the innermost scope outwards, marking it as used.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `name` isn't in scope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scope found to contain `name` doesn't, which can't happen.
     pub fn find_symbol(&mut self, name: &str, span: Span) -> Result<&Symbol, Diagnostic> {
-        for scope in self.symbols.iter().rev() {
-            if let Some(symbol) = scope.get(name) {
-                return Ok(symbol);
-            }
-        }
+        let Some(scope) = self
+            .symbols
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+        else {
+            return Err(self.unknown_variable(name, span));
+        };
+
+        let symbol = self.symbols[scope]
+            .get_mut(name)
+            .expect("the scope was found to contain `name`");
+        symbol.used = true;
+        let symbol = symbol.clone();
+        self.record_reference(name, span, symbol);
+        Ok(&self.symbols[scope][name])
+    }
+
+    /// The error for `name` not being in scope, suggesting a variable whose
+    /// name only differs in case.
+    fn unknown_variable(&self, name: &str, span: Span) -> Diagnostic {
+        let diagnostic = Diagnostic::new(format!("Couldn't find variable `{name}` in scope"), span)
+            .with_code(error_codes::E0003);
+
+        let keywords = amaic_parser::KEYWORDS.map(|keyword| keyword.to_string());
+        let variables = self.symbols.iter().flat_map(HashMap::keys);
+        let Some(similar) =
+            suggest::find_similar(name, variables.chain(&keywords).map(String::as_str))
+        else {
+            return diagnostic;
+        };
 
-        return Err(Diagnostic::new(
-            self.path.display(),
-            format!("Couldn't find variable `{name}` in scope"),
+        let what = if keywords.iter().any(|keyword| keyword == similar) {
+            "keyword"
+        } else {
+            "variable"
+        };
+        // a name differing only in case is almost certainly what was meant
+        let applicability = if similar.eq_ignore_ascii_case(name) {
+            Applicability::MachineApplicable
+        } else {
+            Applicability::MaybeIncorrect
+        };
+        diagnostic.with_suggestion(
+            format!("a {what} with a similar name exists: `{similar}`"),
             span,
-        ));
+            similar,
+            applicability,
+        )
     }
 
+    /// Resolves a written type.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the type, or a type inside it, doesn't exist.
     pub fn resolve_type(&self, ftype: &FrontendType) -> Result<Type, Diagnostic> {
-        match &ftype.ty {
-            FrontendTypeType::Identifier(ident) => {
-                self.type_registry
-                    .get(ident)
-                    .cloned()
-                    .ok_or(Diagnostic::new(
-                        self.path.display(),
-                        format!("Cannot find type `{}`", ident),
-                        ftype.span,
-                    ))
+        match ftype.ty {
+            FrontendTypeType::Identifier(ref ident) => {
+                self.type_registry.get(ident).cloned().ok_or_else(|| {
+                    let diagnostic =
+                        Diagnostic::new(format!("Cannot find type `{ident}`"), ftype.span)
+                            .with_code(error_codes::E0005);
+
+                    let types = self.type_registry.keys().map(String::as_str);
+                    match suggest::find_similar(ident, types) {
+                        Some(similar) => diagnostic.with_suggestion(
+                            format!("a type with a similar name exists: `{similar}`"),
+                            ftype.span,
+                            similar,
+                            Applicability::MaybeIncorrect,
+                        ),
+                        None => diagnostic,
+                    }
+                })
             }
             FrontendTypeType::Unit => Ok(Type::Unit),
-            FrontendTypeType::Vector(vec) => Ok(Type::Vector(Box::new(self.resolve_type(vec)?))),
+            FrontendTypeType::Vector(ref vec) => {
+                Ok(Type::Vector(Box::new(self.resolve_type(vec)?)))
+            }
         }
     }
 
-    fn collect_function(&mut self, node: &ASTNode) -> Result<(), Diagnostic> {
-        match &node.ty {
-            ASTNodeType::FunDef {
-                name,
-                params,
-                return_ty,
-                body: _,
-            } => {
-                let mut params_ty = Vec::new();
-                for (_, ty, _) in params {
-                    params_ty.push(self.resolve_type(ty)?);
-                }
-                let return_ty = match return_ty {
-                    Some(ty) => Box::new(self.resolve_type(ty)?),
-                    None => Box::new(Type::Unit),
-                };
-                self.define_symbol(name, Type::Func(params_ty, return_ty), false, node.span);
+    /// The type of a function with the given parameters and return type.
+    fn function_type(
+        &self,
+        params: &[(String, FrontendType, Span)],
+        return_ty: Option<&FrontendType>,
+    ) -> Type {
+        // Unresolvable types are reported when the definition itself is validated,
+        // so they're only poisoned here.
+        let params_ty = params
+            .iter()
+            .map(|param| self.resolve_type(&param.1).unwrap_or(Type::Error))
+            .collect();
+        let return_ty = return_ty.map_or(Type::Unit, |ty| {
+            self.resolve_type(ty).unwrap_or(Type::Error)
+        });
+        Type::Func(params_ty, Box::new(return_ty))
+    }
+
+    /// Defines the function that `node` is, if it's one.
+    fn collect_function(&mut self, node: &ASTNode) {
+        if let ASTNodeType::FunDef {
+            ref name,
+            ref params,
+            ref return_ty,
+            ..
+        } = node.ty
+        {
+            let already_collected = self
+                .symbols
+                .last()
+                .and_then(|scope| scope.get(name))
+                .is_some_and(|symbol| symbol.defined_at == node.span);
+            if already_collected {
+                return;
             }
-            _ => {}
+            let ty = self.function_type(params, return_ty.as_ref());
+            self.define_symbol(name, SymbolKind::Function, ty, false, node.span);
         }
-
-        Ok(())
     }
-    fn collect_function_deep(&mut self, node: &ASTNode) -> Result<(), Diagnostic> {
-        match &node.ty {
-            ASTNodeType::FunDef {
-                name,
-                params,
-                return_ty,
-                body: _,
-            } => {
-                let mut params_ty = Vec::new();
-                for (_, ty, _) in params {
-                    params_ty.push(self.resolve_type(ty)?);
+
+    /// Defines the function that `node` is, if it's one, looking through `;`.
+    fn collect_function_deep(&mut self, node: &ASTNode) {
+        /// Finds the function definitions among the nodes it visits, looking
+        /// through `;`s but not inside anything else.
+        struct Functions<'ast>(Vec<&'ast ASTNode>);
+
+        impl<'ast> Visitor<'ast> for Functions<'ast> {
+            fn visit_node(&mut self, node: &'ast ASTNode) {
+                match node.ty {
+                    ASTNodeType::FunDef { .. } => self.0.push(node),
+                    ASTNodeType::Semi(_) => visit::walk_node(self, node),
+                    _ => {}
                 }
-                let return_ty = match return_ty {
-                    Some(ty) => Box::new(self.resolve_type(ty)?),
-                    None => Box::new(Type::Unit),
-                };
-                self.define_symbol(name, Type::Func(params_ty, return_ty), false, node.span);
             }
-            ASTNodeType::Semi(s) => self.collect_function_deep(s)?,
-            _ => {}
         }
 
-        Ok(())
+        let mut functions = Functions(Vec::new());
+        functions.visit_node(node);
+        for function in functions.0 {
+            self.collect_function(function);
+        }
     }
 
-    pub fn validate(&mut self, ast: &mut ASTModule) -> Result<(), Vec<Diagnostic>> {
-        let mut diagnostics = Vec::new();
+    /// Records `diagnostic`, returning the type that poisons the expression it
+    /// was reported for.
+    fn report(&mut self, diagnostic: Diagnostic) -> Type {
+        self.diagnostics.push(diagnostic);
+        Type::Error
+    }
 
+    /// Checks a whole module, with its functions usable before they're
+    /// defined, returning its warnings if it has no errors.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the problems found if any is an error, along with the
+    /// warnings, ordered by where they are.
+    pub fn validate(&mut self, ast: &mut ASTModule) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
         for node in &ast.nodes {
-            if let Err(diag) = self.collect_function_deep(node) {
-                diagnostics.push(diag);
-            }
+            self.collect_function_deep(node);
         }
 
         for node in &mut ast.nodes {
-            if let Err(diag) = self.validate_node(node, false, false) {
-                diagnostics.extend(diag);
-            }
+            self.validate_node(node, false, false);
         }
 
-        if !diagnostics.is_empty() {
+        let mut diagnostics = mem::take(&mut self.diagnostics);
+        diagnostics.sort_by_key(|diag| (diag.primary_span.start(), diag.primary_span.end()));
+        if diagnostics.iter().any(Diagnostic::is_error) {
             return Err(diagnostics);
         }
 
-        Ok(())
+        Ok(diagnostics)
     }
 
+    /// Checks `node` and returns its type. With `force_exhaustive`, an `if`
+    /// must have an `else` whose type matches, as its value is used. With
+    /// `recollect`, a function that `node` defines is defined first.
+    ///
+    /// Errors are recorded rather than returned, and yield [`Type::Error`],
+    /// which every check treats as already-reported so that a single mistake
+    /// doesn't cascade.
+    #[expect(
+        clippy::too_many_lines,
+        reason = "Each kind of node is checked in a single arm."
+    )]
+    #[expect(
+        clippy::cognitive_complexity,
+        reason = "Each kind of node is checked in a single arm."
+    )]
+    #[expect(
+        clippy::if_not_else,
+        reason = "Each arm checks the node, then reports it if it's at the root."
+    )]
     pub fn validate_node(
         &mut self,
         node: &mut ASTNode,
         force_exhaustive: bool,
         recollect: bool,
-    ) -> Result<Type, Vec<Diagnostic>> {
+    ) -> Type {
         if recollect {
-            self.collect_function(node).map_err(|err| vec![err])?;
+            self.collect_function(node);
         }
-        match &mut node.ty {
+        match node.ty {
             ASTNodeType::IntLit(_) => {
                 if self.context != Context::Root {
-                    Ok(Type::Int)
+                    Type::Int
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Integer literals can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Integer literals can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::FloatLit(_) => {
                 if self.context != Context::Root {
-                    Ok(Type::Float)
+                    Type::Float
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Float literals can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Float literals can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::StringLit(_) => {
                 if self.context != Context::Root {
-                    Ok(Type::String)
+                    Type::String
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("String literals can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("String literals can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
+                }
+            }
+            ASTNodeType::Interpolation(ref mut parts) => {
+                if self.context == Context::Root {
+                    return self.report(
+                        Diagnostic::new("String literals can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    );
+                }
+                for part in parts {
+                    let InterpolationPart::Expr {
+                        ref mut expr,
+                        ref mut ty,
+                    } = *part
+                    else {
+                        continue;
+                    };
+                    let expr_ty = self.validate_node(expr, true, true);
+                    match expr_ty {
+                        Type::Bool | Type::Float | Type::Int | Type::String => {
+                            *ty = Some(expr_ty);
+                        }
+                        Type::Error | Type::Unknown => {}
+                        Type::Func(..) | Type::Unit | Type::Vector(_) => {
+                            self.report(
+                                Diagnostic::new(
+                                    format!(
+                                        "Values of type `{}` can't be interpolated into a string",
+                                        expr_ty.display()
+                                    ),
+                                    expr.span,
+                                )
+                                .with_code(error_codes::E0015)
+                                .with_primary_label(format!(
+                                    "this is of type `{}`",
+                                    expr_ty.display()
+                                ))
+                                .with_note(
+                                    "only `int`, `float`, `bool` and `string` values can be \
+                                     interpolated",
+                                ),
+                            );
+                        }
+                    }
                 }
+                Type::String
             }
             ASTNodeType::Boolean(_) => {
                 if self.context != Context::Root {
-                    Ok(Type::Bool)
+                    Type::Bool
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Booleans can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Booleans can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
-            ASTNodeType::Identifier(s) => {
+            ASTNodeType::Identifier(ref name) => {
                 if self.context != Context::Root {
-                    self.find_symbol(s, node.span.clone())
-                        .map(|sy| sy.ty.clone())
-                        .map_err(|err| vec![err])
+                    match self.find_symbol(name, node.span) {
+                        Ok(symbol) => symbol.ty.clone(),
+                        Err(err) => self.report(err),
+                    }
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Identifiers can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Identifiers can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
-            ASTNodeType::Semi(stmt) => {
-                self.validate_node(stmt, false, true)?;
-                Ok(Type::Unit)
+            ASTNodeType::Semi(ref mut stmt) => {
+                let ty = self.validate_node(stmt, false, true);
+                if ![Type::Unit, Type::Error].contains(&ty)
+                    && !matches!(stmt.ty, ASTNodeType::FunDef { .. })
+                {
+                    self.lint(
+                        Lint::UnusedResults,
+                        Diagnostic::new(
+                            format!("Result of type `{}` is discarded", ty.display()),
+                            stmt.span,
+                        ),
+                    );
+                }
+                Type::Unit
             }
-            ASTNodeType::Block(stmts) => {
+            ASTNodeType::Block(ref mut stmts) => {
                 if self.context != Context::Root {
-                    let mut last_ty = Type::Unit;
+                    self.scoped(|this| {
+                        let mut last_ty = Type::Unit;
 
-                    for stmt in &*stmts {
-                        self.collect_function_deep(stmt).map_err(|err| vec![err])?;
-                    }
-                    for stmt in stmts {
-                        last_ty = self.validate_node(stmt, false, false)?;
-                    }
+                        for stmt in &*stmts {
+                            this.collect_function_deep(stmt);
+                        }
+                        for stmt in stmts {
+                            last_ty = this.validate_node(stmt, false, false);
+                        }
 
-                    Ok(last_ty)
+                        last_ty
+                    })
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Blocks can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Blocks can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::Unit => {
                 if self.context != Context::Root {
-                    Ok(Type::Unit)
+                    Type::Unit
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Units can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Units can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::BinaryOp {
-                op,
-                lhs,
-                rhs,
-                op_tys,
+                ref op,
+                ref mut lhs,
+                ref mut rhs,
+                ref mut op_tys,
             } => {
                 if self.context != Context::Root {
                     if [
@@ -305,398 +693,808 @@ impl SemanticChecker {
                     ]
                     .contains(op)
                     {
-                        match &lhs.ty {
-                            ASTNodeType::Identifier(s) => {
-                                let rhs_ty = self.validate_node(rhs, true, true)?;
-                                self.mutate_symbol(s, &rhs_ty, node.span.clone())
-                                    .map_err(|err| vec![err])?;
-                                let sym = self
-                                    .find_symbol(s, node.span)
-                                    .map_err(|err| vec![err])?
-                                    .clone();
-                                if *op != Operator::Assign
-                                    && ![Type::Int, Type::Float].contains(&sym.ty)
-                                {
-                                    return Err(
-                                    vec![Diagnostic::new(
-                                        self.path.display(),
-                                        format!("Cannot use arithmetic mutation on variable of type `{}`", sym.ty.display()),
-                                        node.span.clone(),
-                                    ).with_secondary_message(Some(format!("Variable `{s}` was defined here:")), sym.defined_at)]
-                                );
-                                }
-                                let var_ty = sym.ty;
-                                *op_tys = Some((var_ty, rhs_ty));
-                                return Ok(Type::Unit);
-                            }
-                            _ => {
-                                return Err(vec![Diagnostic::new(
-                                    self.path.display(),
-                                    "Can only mutate variables",
-                                    node.span.clone(),
-                                )]);
-                            }
+                        let ASTNodeType::Identifier(ref var) = lhs.ty else {
+                            self.validate_node(rhs, true, true);
+                            self.report(
+                                Diagnostic::new("Can only mutate variables", node.span)
+                                    .with_code(error_codes::E0007),
+                            );
+                            return Type::Unit;
+                        };
+                        let rhs_ty = self.validate_node(rhs, true, true);
+                        if let Err(err) = self.mutate_symbol(var, &rhs_ty, node.span) {
+                            self.report(err);
                         }
+                        let Some(sym) = self.lookup_symbol(var).cloned() else {
+                            return Type::Unit;
+                        };
+                        self.record_reference(var, lhs.span, sym.clone());
+                        if *op != Operator::Assign
+                            && ![Type::Int, Type::Float, Type::Error].contains(&sym.ty)
+                        {
+                            self.report(
+                                Diagnostic::new(
+                                    format!(
+                                        "Cannot use arithmetic mutation on variable of type `{}`",
+                                        sym.ty.display()
+                                    ),
+                                    node.span,
+                                )
+                                .with_code(error_codes::E0006)
+                                .with_secondary_label(
+                                    Some(format!("`{var}` was defined here")),
+                                    sym.defined_at,
+                                ),
+                            );
+                        }
+                        *op_tys = Some((sym.ty, rhs_ty));
+                        return Type::Unit;
                     }
 
-                    let lhs_ty = self.validate_node(lhs, true, true)?;
-                    let rhs_ty = self.validate_node(rhs, true, true)?;
+                    let lhs_ty = self.validate_node(lhs, true, true);
+                    let rhs_ty = self.validate_node(rhs, true, true);
 
-                    if let Some(output) = op.infix_output(&lhs_ty, &rhs_ty) {
+                    if lhs_ty == Type::Error || rhs_ty == Type::Error {
+                        Type::Error
+                    } else if let Some(output) = op.infix_output(&lhs_ty, &rhs_ty) {
                         *op_tys = Some((lhs_ty, rhs_ty));
-                        Ok(output)
+                        output
                     } else {
-                        Err(vec![Diagnostic::new(
-                            self.path.display(),
+                        self.report(Diagnostic::new(
                             format!(
-                                "Cannot apply `{}` as an infix operator on types `{}` and `{}`",
-                                op.err_str(),
+                                "Cannot apply `{op}` as an infix operator on types `{}` and `{}`",
                                 lhs_ty.display(),
                                 rhs_ty.display()
                             ),
-                            node.span.clone(),
-                        )])
+                            node.span,
+                        ).with_code(error_codes::E0006))
                     }
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Binary operations can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Binary operations can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
-            ASTNodeType::UnaryOp { op, operand, op_ty } => {
+            ASTNodeType::UnaryOp {
+                ref op,
+                ref mut operand,
+                ref mut op_ty,
+            } => {
                 if self.context != Context::Root {
-                    let operand_ty = self.validate_node(operand, true, true)?;
+                    let operand_ty = self.validate_node(operand, true, true);
 
-                    if let Some(output) = op.prefix_output(&operand_ty) {
+                    if operand_ty == Type::Error {
+                        Type::Error
+                    } else if let Some(output) = op.prefix_output(&operand_ty) {
                         *op_ty = Some(operand_ty);
-                        Ok(output)
+                        output
                     } else {
-                        Err(vec![Diagnostic::new(
-                            self.path.display(),
-                            format!(
-                                "Cannot apply `{}` as a unary operator on type `{}`",
-                                op.err_str(),
-                                operand_ty.display()
-                            ),
-                            node.span.clone(),
-                        )])
+                        self.report(
+                            Diagnostic::new(
+                                format!(
+                                    "Cannot apply `{op}` as a unary operator on type `{}`",
+                                    operand_ty.display()
+                                ),
+                                node.span,
+                            )
+                            .with_code(error_codes::E0006),
+                        )
                     }
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Unary operations can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Unary operations can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
-            ASTNodeType::LetDecl { name, ty, init } => {
+            ASTNodeType::LetDecl {
+                ref name,
+                ref ty,
+                ref mut init,
+                ..
+            } => {
                 if self.context != Context::Root {
-                    let mut var_ty = if let Some(ty) = ty {
-                        self.resolve_type(ty).map_err(|err| vec![err])?
-                    } else {
-                        Type::Unknown
+                    let mut var_ty = match ty.as_ref().map(|ty| self.resolve_type(ty)) {
+                        Some(Ok(ty)) => ty,
+                        Some(Err(err)) => self.report(err),
+                        None => Type::Unknown,
                     };
-                    if let Some(i) = init {
-                        let init_ty = self.validate_node(i, true, true)?;
-                        if var_ty == Type::Unknown {
+                    if let Some(ref mut init_expr) = *init {
+                        let init_ty = self.validate_node(init_expr, true, true);
+                        if [Type::Unknown, Type::Error].contains(&var_ty) {
                             var_ty = init_ty.clone();
                         }
-                        if init_ty != var_ty {
-                            return Err(vec![Diagnostic::new(
-                                self.path.display(),
+                        if init_ty != var_ty && init_ty != Type::Error {
+                            let mut diagnostic = Diagnostic::new(
                                 format!(
                                     "Variable `{name}` is declared as `{}` but initialized as `{}`",
                                     var_ty.display(),
                                     init_ty.display(),
                                 ),
-                                i.span.clone(),
-                            )]);
+                                init_expr.span,
+                            )
+                            .with_code(error_codes::E0004);
+                            diagnostic = match (&var_ty, &init_expr.ty, ty.as_ref()) {
+                                (&Type::Float, &ASTNodeType::IntLit(int), _) => diagnostic
+                                    .with_suggestion(
+                                        "use a float literal",
+                                        init_expr.span,
+                                        format!("{int}.0"),
+                                        Applicability::MachineApplicable,
+                                    ),
+                                (_, _, Some(ty)) if !matches!(init_ty, Type::Func(..)) => {
+                                    diagnostic.with_suggestion(
+                                        format!("change the type of `{name}`"),
+                                        ty.span,
+                                        init_ty.display(),
+                                        Applicability::MaybeIncorrect,
+                                    )
+                                }
+                                _ => diagnostic,
+                            };
+                            self.report(diagnostic);
                         }
-                        self.define_symbol(name, var_ty, true, node.span);
+                        self.define_symbol(name, SymbolKind::Variable, var_ty, true, node.span);
                     } else {
-                        self.define_symbol(name, var_ty, false, node.span);
+                        self.define_symbol(name, SymbolKind::Variable, var_ty, false, node.span);
                     }
 
-                    Ok(Type::Unit)
+                    Type::Unit
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Variable declarations can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new(
+                            "Variable declarations can't be a root-level item",
+                            node.span,
+                        )
+                        .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::If {
-                condition,
-                then_body,
-                else_body,
+                ref mut condition,
+                ref mut then_body,
+                ref mut else_body,
             } => {
                 if self.context != Context::Root {
-                    let mut errors = Vec::new();
-                    let _ = self
-                        .validate_node(condition, true, true)
-                        .inspect_err(|err| errors.extend(err.clone()))
-                        .inspect(|ty| {
-                            if *ty != Type::Bool {
-                                errors.push(Diagnostic::new(
-                                    self.path.display(),
-                                    "Expected boolean condition in `if`",
-                                    condition.span.clone(),
-                                ))
-                            }
-                        });
+                    let cond_ty = self.validate_node(condition, true, true);
+                    if ![Type::Bool, Type::Error].contains(&cond_ty) {
+                        self.report(
+                            Diagnostic::new("Expected boolean condition in `if`", condition.span)
+                                .with_code(error_codes::E0008),
+                        );
+                    }
 
-                    let then_body_ty = self.validate_node(then_body, force_exhaustive, true)?;
-                    if force_exhaustive {
-                        if let Some(else_body) = else_body {
-                            let else_body_ty =
-                                self.validate_node(else_body, force_exhaustive, true)?;
+                    let then_body_ty =
+                        self.scoped(|this| this.validate_node(then_body, force_exhaustive, true));
+                    let else_body_ty = else_body.as_mut().map(|else_expr| {
+                        self.scoped(|this| this.validate_node(else_expr, force_exhaustive, true))
+                    });
 
-                            if else_body_ty != then_body_ty {
-                                errors.push(Diagnostic::new(
-                                    self.path.display(),
-                                    format!(
-                                        "`if`'s clauses has different return types: `{}` and `{}`",
-                                        then_body_ty.display(),
-                                        else_body_ty.display(),
-                                    ),
-                                    node.span.clone(),
-                                ));
-                                Err(errors)
-                            } else {
-                                Ok(then_body_ty)
-                            }
-                        } else {
-                            errors.push(Diagnostic::new(
-                                self.path.display(),
+                    if !force_exhaustive {
+                        return Type::Unit;
+                    }
+                    match else_body_ty {
+                        Some(Type::Error) => Type::Error,
+                        Some(_) if then_body_ty == Type::Error => Type::Error,
+                        Some(else_body_ty) if else_body_ty != then_body_ty => self.report(
+                            Diagnostic::new(
+                                format!(
+                                    "`if`'s clauses has different return types: `{}` and `{}`",
+                                    then_body_ty.display(),
+                                    else_body_ty.display(),
+                                ),
+                                node.span,
+                            )
+                            .with_code(error_codes::E0004),
+                        ),
+                        Some(_) => then_body_ty,
+                        None => self.report(
+                            Diagnostic::new(
                                 format!(
                                     "Missing `else` clause that evaluates to type `{}`",
                                     then_body_ty.display(),
                                 ),
-                                node.span.clone(),
-                            ));
-                            Err(errors)
-                        }
-                    } else {
-                        Ok(Type::Unit)
+                                node.span,
+                            )
+                            .with_code(error_codes::E0009)
+                            .with_help(
+                                "`if` is used as a value here, so both branches must be present",
+                            )
+                            .with_suggestion(
+                                "add an `else` clause",
+                                node.span.shrink_to_hi(),
+                                " else ...",
+                                Applicability::HasPlaceholders,
+                            ),
+                        ),
                     }
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("`if` conditionals can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("`if` conditionals can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
-            ASTNodeType::While { condition, body } => {
+            ASTNodeType::While {
+                ref mut condition,
+                ref mut body,
+            } => {
                 if self.context != Context::Root {
-                    let mut errors = Vec::new();
-                    let cond_ty = self.validate_node(condition, true, true)?;
-                    if cond_ty != Type::Bool {
-                        errors.push(Diagnostic::new(
-                            self.path.display(),
-                            "Expected boolean condition in `while`",
-                            condition.span.clone(),
-                        ))
+                    let cond_ty = self.validate_node(condition, true, true);
+                    if ![Type::Bool, Type::Error].contains(&cond_ty) {
+                        self.report(
+                            Diagnostic::new(
+                                "Expected boolean condition in `while`",
+                                condition.span,
+                            )
+                            .with_code(error_codes::E0008),
+                        );
                     }
 
-                    self.symbols.push(HashMap::new());
-                    let _ = self
-                        .validate_node(body, force_exhaustive, true)
-                        .inspect_err(|err| errors.extend_from_slice(err.as_slice()));
-                    if !errors.is_empty() {
-                        return Err(errors);
-                    }
-                    self.symbols.pop();
-                    Ok(Type::Unit)
+                    self.scoped(|this| this.validate_node(body, force_exhaustive, true));
+                    Type::Unit
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("`while` loops can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("`while` loops can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
             ASTNodeType::FunDef {
-                name,
-                params,
-                return_ty,
-                body,
+                ref name,
+                ref params,
+                ref return_ty,
+                ref mut body,
+                ..
             } => {
-                let mut scope = HashMap::new();
-                for (name, ty, span) in params {
-                    scope.insert(
-                        name.clone(),
-                        Symbol {
-                            ty: self.resolve_type(ty).map_err(|err| vec![err])?,
-                            is_unitialized: false,
-                            defined_at: *span,
-                        },
-                    );
+                self.symbols.push(HashMap::new());
+                for &(ref param, ref ty, span) in params {
+                    if let Some(previous) = self.symbols.last().and_then(|scope| scope.get(param)) {
+                        let diagnostic = Diagnostic::new(
+                            format!(
+                                "Parameter `{param}` of function `{name}` is defined more than once"
+                            ),
+                            span,
+                        )
+                        .with_code(error_codes::E0010)
+                        .with_secondary_label(Some("first defined here"), previous.defined_at);
+                        self.report(diagnostic);
+                        continue;
+                    }
+                    let param_ty = match self.resolve_type(ty) {
+                        Ok(param_ty) => param_ty,
+                        Err(err) => self.report(err),
+                    };
+                    self.define_symbol(param, SymbolKind::Parameter, param_ty, false, span);
                 }
-                self.symbols.push(scope);
+                let return_ty = match return_ty.as_ref().map(|ty| self.resolve_type(ty)) {
+                    Some(Ok(ty)) => ty,
+                    Some(Err(err)) => self.report(err),
+                    None => Type::Unit,
+                };
+
                 let previous = self.context;
                 self.context = Context::FunctionDecl;
-                let body_ty = self.validate_node(body, true, true)?;
-                let return_ty = return_ty
-                    .as_ref()
-                    .map(|ty| self.resolve_type(ty))
-                    .unwrap_or(Ok(Type::Unit))
-                    .map_err(|err| vec![err])?;
-                if body_ty != return_ty {
-                    return Err(vec![Diagnostic::new(
-                        self.path.display(),
+                let body_ty = self.validate_node(body, true, true);
+                self.context = previous;
+                self.pop_scope();
+
+                if body_ty != return_ty && body_ty != Type::Error && return_ty != Type::Error {
+                    self.report(Diagnostic::new(
                         format!(
                             "Function `{name}` is declared as a function of return type `{}`, but body returns `{}`",
                             return_ty.display(),
                             body_ty.display()
                         ),
                         body.span,
-                    )]);
+                    ).with_code(error_codes::E0004));
                 }
-                self.symbols.pop();
-                self.context = previous;
-                Ok(return_ty)
+                return_ty
             }
-            ASTNodeType::FunCall { callee, args } => {
+            ASTNodeType::FunCall {
+                ref callee,
+                ref mut args,
+            } => {
                 if self.context != Context::Root {
-                    let symbol = self
-                        .find_symbol(&callee, node.span)
-                        .map_err(|err| vec![err])?
-                        .clone();
+                    let symbol = match self.find_symbol(callee, node.span) {
+                        Ok(symbol) => symbol.clone(),
+                        Err(err) => {
+                            for arg in args.iter_mut() {
+                                self.validate_node(arg, true, true);
+                            }
+                            return self.report(err);
+                        }
+                    };
                     if let Type::Func(params_ty, ty) = symbol.ty {
-                        for (i, arg) in args.iter_mut().enumerate() {
-                            let arg_ty = self.validate_node(arg, true, true)?;
-                            if params_ty[i] != arg_ty {
-                                return Err(vec![Diagnostic::new(
-                                    self.path.display(),
+                        if args.len() != params_ty.len() {
+                            let diagnostic = Diagnostic::new(
+                                format!(
+                                    "Function `{callee}` takes {} but {} supplied",
+                                    plural(params_ty.len(), "argument", "arguments"),
+                                    plural(args.len(), "argument was", "arguments were"),
+                                ),
+                                node.span,
+                            )
+                            .with_code(error_codes::E0011)
+                            .with_secondary_label(
+                                Some(format!("`{callee}` was defined here")),
+                                symbol.defined_at,
+                            );
+                            self.report(diagnostic);
+                        }
+                        for (index, arg) in args.iter_mut().enumerate() {
+                            let arg_ty = self.validate_node(arg, true, true);
+                            let Some(param_ty) = params_ty.get(index) else {
+                                continue;
+                            };
+                            if *param_ty != arg_ty
+                                && *param_ty != Type::Error
+                                && arg_ty != Type::Error
+                            {
+                                let diagnostic = Diagnostic::new(
                                     format!(
-                                        "Function has argument #{i} as type `{}` but found `{}`",
-                                        params_ty[i].display(),
+                                        "Function `{callee}` has argument #{} as type `{}` but found `{}`",
+                                        index.strict_add(1),
+                                        param_ty.display(),
                                         arg_ty.display()
                                     ),
-                                    node.span,
-                                )]);
+                                    arg.span,
+                                ).with_code(error_codes::E0004)
+                                .with_secondary_label(
+                                    Some(format!("`{callee}` was defined here")),
+                                    symbol.defined_at,
+                                );
+                                self.report(diagnostic);
                             }
                         }
-                        Ok(*ty)
+                        *ty
+                    } else if symbol.ty == Type::Error {
+                        Type::Error
                     } else {
-                        Err(vec![Diagnostic::new(
-                            self.path.display(),
-                            format!("Identifier {callee} is not a function"),
-                            node.span,
-                        )])
+                        self.report(
+                            Diagnostic::new(
+                                format!("Identifier {callee} is not a function"),
+                                node.span,
+                            )
+                            .with_code(error_codes::E0012),
+                        )
                     }
                 } else {
-                    Err(vec![Diagnostic::new(
-                        self.path.display(),
-                        format!("Function calls can't be a root-level item"),
-                        node.span,
-                    )])
+                    self.report(
+                        Diagnostic::new("Function calls can't be a root-level item", node.span)
+                            .with_code(error_codes::E0002),
+                    )
                 }
             }
         }
     }
 }
 
+impl Default for SemanticChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The types that operators produce.
 trait TyExt {
+    /// The type of applying the infix operator to `lhs` and `rhs`, or `None`
+    /// if it can't be applied to them.
     fn infix_output(&self, lhs: &Type, rhs: &Type) -> Option<Type>;
+
+    /// The type of applying the prefix operator to `operand`, or `None` if it
+    /// can't be applied to it.
     fn prefix_output(&self, operand: &Type) -> Option<Type>;
 }
 
 impl TyExt for Operator {
     fn infix_output(&self, lhs: &Type, rhs: &Type) -> Option<Type> {
-        match self {
-            Operator::Plus
-            | Operator::Minus
-            | Operator::Star
-            | Operator::Slash
-            | Operator::Modulo => match (lhs, rhs) {
-                // numeric
-                (Type::Int, Type::Int) => Some(Type::Int),
-                (Type::Float, Type::Float) => Some(Type::Float),
-                _ => None,
-            },
-            Operator::Gt | Operator::Lt | Operator::Ge | Operator::Le => match (lhs, rhs) {
+        match *self {
+            Self::Plus | Self::Minus | Self::Star | Self::Slash | Self::Modulo => {
+                match (lhs, rhs) {
+                    // numeric
+                    (&Type::Int, &Type::Int) => Some(Type::Int),
+                    (&Type::Float, &Type::Float) => Some(Type::Float),
+                    _ => None,
+                }
+            }
+            Self::Gt | Self::Lt | Self::Ge | Self::Le => match (lhs, rhs) {
                 // numeric
-                (Type::Int, Type::Int) | (Type::Float, Type::Float) => Some(Type::Bool),
+                (&Type::Int, &Type::Int) | (&Type::Float, &Type::Float) => Some(Type::Bool),
                 _ => None,
             },
-            Operator::Concat => match (lhs, rhs) {
-                (Type::String, Type::String) => Some(Type::String),
-                (Type::Vector(l), Type::Vector(r)) if l == r => Some(Type::Vector(l.clone())),
+            Self::Concat => match (lhs, rhs) {
+                (&Type::String, &Type::String) => Some(Type::String),
+                (&Type::Vector(_), &Type::Vector(_)) if lhs == rhs => Some(lhs.clone()),
                 _ => None,
             },
-            Operator::Pipe
-            | Operator::Ampersand
-            | Operator::Caret
-            | Operator::Lsh
-            | Operator::Rsh => {
-                if let (Type::Int, Type::Int) = (lhs, rhs) {
-                    Some(Type::Int)
-                } else {
-                    None
-                }
+            Self::Pipe | Self::Ampersand | Self::Caret | Self::Lsh | Self::Rsh => {
+                matches!((lhs, rhs), (&Type::Int, &Type::Int)).then_some(Type::Int)
             }
-            Operator::Range | Operator::RangeInclus => {
-                if let (Type::Int, Type::Int) = (lhs, rhs) {
-                    Some(Type::Vector(Box::new(Type::Int)))
-                } else {
-                    None
-                }
+            Self::Range | Self::RangeInclus => matches!((lhs, rhs), (&Type::Int, &Type::Int))
+                .then(|| Type::Vector(Box::new(Type::Int))),
+            Self::LogOr | Self::LogAnd => {
+                matches!((lhs, rhs), (&Type::Bool, &Type::Bool)).then_some(Type::Bool)
             }
-            Operator::LogOr | Operator::LogAnd => {
-                if let (Type::Bool, Type::Bool) = (lhs, rhs) {
-                    Some(Type::Bool)
-                } else {
-                    None
-                }
-            }
-            Operator::Assign
-            | Operator::PlusAssign
-            | Operator::MinusAssign
-            | Operator::StarAssign
-            | Operator::SlashAssign
-            | Operator::ModuloAssign => unreachable!("Assignations should be handled separately"),
-            Operator::Eq | Operator::Ne => {
-                if lhs == rhs {
-                    Some(Type::Bool)
-                } else {
-                    None
-                }
-            }
-            Operator::Tilde | Operator::Bang => {
+            Self::Assign
+            | Self::PlusAssign
+            | Self::MinusAssign
+            | Self::StarAssign
+            | Self::SlashAssign
+            | Self::ModuloAssign => unreachable!("Assignations should be handled separately"),
+            Self::Eq | Self::Ne => (lhs == rhs).then_some(Type::Bool),
+            Self::Tilde | Self::Bang => {
                 unreachable!("Prefix ops should not be called here")
             }
         }
     }
 
     fn prefix_output(&self, operand: &Type) -> Option<Type> {
-        {
-            match self {
-                Operator::Plus | Operator::Minus => match operand {
-                    // numeric
-                    Type::Int | Type::Float => Some(operand.clone()),
-                    _ => None,
-                },
-                Operator::Tilde => {
-                    if *operand == Type::Int {
-                        Some(Type::Int)
-                    } else {
-                        None
-                    }
-                }
-                Operator::Bang => {
-                    if *operand == Type::Bool {
-                        Some(Type::Bool)
-                    } else {
-                        None
-                    }
-                }
-                _ => unreachable!("Infix ops should not be called here"),
-            }
+        match *self {
+            Self::Plus | Self::Minus => match *operand {
+                // numeric
+                Type::Int | Type::Float => Some(operand.clone()),
+                _ => None,
+            },
+            Self::Tilde => (*operand == Type::Int).then_some(Type::Int),
+            Self::Bang => (*operand == Type::Bool).then_some(Type::Bool),
+            _ => unreachable!("Infix ops should not be called here"),
         }
     }
 }
+
+/// `count` followed by the `singular` or `plural` noun it takes.
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{count} {singular}")
+    } else {
+        format!("{count} {plural}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amaic_core::{FileId, Severity};
+    use amaic_lexer::tokenize;
+    use amaic_parser::Parser;
+
+    use super::*;
+
+    fn check(source: &str) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+        let tokens = tokenize(source).expect("the source should lex");
+        let mut module = Parser::new("test.amai", FileId::default(), &tokens)
+            .parse()
+            .expect("the source should parse");
+        SemanticChecker::new().validate(&mut module)
+    }
+
+    /// The names that are reported as not being in scope.
+    fn unknown_names(source: &str) -> Vec<&str> {
+        let Err(errors) = check(source) else {
+            return Vec::new();
+        };
+        errors
+            .iter()
+            .filter(|diag| diag.primary_err.starts_with("Couldn't find variable"))
+            .map(|diag| {
+                source
+                    .get(diag.primary_span.start()..diag.primary_span.end())
+                    .expect("the span should be within the source")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn block_locals_are_not_visible_after_the_block() {
+        assert_eq!(
+            unknown_names("let f(): int = { { let x = 1; x }; x };"),
+            ["x"]
+        );
+    }
+
+    #[test]
+    fn branch_and_loop_locals_are_not_visible_after_them() {
+        assert_eq!(
+            unknown_names("let f(): int = { if true then { let x = 1; x } else 2; x };"),
+            ["x"]
+        );
+        assert_eq!(
+            unknown_names("let f(): int = { while false do { let y = 1; y; }; y };"),
+            ["y"]
+        );
+    }
+
+    #[test]
+    fn parameters_are_not_visible_outside_their_function() {
+        assert_eq!(
+            unknown_names("let f(a: int): int = a;\nlet g(): int = a;"),
+            ["a"]
+        );
+    }
+
+    #[test]
+    fn shadowing_ends_with_the_block() {
+        assert!(errors("let f(): int = { let x = 1; { let x = \"s\"; x }; x };").is_empty());
+        assert_eq!(
+            errors("let f(): int = { let x = \"s\"; { let x = 1; x }; x };"),
+            [
+                "Function `f` is declared as a function of return type `int`, but body returns `string`"
+            ]
+        );
+    }
+
+    #[test]
+    fn outer_locals_are_visible_inside_blocks() {
+        assert!(errors("let f(): int = { let x = 1; { { x } } };").is_empty());
+    }
+
+    /// The messages of the errors reported.
+    fn errors(source: &str) -> Vec<String> {
+        check(source)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .map(|diag| diag.primary_err)
+            .collect()
+    }
+
+    #[test]
+    fn unknown_types_still_mismatch() {
+        assert_eq!(
+            errors("let f(): int = { let x; x };"),
+            [
+                "Function `f` is declared as a function of return type `int`, but body returns `{unknown}`"
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_do_not_cascade() {
+        let unknown = "Couldn't find variable `nope` in scope";
+        let unknown_type = "Cannot find type `nope`";
+        assert_eq!(errors("let f(): int = -nope + 1 * 2;"), [unknown]);
+        assert_eq!(
+            errors("let f(): bool = if nope then nope else 1;"),
+            [unknown, unknown]
+        );
+        assert_eq!(errors("let f(a: nope): int = a;"), [unknown_type]);
+        assert_eq!(
+            errors("let f(a: nope): int = a;\nlet g(): int = f(1);"),
+            [unknown_type]
+        );
+        assert_eq!(
+            errors("let f(): int = { let x: nope = 1; x };"),
+            [unknown_type]
+        );
+    }
+
+    #[test]
+    fn parameters_are_linted_like_variables() {
+        // the `а` is Cyrillic
+        let warnings =
+            check("let f(\u{430}ge: int): int = \u{430}ge;").expect("the source should check");
+        let confusables: Vec<_> = warnings
+            .iter()
+            .filter(|diag| diag.primary_err.contains("isn't written in ASCII"))
+            .map(|diag| diag.primary_err.as_str())
+            .collect();
+        assert_eq!(
+            confusables,
+            ["`\u{430}ge` looks like `age`, but isn't written in ASCII"]
+        );
+
+        // a repeated parameter is an error, not shadowing
+        let errors = check("let f(a: int, a: int): int = a;").expect_err("`a` is repeated");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, Some(error_codes::E0010.code));
+    }
+
+    /// The warnings reported for `source`, with the text each is at.
+    fn warnings(source: &str) -> Vec<(String, &str)> {
+        check(source)
+            .expect("the source should check")
+            .into_iter()
+            .map(|diag| (diag.primary_err, text(source, diag.primary_span)))
+            .collect()
+    }
+
+    #[test]
+    fn unused_names_are_linted() {
+        assert_eq!(
+            warnings(
+                "let f(a: int, _b: int): int = {\n\
+                    let x = 1; let _y = 2;\n\
+                    let g(): int = 1; let _h(): int = 1;\n\
+                    0\n\
+                };"
+            ),
+            [
+                ("Parameter `a` is never used".to_owned(), "a: int"),
+                ("Variable `x` is never used".to_owned(), "let x = 1"),
+                ("Function `g` is never used".to_owned(), "let g(): int = 1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unused_names_are_reported_with_their_lint_and_a_help() {
+        let diagnostics =
+            check("let f(): int = { let x = 1; 0 };").expect("the source should check");
+        let messages: Vec<_> = diagnostics[0]
+            .children
+            .iter()
+            .map(|child| (child.severity, child.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    Severity::Help,
+                    "if this is intentional, prefix it with an underscore: `_x`"
+                ),
+                (Severity::Note, "`unused_variables` is set to `warn`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn discarded_results_are_linted() {
+        assert_eq!(
+            warnings("let f(): int = { 1; let x = 2; x; f(); 0 };"),
+            [
+                ("Result of type `int` is discarded".to_owned(), "1"),
+                ("Result of type `int` is discarded".to_owned(), "x"),
+                ("Result of type `int` is discarded".to_owned(), "f()"),
+            ]
+        );
+        assert_eq!(
+            warnings("let f(x: int): int = { x = 2; while false do {}; f(x) };"),
+            []
+        );
+    }
+
+    #[test]
+    fn shadowing_in_the_same_scope_is_linted() {
+        let source = "let f(): int = { let x = 1; let x = 2; x };";
+        let diagnostics = check(source).expect("the source should check");
+        let reported: Vec<_> = diagnostics
+            .iter()
+            .map(|diag| (diag.primary_err.as_str(), text(source, diag.primary_span)))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                ("Variable `x` is never used", "let x = 1"),
+                (
+                    "`x` shadows an earlier definition in the same scope",
+                    "let x = 2"
+                ),
+            ]
+        );
+        let labels: Vec<_> = diagnostics[1]
+            .secondary_labels
+            .iter()
+            .map(|label| (label.message.as_deref(), text(source, label.span)))
+            .collect();
+        assert_eq!(labels, [(Some("previously defined here"), "let x = 1")]);
+
+        // shadowing in an inner scope is how blocks are meant to be used
+        assert_eq!(
+            warnings("let f(): int = { let x = 1; { let x = 2; x } + x };"),
+            []
+        );
+    }
+
+    #[test]
+    fn top_level_functions_are_not_linted() {
+        assert_eq!(warnings("let f(): int = 1;\nlet g(): int = 2;"), []);
+    }
+
+    /// The replacements suggested for names that aren't in scope.
+    fn suggested_names(source: &str) -> Vec<String> {
+        let Err(errors) = check(source) else {
+            return Vec::new();
+        };
+        errors
+            .into_iter()
+            .filter(|diag| diag.code == Some(error_codes::E0003.code))
+            .flat_map(|diag| diag.suggestions)
+            .map(|suggestion| suggestion.replacement)
+            .collect()
+    }
+
+    /// The text of `span` within `source`.
+    fn text(source: &str, span: Span) -> &str {
+        source
+            .get(span.start()..span.end())
+            .expect("the span should be within the source")
+    }
+
+    #[test]
+    fn reports_the_wrong_number_of_arguments() {
+        let source = "let f(a: int, b: int): int = a + b;\n\
+            let g(): int = f(1, 2, 3);\n\
+            let h(): int = f(1);";
+        let errors = check(source).expect_err("the calls have the wrong number of arguments");
+        let reported: Vec<_> = errors
+            .iter()
+            .map(|diag| (diag.primary_err.as_str(), text(source, diag.primary_span)))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (
+                    "Function `f` takes 2 arguments but 3 arguments were supplied",
+                    "f(1, 2, 3)"
+                ),
+                (
+                    "Function `f` takes 2 arguments but 1 argument was supplied",
+                    "f(1)"
+                ),
+            ]
+        );
+        for error in &errors {
+            assert_eq!(error.code, Some(error_codes::E0011.code));
+            assert_eq!(error.secondary_labels.len(), 1);
+            let label = &error.secondary_labels[0];
+            assert_eq!(label.message.as_deref(), Some("`f` was defined here"));
+            assert!(text(source, label.span).starts_with("let f("));
+        }
+    }
+
+    #[test]
+    fn reports_each_mismatched_argument_at_its_own_span() {
+        let source = "let f(a: int, b: string, c: bool): int = a;\n\
+            let g(): int = f(\"x\", \"y\", 1, nope);";
+        let errors = check(source).expect_err("the arguments are mismatched");
+        let errors: Vec<_> = errors
+            .into_iter()
+            .filter(|diag| diag.severity == Severity::Error)
+            .collect();
+        let reported: Vec<_> = errors
+            .iter()
+            .map(|diag| (diag.primary_err.as_str(), text(source, diag.primary_span)))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (
+                    "Function `f` takes 3 arguments but 4 arguments were supplied",
+                    "f(\"x\", \"y\", 1, nope)"
+                ),
+                (
+                    "Function `f` has argument #1 as type `int` but found `string`",
+                    "\"x\""
+                ),
+                (
+                    "Function `f` has argument #3 as type `bool` but found `int`",
+                    "1"
+                ),
+                ("Couldn't find variable `nope` in scope", "nope"),
+            ]
+        );
+        for error in errors.iter().take(3) {
+            let labels: Vec<_> = error
+                .secondary_labels
+                .iter()
+                .map(|label| (label.message.as_deref(), text(source, label.span)))
+                .collect();
+            assert_eq!(
+                labels,
+                [(
+                    Some("`f` was defined here"),
+                    "let f(a: int, b: string, c: bool): int = a"
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn suggests_keywords_the_grammar_uses() {
+        assert_eq!(suggested_names("let f(): bool = tru;"), ["true"]);
+        // `return` is reserved, but there's nothing to suggest it for
+        assert!(suggested_names("let f(): int = { retrn; 1 };").is_empty());
+    }
+}
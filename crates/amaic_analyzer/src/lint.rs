@@ -0,0 +1,113 @@
+//! See [`Lint`].
+
+use amaic_core::{Diagnostic, Severity};
+
+/// A check for code that's valid but likely a mistake, reported as a warning
+/// unless its level says otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Lint {
+    /// A name made up of ASCII and characters that look like ASCII.
+    ConfusableIdents,
+
+    /// A name defined again in the scope it's already defined in.
+    SameScopeShadowing,
+
+    /// A nested function that's never called. Top-level functions are the
+    /// module's API, so they're never reported.
+    UnusedFunctions,
+
+    /// A parameter that's never read.
+    UnusedParameters,
+
+    /// A statement whose value is discarded.
+    UnusedResults,
+
+    /// A variable that's never read.
+    UnusedVariables,
+}
+
+impl Lint {
+    /// Every lint.
+    pub const ALL: [Self; 6] = [
+        Self::ConfusableIdents,
+        Self::SameScopeShadowing,
+        Self::UnusedFunctions,
+        Self::UnusedParameters,
+        Self::UnusedResults,
+        Self::UnusedVariables,
+    ];
+
+    /// The level of the lint when none is set.
+    #[must_use]
+    pub const fn default_level(self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// The lint called `name`, if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+
+    /// The name the lint is referred to by on the command line.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ConfusableIdents => "confusable_idents",
+            Self::SameScopeShadowing => "same_scope_shadowing",
+            Self::UnusedFunctions => "unused_functions",
+            Self::UnusedParameters => "unused_parameters",
+            Self::UnusedResults => "unused_results",
+            Self::UnusedVariables => "unused_variables",
+        }
+    }
+}
+
+/// How a lint's findings are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintLevel {
+    /// They aren't reported.
+    Allow,
+
+    /// They're reported as errors.
+    Deny,
+
+    /// They're reported as warnings.
+    Warn,
+}
+
+impl LintLevel {
+    /// Applies the level to a diagnostic raised by a lint, returning `None` if
+    /// the lint is allowed.
+    #[must_use]
+    pub fn apply(self, diagnostic: Diagnostic) -> Option<Diagnostic> {
+        match self {
+            Self::Allow => None,
+            Self::Deny => Some(diagnostic.with_severity(Severity::Error)),
+            Self::Warn => Some(diagnostic.with_severity(Severity::Warning)),
+        }
+    }
+
+    /// The level called `name`, if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(Self::Allow),
+            "deny" => Some(Self::Deny),
+            "warn" => Some(Self::Warn),
+            _ => None,
+        }
+    }
+
+    /// The level's name, as accepted by [`LintLevel::from_name`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Warn => "warn",
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! See [`find_similar`].
+
+use std::mem;
+
+/// The Levenshtein distance between `from` and `to`, counted in `char`s.
+fn edit_distance(from: &str, to: &str) -> usize {
+    let to = to.chars().collect::<Vec<_>>();
+    let mut previous = (0..=to.len()).collect::<Vec<_>>();
+    let mut current = vec![0; to.len().strict_add(1)];
+
+    for (row, from_char) in from.chars().enumerate() {
+        current[0] = row.strict_add(1);
+        for (col, &to_char) in to.iter().enumerate() {
+            let substitution = previous[col].strict_add(usize::from(from_char != to_char));
+            current[col.strict_add(1)] = substitution
+                .min(previous[col.strict_add(1)].strict_add(1))
+                .min(current[col].strict_add(1));
+        }
+        mem::swap(&mut previous, &mut current);
+    }
+
+    previous[to.len()]
+}
+
+/// Finds the candidate closest to `name`, if any is close enough to plausibly
+/// be a typo of it. Names differing only in case are always considered close.
+pub fn find_similar<'cand>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'cand str>,
+) -> Option<&'cand str> {
+    let max_distance = name.chars().count().div_euclid(3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|&candidate| candidate != name)
+        .filter_map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(name) {
+                0
+            } else {
+                edit_distance(name, candidate)
+            };
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
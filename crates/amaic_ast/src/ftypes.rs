@@ -1,14 +1,34 @@
+//! See [`FrontendType`].
+
 use amaic_core::Span;
 
+/// The kind of a [`FrontendType`].
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "The parser and analyzer build and match every kind of type."
+)]
 pub enum FrontendTypeType {
+    /// A named type, such as `int`.
     Identifier(String),
-    Vector(Box<FrontendType>),
+
+    /// `()`.
     Unit,
+
+    /// `[ty]`.
+    Vector(Box<FrontendType>),
 }
 
+/// A type as it's written in the source-text.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "The parser builds types field by field."
+)]
 pub struct FrontendType {
-    pub ty: FrontendTypeType,
+    /// The span of the type.
     pub span: Span,
+
+    /// The kind of the type.
+    pub ty: FrontendTypeType,
 }
@@ -1,6 +1,10 @@
+//! The abstract syntax tree that the parser produces and the analyzer checks.
+
 mod ftypes;
 mod pattern;
+mod print;
 mod types;
+pub mod visit;
 
 use amaic_core::Span;
 use amaic_lexer::Operator;
@@ -10,62 +14,186 @@ use std::path::PathBuf;
 pub use ftypes::*;
 pub use pattern::*;
 pub use types::*;
+pub use visit::{Visitor, VisitorMut};
 
+/// A parsed source file.
 #[derive(Debug, Clone)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "The parser builds modules field by field."
+)]
 pub struct ASTModule {
-    pub path: PathBuf,
+    /// The text of the module's `//!` doc comments, one line per comment.
+    pub docs: Option<String>,
+
+    /// The top-level items of the file.
     pub nodes: Box<[ASTNode]>,
+
+    /// The path of the file.
+    pub path: PathBuf,
 }
 
+/// The kind of an [`ASTNode`], along with its children.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "The parser and analyzer build and match every kind of node."
+)]
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "Literals come first, then the expressions built from them."
+)]
 pub enum ASTNodeType {
+    /// An integer literal, such as `42`.
     IntLit(i64),
+
+    /// A float literal, such as `4.2`.
     FloatLit(f64),
+
+    /// A string literal, with its escapes already processed.
     StringLit(String),
+
+    /// A string literal with expressions interpolated into it, such as
+    /// `"total: {count} items"`.
+    Interpolation(Vec<InterpolationPart>),
+
+    /// `true` or `false`.
     Boolean(bool),
+
+    /// A reference to a variable or function.
     Identifier(String),
+
+    /// An expression followed by `;`, whose value is discarded.
     Semi(Box<ASTNode>),
+
+    /// A `{ ... }` block, whose value is that of its last expression.
     Block(Vec<ASTNode>),
+
+    /// `()`.
     Unit,
+
+    /// An infix operation, such as `a + b`.
     BinaryOp {
+        /// The operator.
         op: Operator,
+
+        /// The left operand.
         lhs: Box<ASTNode>,
+
+        /// The right operand.
         rhs: Box<ASTNode>,
+
+        /// The types of the operands, once analyzed.
         op_tys: Option<(Type, Type)>,
     },
+
+    /// A prefix operation, such as `-a`.
     UnaryOp {
+        /// The operator.
         op: Operator,
+
+        /// The operand.
         operand: Box<ASTNode>,
+
+        /// The type of the operand, once analyzed.
         op_ty: Option<Type>,
     },
+
+    /// `let name: ty = init;`.
     LetDecl {
+        /// The text of the `///` doc comments before the declaration.
+        docs: Option<String>,
+
+        /// The name of the variable.
         name: String,
+
+        /// The type written after `:`, if any.
         ty: Option<FrontendType>,
+
+        /// The value after `=`, if any.
         init: Option<Box<ASTNode>>,
     },
+
+    /// `if condition then_body else else_body`.
     If {
+        /// The condition.
         condition: Box<ASTNode>,
+
+        /// The branch taken when the condition is `true`.
         then_body: Box<ASTNode>,
+
+        /// The branch taken when the condition is `false`, if any.
         else_body: Option<Box<ASTNode>>,
     },
+
+    /// `while condition body`.
     While {
+        /// The condition checked before each iteration.
         condition: Box<ASTNode>,
+
+        /// The loop's body.
         body: Box<ASTNode>,
     },
+
+    /// `let name(params): return_ty = body`.
     FunDef {
+        /// The text of the `///` doc comments before the definition.
+        docs: Option<String>,
+
+        /// The name of the function.
         name: String,
+
+        /// The name, type and span of each parameter.
         params: Vec<(String, FrontendType, Span)>,
+
+        /// The return type, if any.
         return_ty: Option<FrontendType>,
+
+        /// The function's body.
         body: Box<ASTNode>,
     },
+
+    /// `callee(args)`.
     FunCall {
+        /// The name of the function being called.
         callee: String,
+
+        /// The arguments.
         args: Vec<ASTNode>,
     },
 }
 
+/// A piece of an interpolated string.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "An interpolated string is only ever made up of text and expressions."
+)]
+pub enum InterpolationPart {
+    /// An embedded expression, along with its type once analyzed, which
+    /// decides how it's converted to a string.
+    Expr {
+        /// The expression between the braces.
+        expr: ASTNode,
+
+        /// The type of the expression, once analyzed.
+        ty: Option<Type>,
+    },
+
+    /// Text, with its escapes already processed.
+    Text(String),
+}
+
+/// A node of the syntax tree, along with where in the source-text it is.
+#[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "The parser builds nodes field by field."
+)]
 pub struct ASTNode {
-    pub ty: ASTNodeType,
+    /// The span of the node.
     pub span: Span,
+
+    /// The kind of the node.
+    pub ty: ASTNodeType,
 }
@@ -1,20 +1,48 @@
+//! See [`Pattern`].
+
 use amaic_core::Span;
 
+/// A literal that a [`Pattern`] matches.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "Patterns are built and matched by kind."
+)]
 pub enum PatternLiteral {
-    Integer(i64),
-    Float(f64),
+    /// `true` or `false`.
     Boolean(bool),
+
+    /// A float literal.
+    Float(f64),
+
+    /// An integer literal.
+    Integer(i64),
 }
 
+/// The kind of a [`Pattern`].
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "Patterns are built and matched by kind."
+)]
 pub enum PatternType {
+    /// A name that binds the matched value.
     Identifier(String),
+
+    /// A literal that the value must equal.
     Literal(PatternLiteral),
 }
 
+/// A pattern that a value is matched against.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "Patterns are built field by field."
+)]
 pub struct Pattern {
-    pub ty: PatternType,
+    /// The span of the pattern.
     pub span: Span,
+
+    /// The kind of the pattern.
+    pub ty: PatternType,
 }
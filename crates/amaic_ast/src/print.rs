@@ -0,0 +1,426 @@
+//! Prints syntax trees back into source-text, with only the parentheses that
+//! operator precedence requires.
+//!
+//! Parsing printed source-text gives back the same tree, apart from spans and
+//! the types the analyzer fills in, with a few exceptions for trees the parser
+//! never produces:
+//! - An expression statement without a `;` that's followed by a statement
+//!   starting with a prefix operator is parsed as a single binary operation.
+//! - Interpolated expressions can't contain strings or blocks.
+//! - `NaN` has no literal, so it's printed as `(0.0 / 0.0)`.
+
+use std::fmt::{self, Display, Formatter, Write as _};
+
+use amaic_core::Span;
+use amaic_lexer::Operator;
+
+use crate::{ASTModule, ASTNode, ASTNodeType, FrontendType, FrontendTypeType, InterpolationPart};
+
+/// How many spaces each level of blocks is indented by.
+const INDENT: usize = 4;
+
+impl ASTNode {
+    /// Whether this node ends in an `if` without an `else`.
+    fn dangles(&self) -> bool {
+        match self.ty {
+            ASTNodeType::BinaryOp { op, ref rhs, .. } => {
+                !rhs.needs_parens_as_rhs(op) && rhs.dangles()
+            }
+            ASTNodeType::FunDef { ref body, .. }
+            | ASTNodeType::If {
+                else_body: Some(ref body),
+                ..
+            }
+            | ASTNodeType::LetDecl {
+                init: Some(ref body),
+                ..
+            }
+            | ASTNodeType::While { ref body, .. } => body.dangles(),
+            ASTNodeType::If {
+                else_body: None, ..
+            } => true,
+            ASTNodeType::UnaryOp {
+                op, ref operand, ..
+            } => !operand.needs_parens_as_operand(op) && operand.dangles(),
+            _ => false,
+        }
+    }
+
+    /// Whether this node needs parentheses as the left-hand side of `op`,
+    /// which is the case if it binds less tightly than `op`, or would take
+    /// `op` in.
+    #[must_use]
+    pub fn needs_parens_as_lhs(&self, op: Operator) -> bool {
+        match self.ty {
+            ASTNodeType::BinaryOp { op: inner, .. }
+                if op.precedence().0 >= inner.precedence().1 =>
+            {
+                true
+            }
+            _ => self.takes_in(op),
+        }
+    }
+
+    /// Whether this node needs parentheses as the operand of the prefix
+    /// operator `op`. A `-` directly followed by a literal is folded into a
+    /// negative literal.
+    #[must_use]
+    pub fn needs_parens_as_operand(&self, op: Operator) -> bool {
+        match self.ty {
+            ASTNodeType::BinaryOp { .. } => true,
+            ASTNodeType::FloatLit(float) => op == Operator::Minus && float.is_sign_positive(),
+            ASTNodeType::IntLit(int) => op == Operator::Minus && int >= 0,
+            _ => false,
+        }
+    }
+
+    /// Whether this node needs parentheses as the right-hand side of `op`.
+    #[must_use]
+    pub const fn needs_parens_as_rhs(&self, op: Operator) -> bool {
+        matches!(
+            self.ty,
+            ASTNodeType::BinaryOp { op: inner, .. } if inner.precedence().0 < op.precedence().1
+        )
+    }
+
+    /// Whether this node needs parentheses as the `then` branch of an `if`,
+    /// which is the case if it ends in an `if` without an `else`, that would
+    /// take in the `else` of the outer `if`.
+    #[must_use]
+    pub fn needs_parens_as_then_body(&self, has_else: bool) -> bool {
+        has_else && self.dangles()
+    }
+
+    /// Whether this node needs a space after the prefix operator `op`, which is
+    /// the case if it starts with the same operator, so that `- -5` isn't read
+    /// as `--5`.
+    #[must_use]
+    pub fn needs_space_as_operand(&self, op: Operator) -> bool {
+        match self.ty {
+            ASTNodeType::FloatLit(float) => op == Operator::Minus && float.is_sign_negative(),
+            ASTNodeType::IntLit(int) => op == Operator::Minus && int < 0,
+            ASTNodeType::UnaryOp { op: inner, .. } => inner == op,
+            _ => false,
+        }
+    }
+
+    /// Whether this node ends in an expression that would take in `op` after
+    /// it, such as the body of a `while`.
+    fn takes_in(&self, op: Operator) -> bool {
+        match self.ty {
+            ASTNodeType::BinaryOp {
+                op: inner, ref rhs, ..
+            } => !rhs.needs_parens_as_rhs(inner) && rhs.takes_in(op),
+            ASTNodeType::FunDef { .. }
+            | ASTNodeType::If { .. }
+            | ASTNodeType::LetDecl { init: Some(_), .. }
+            | ASTNodeType::While { .. } => true,
+            // `let x = 5` declares `x` with an initializer
+            ASTNodeType::LetDecl { init: None, .. } => op == Operator::Assign,
+            ASTNodeType::UnaryOp {
+                op: inner,
+                ref operand,
+                ..
+            } => !operand.needs_parens_as_operand(inner) && operand.takes_in(op),
+            _ => false,
+        }
+    }
+}
+
+impl Display for ASTModule {
+    /// Prints the module's doc comments, then each of its items on its own
+    /// line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(ref docs) = self.docs {
+            write_docs(f, "//!", docs, 0)?;
+            if !self.nodes.is_empty() {
+                f.write_char('\n')?;
+            }
+        }
+        for node in &self.nodes {
+            write_node(f, node, 0)?;
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ASTNode {
+    /// Prints the node as source-text, with the statements of blocks on their
+    /// own lines.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_node(f, self, 0)
+    }
+}
+
+impl Display for FrontendType {
+    /// Prints the type as it's written.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            FrontendTypeType::Identifier(ref name) => f.write_str(name),
+            FrontendTypeType::Unit => f.write_str("()"),
+            FrontendTypeType::Vector(ref inner) => write!(f, "[{inner}]"),
+        }
+    }
+}
+
+/// Writes a block, with each of its statements on its own line, and its `}`
+/// indented by `indent` spaces.
+fn write_block(out: &mut Formatter<'_>, stmts: &[ASTNode], indent: usize) -> fmt::Result {
+    if stmts.is_empty() {
+        return out.write_str("{}");
+    }
+    let inner = indent.strict_add(INDENT);
+    out.write_str("{\n")?;
+    for stmt in stmts {
+        write!(out, "{:inner$}", "")?;
+        write_node(out, stmt, inner)?;
+        out.write_char('\n')?;
+    }
+    write!(out, "{:indent$}}}", "")
+}
+
+/// Writes a call to `callee`.
+fn write_call(
+    out: &mut Formatter<'_>,
+    callee: &str,
+    args: &[ASTNode],
+    indent: usize,
+) -> fmt::Result {
+    write!(out, "{callee}(")?;
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            out.write_str(", ")?;
+        }
+        write_node(out, arg, indent)?;
+    }
+    out.write_char(')')
+}
+
+/// Writes the doc comments of a declaration, if any, followed by the
+/// indentation of the declaration.
+fn write_declaration_docs(
+    out: &mut Formatter<'_>,
+    docs: Option<&str>,
+    indent: usize,
+) -> fmt::Result {
+    if let Some(text) = docs {
+        write_docs(out, "///", text, indent)?;
+        write!(out, "{:indent$}", "")?;
+    }
+    Ok(())
+}
+
+/// Writes doc comments with the given marker, one per line of `docs`, each
+/// followed by a newline, and all but the first indented by `indent` spaces.
+fn write_docs(out: &mut Formatter<'_>, marker: &str, docs: &str, indent: usize) -> fmt::Result {
+    for (index, line) in docs.split('\n').enumerate() {
+        if index > 0 {
+            write!(out, "{:indent$}", "")?;
+        }
+        if line.is_empty() {
+            writeln!(out, "{marker}")?;
+        } else {
+            writeln!(out, "{marker} {line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the text of a string literal, escaping whatever can't appear in it
+/// as-is.
+fn write_escaped(out: &mut Formatter<'_>, text: &str) -> fmt::Result {
+    for ch in text.chars() {
+        match ch {
+            '\0' => out.write_str("\\0")?,
+            '\t' => out.write_str("\\t")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '"' | '\\' | '{' | '}' => write!(out, "\\{ch}")?,
+            _ if ch.is_control() => write!(out, "\\u{{{:X}}}", u32::from(ch))?,
+            _ => out.write_char(ch)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes a float literal that parses back to `float`.
+#[expect(
+    clippy::use_debug,
+    reason = "`Debug` prints floats as literals that parse back to them."
+)]
+fn write_float(out: &mut Formatter<'_>, float: f64) -> fmt::Result {
+    if float.is_nan() {
+        out.write_str("(0.0 / 0.0)")
+    } else if float.is_infinite() {
+        // too large for a float, so it's rounded to infinity
+        out.write_str(if float.is_sign_positive() {
+            "1e999"
+        } else {
+            "-1e999"
+        })
+    } else {
+        // unlike `Display`, `Debug` always prints a `.` or an exponent, so the
+        // literal is lexed as a float
+        write!(out, "{float:?}")
+    }
+}
+
+/// Writes a function definition, whose first line is indented by `indent`
+/// spaces.
+fn write_fun_def(
+    out: &mut Formatter<'_>,
+    name: &str,
+    params: &[(String, FrontendType, Span)],
+    return_ty: Option<&FrontendType>,
+    body: &ASTNode,
+    indent: usize,
+) -> fmt::Result {
+    write!(out, "let {name}(")?;
+    for (index, param) in params.iter().enumerate() {
+        if index > 0 {
+            out.write_str(", ")?;
+        }
+        write!(out, "{}: {}", param.0, param.1)?;
+    }
+    out.write_char(')')?;
+    if let Some(ty) = return_ty {
+        write!(out, ": {ty}")?;
+    }
+    out.write_str(" = ")?;
+    write_node(out, body, indent)
+}
+
+/// Writes an interpolated string literal.
+fn write_interpolation(
+    out: &mut Formatter<'_>,
+    parts: &[InterpolationPart],
+    indent: usize,
+) -> fmt::Result {
+    out.write_char('"')?;
+    for part in parts {
+        match *part {
+            InterpolationPart::Expr { ref expr, .. } => {
+                out.write_char('{')?;
+                write_node(out, expr, indent)?;
+                out.write_char('}')?;
+            }
+            InterpolationPart::Text(ref text) => write_escaped(out, text)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Writes `node`, whose first line is indented by `indent` spaces.
+fn write_node(out: &mut Formatter<'_>, node: &ASTNode, indent: usize) -> fmt::Result {
+    match node.ty {
+        ASTNodeType::BinaryOp {
+            ref lhs,
+            op,
+            ref rhs,
+            ..
+        } => {
+            write_parenthesized(out, lhs, lhs.needs_parens_as_lhs(op), indent)?;
+            write!(out, " {op} ")?;
+            write_parenthesized(out, rhs, rhs.needs_parens_as_rhs(op), indent)
+        }
+        ASTNodeType::Block(ref stmts) => write_block(out, stmts, indent),
+        ASTNodeType::Boolean(value) => write!(out, "{value}"),
+        ASTNodeType::FloatLit(float) => write_float(out, float),
+        ASTNodeType::FunCall {
+            ref args,
+            ref callee,
+        } => write_call(out, callee, args, indent),
+        ASTNodeType::FunDef {
+            ref body,
+            ref docs,
+            ref name,
+            ref params,
+            ref return_ty,
+        } => {
+            write_declaration_docs(out, docs.as_deref(), indent)?;
+            write_fun_def(out, name, params, return_ty.as_ref(), body, indent)
+        }
+        ASTNodeType::Identifier(ref name) => out.write_str(name),
+        ASTNodeType::If {
+            ref condition,
+            ref else_body,
+            ref then_body,
+        } => {
+            out.write_str("if ")?;
+            write_node(out, condition, indent)?;
+            out.write_str(" then ")?;
+            let parens = then_body.needs_parens_as_then_body(else_body.is_some());
+            write_parenthesized(out, then_body, parens, indent)?;
+            if let Some(ref otherwise) = *else_body {
+                out.write_str(" else ")?;
+                write_node(out, otherwise, indent)?;
+            }
+            Ok(())
+        }
+        ASTNodeType::IntLit(int) => write!(out, "{int}"),
+        ASTNodeType::Interpolation(ref parts) => write_interpolation(out, parts, indent),
+        ASTNodeType::LetDecl {
+            ref docs,
+            ref init,
+            ref name,
+            ref ty,
+        } => {
+            write_declaration_docs(out, docs.as_deref(), indent)?;
+            write!(out, "let {name}")?;
+            if let Some(ref annotation) = *ty {
+                write!(out, ": {annotation}")?;
+            }
+            if let Some(ref value) = *init {
+                out.write_str(" = ")?;
+                write_node(out, value, indent)?;
+            }
+            Ok(())
+        }
+        ASTNodeType::Semi(ref inner) => {
+            write_node(out, inner, indent)?;
+            out.write_char(';')
+        }
+        ASTNodeType::StringLit(ref text) => {
+            out.write_char('"')?;
+            write_escaped(out, text)?;
+            out.write_char('"')
+        }
+        ASTNodeType::UnaryOp {
+            op, ref operand, ..
+        } => {
+            let parens = operand.needs_parens_as_operand(op);
+            write!(out, "{op}")?;
+            if !parens && operand.needs_space_as_operand(op) {
+                out.write_char(' ')?;
+            }
+            write_parenthesized(out, operand, parens, indent)
+        }
+        ASTNodeType::Unit => out.write_str("()"),
+        ASTNodeType::While {
+            ref body,
+            ref condition,
+        } => {
+            out.write_str("while ")?;
+            write_node(out, condition, indent)?;
+            out.write_str(" do ")?;
+            write_node(out, body, indent)
+        }
+    }
+}
+
+/// Writes `node`, in parentheses if `parens` is set.
+fn write_parenthesized(
+    out: &mut Formatter<'_>,
+    node: &ASTNode,
+    parens: bool,
+    indent: usize,
+) -> fmt::Result {
+    if parens {
+        out.write_char('(')?;
+        write_node(out, node, indent)?;
+        out.write_char(')')
+    } else {
+        write_node(out, node, indent)
+    }
+}
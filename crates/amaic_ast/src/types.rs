@@ -1,24 +1,63 @@
+//! See [`Type`].
+
+/// The type of an expression, as determined by the analyzer.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "The analyzer builds and matches every type."
+)]
 pub enum Type {
-    Int, Float, String, Bool, Unit, Unknown,
-    Vector(Box<Type>), Func(Vec<Type>, Box<Type>),
+    /// `bool`.
+    Bool,
+
+    /// The type of an expression whose error was already reported. It's
+    /// accepted wherever a type is expected, so one mistake doesn't cascade.
+    Error,
+
+    /// `float`.
+    Float,
+
+    /// A function taking the first types and returning the last.
+    Func(Vec<Self>, Box<Self>),
+
+    /// `int`.
+    Int,
+
+    /// `string`.
+    String,
+
+    /// `()`.
+    Unit,
+
+    /// The type of a variable declared without a type or initializer, until
+    /// it's first assigned.
+    Unknown,
+
+    /// `[ty]`.
+    Vector(Box<Self>),
 }
 
 impl Type {
+    /// The type as it's written in the source-text.
+    #[must_use]
     pub fn display(&self) -> String {
-        match self {
-            Type::Int => "int".to_string(),
-            Type::Float => "float".to_string(),
-            Type::String => "string".to_string(),
-            Type::Bool => "bool".to_string(),
-            Type::Unit => "()".to_string(),
-            Type::Unknown => "{unknown}".to_string(),
-            Type::Vector(ty) => format!("[{}]", ty.display()),
-            Self::Func(args, return_ty) => format!(
+        match *self {
+            Self::Bool => "bool".to_owned(),
+            Self::Error => "{error}".to_owned(),
+            Self::Float => "float".to_owned(),
+            Self::Func(ref args, ref return_ty) => format!(
                 "$({}) -> {}",
-                args.iter().map(|arg| arg.display()).collect::<Vec<_>>().join(", "),
+                args.iter()
+                    .map(Self::display)
+                    .collect::<Vec<_>>()
+                    .join(", "),
                 return_ty.display(),
             ),
+            Self::Int => "int".to_owned(),
+            Self::String => "string".to_owned(),
+            Self::Unit => "()".to_owned(),
+            Self::Unknown => "{unknown}".to_owned(),
+            Self::Vector(ref ty) => format!("[{}]", ty.display()),
         }
     }
-}
\ No newline at end of file
+}
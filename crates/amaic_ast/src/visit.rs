@@ -0,0 +1,292 @@
+//! Traversal of syntax trees, through [`Visitor`] for shared references and
+//! [`VisitorMut`] for mutable ones.
+//!
+//! Each `visit_*` method defaults to the `walk_*` function of the same name,
+//! which visits whatever is directly inside. A pass overrides only the methods
+//! for what it cares about, and calls the `walk_*` function from them to keep
+//! going deeper. The walkers are the only place that lists what every node
+//! contains.
+
+use amaic_core::Span;
+
+use crate::{ASTModule, ASTNode, ASTNodeType, FrontendType, FrontendTypeType, InterpolationPart};
+
+/// A function parameter: its name, type, and the span of the whole parameter.
+pub type Param = (String, FrontendType, Span);
+
+/// Visits the parts of a syntax tree, borrowed for `'ast`.
+pub trait Visitor<'ast>: Sized {
+    /// Visits a part of an interpolated string literal.
+    fn visit_interpolation_part(&mut self, part: &'ast InterpolationPart) {
+        walk_interpolation_part(self, part);
+    }
+
+    /// Visits a whole module.
+    fn visit_module(&mut self, module: &'ast ASTModule) {
+        walk_module(self, module);
+    }
+
+    /// Visits a node, whether it's an expression, a statement or an item.
+    fn visit_node(&mut self, node: &'ast ASTNode) {
+        walk_node(self, node);
+    }
+
+    /// Visits a parameter of a function definition.
+    fn visit_param(&mut self, param: &'ast Param) {
+        walk_param(self, param);
+    }
+
+    /// Visits a written type, such as a parameter's or a return type.
+    fn visit_type(&mut self, ty: &'ast FrontendType) {
+        walk_type(self, ty);
+    }
+}
+
+/// Visits a syntax tree mutably, in the same order as [`Visitor`].
+pub trait VisitorMut: Sized {
+    /// Visits a part of an interpolated string literal.
+    fn visit_interpolation_part_mut(&mut self, part: &mut InterpolationPart) {
+        walk_interpolation_part_mut(self, part);
+    }
+
+    /// Visits a whole module.
+    fn visit_module_mut(&mut self, module: &mut ASTModule) {
+        walk_module_mut(self, module);
+    }
+
+    /// Visits a node, whether it's an expression, a statement or an item.
+    fn visit_node_mut(&mut self, node: &mut ASTNode) {
+        walk_node_mut(self, node);
+    }
+
+    /// Visits a parameter of a function definition.
+    fn visit_param_mut(&mut self, param: &mut Param) {
+        walk_param_mut(self, param);
+    }
+
+    /// Visits a written type, such as a parameter's or a return type.
+    fn visit_type_mut(&mut self, ty: &mut FrontendType) {
+        walk_type_mut(self, ty);
+    }
+}
+
+/// Visits the expression of an interpolation part, if it has one.
+pub fn walk_interpolation_part<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    part: &'ast InterpolationPart,
+) {
+    match *part {
+        InterpolationPart::Expr { ref expr, .. } => visitor.visit_node(expr),
+        InterpolationPart::Text(_) => {}
+    }
+}
+
+/// Visits the expression of an interpolation part mutably, if it has one.
+pub fn walk_interpolation_part_mut<V: VisitorMut>(visitor: &mut V, part: &mut InterpolationPart) {
+    match *part {
+        InterpolationPart::Expr { ref mut expr, .. } => visitor.visit_node_mut(expr),
+        InterpolationPart::Text(_) => {}
+    }
+}
+
+/// Visits the top-level nodes of `module`.
+pub fn walk_module<'ast, V: Visitor<'ast>>(visitor: &mut V, module: &'ast ASTModule) {
+    for node in &module.nodes {
+        visitor.visit_node(node);
+    }
+}
+
+/// Visits the top-level nodes of `module` mutably.
+pub fn walk_module_mut<V: VisitorMut>(visitor: &mut V, module: &mut ASTModule) {
+    for node in &mut module.nodes {
+        visitor.visit_node_mut(node);
+    }
+}
+
+/// Visits the nodes, types, parameters and interpolation parts directly inside
+/// `node`, in source order.
+pub fn walk_node<'ast, V: Visitor<'ast>>(visitor: &mut V, node: &'ast ASTNode) {
+    match node.ty {
+        ASTNodeType::BinaryOp {
+            ref lhs, ref rhs, ..
+        } => {
+            visitor.visit_node(lhs);
+            visitor.visit_node(rhs);
+        }
+        ASTNodeType::Block(ref nodes)
+        | ASTNodeType::FunCall {
+            args: ref nodes, ..
+        } => {
+            for inner in nodes {
+                visitor.visit_node(inner);
+            }
+        }
+        ASTNodeType::Boolean(_)
+        | ASTNodeType::FloatLit(_)
+        | ASTNodeType::Identifier(_)
+        | ASTNodeType::IntLit(_)
+        | ASTNodeType::StringLit(_)
+        | ASTNodeType::Unit => {}
+        ASTNodeType::FunDef {
+            ref body,
+            ref params,
+            ref return_ty,
+            ..
+        } => {
+            for param in params {
+                visitor.visit_param(param);
+            }
+            if let Some(ref return_ty) = *return_ty {
+                visitor.visit_type(return_ty);
+            }
+            visitor.visit_node(body);
+        }
+        ASTNodeType::If {
+            ref condition,
+            ref else_body,
+            ref then_body,
+        } => {
+            visitor.visit_node(condition);
+            visitor.visit_node(then_body);
+            if let Some(ref else_body) = *else_body {
+                visitor.visit_node(else_body);
+            }
+        }
+        ASTNodeType::Interpolation(ref parts) => {
+            for part in parts {
+                visitor.visit_interpolation_part(part);
+            }
+        }
+        ASTNodeType::LetDecl {
+            ref init, ref ty, ..
+        } => {
+            if let Some(ref ty) = *ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(ref init) = *init {
+                visitor.visit_node(init);
+            }
+        }
+        ASTNodeType::Semi(ref inner)
+        | ASTNodeType::UnaryOp {
+            operand: ref inner, ..
+        } => visitor.visit_node(inner),
+        ASTNodeType::While {
+            ref body,
+            ref condition,
+        } => {
+            visitor.visit_node(condition);
+            visitor.visit_node(body);
+        }
+    }
+}
+
+/// Visits the nodes, types, parameters and interpolation parts directly inside
+/// `node` mutably, in source order.
+pub fn walk_node_mut<V: VisitorMut>(visitor: &mut V, node: &mut ASTNode) {
+    match node.ty {
+        ASTNodeType::BinaryOp {
+            ref mut lhs,
+            ref mut rhs,
+            ..
+        } => {
+            visitor.visit_node_mut(lhs);
+            visitor.visit_node_mut(rhs);
+        }
+        ASTNodeType::Block(ref mut nodes)
+        | ASTNodeType::FunCall {
+            args: ref mut nodes,
+            ..
+        } => {
+            for inner in nodes {
+                visitor.visit_node_mut(inner);
+            }
+        }
+        ASTNodeType::Boolean(_)
+        | ASTNodeType::FloatLit(_)
+        | ASTNodeType::Identifier(_)
+        | ASTNodeType::IntLit(_)
+        | ASTNodeType::StringLit(_)
+        | ASTNodeType::Unit => {}
+        ASTNodeType::FunDef {
+            ref mut body,
+            ref mut params,
+            ref mut return_ty,
+            ..
+        } => {
+            for param in params {
+                visitor.visit_param_mut(param);
+            }
+            if let Some(ref mut return_ty) = *return_ty {
+                visitor.visit_type_mut(return_ty);
+            }
+            visitor.visit_node_mut(body);
+        }
+        ASTNodeType::If {
+            ref mut condition,
+            ref mut else_body,
+            ref mut then_body,
+        } => {
+            visitor.visit_node_mut(condition);
+            visitor.visit_node_mut(then_body);
+            if let Some(ref mut else_body) = *else_body {
+                visitor.visit_node_mut(else_body);
+            }
+        }
+        ASTNodeType::Interpolation(ref mut parts) => {
+            for part in parts {
+                visitor.visit_interpolation_part_mut(part);
+            }
+        }
+        ASTNodeType::LetDecl {
+            ref mut init,
+            ref mut ty,
+            ..
+        } => {
+            if let Some(ref mut ty) = *ty {
+                visitor.visit_type_mut(ty);
+            }
+            if let Some(ref mut init) = *init {
+                visitor.visit_node_mut(init);
+            }
+        }
+        ASTNodeType::Semi(ref mut inner)
+        | ASTNodeType::UnaryOp {
+            operand: ref mut inner,
+            ..
+        } => visitor.visit_node_mut(inner),
+        ASTNodeType::While {
+            ref mut body,
+            ref mut condition,
+        } => {
+            visitor.visit_node_mut(condition);
+            visitor.visit_node_mut(body);
+        }
+    }
+}
+
+/// Visits the type of a parameter.
+pub fn walk_param<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast Param) {
+    visitor.visit_type(&param.1);
+}
+
+/// Visits the type of a parameter mutably.
+pub fn walk_param_mut<V: VisitorMut>(visitor: &mut V, param: &mut Param) {
+    visitor.visit_type_mut(&mut param.1);
+}
+
+/// Visits the types inside `ty`, such as a vector's element type.
+pub fn walk_type<'ast, V: Visitor<'ast>>(visitor: &mut V, ty: &'ast FrontendType) {
+    match ty.ty {
+        FrontendTypeType::Identifier(_) | FrontendTypeType::Unit => {}
+        FrontendTypeType::Vector(ref inner) => visitor.visit_type(inner),
+    }
+}
+
+/// Visits the types inside `ty` mutably, such as a vector's element type.
+pub fn walk_type_mut<V: VisitorMut>(visitor: &mut V, ty: &mut FrontendType) {
+    match ty.ty {
+        FrontendTypeType::Identifier(_) | FrontendTypeType::Unit => {}
+        FrontendTypeType::Vector(ref mut inner) => visitor.visit_type_mut(inner),
+    }
+}
@@ -1,235 +1,359 @@
-use crate::Span;
+//! See [`Diagnostic`].
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::{
+    ErrorCode, SourceMap, Span,
+    render::{self, Annotation},
+};
+
+use colored::{Color, Colorize as _};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "Emitters match on every severity, so that adding one points out each of them."
+)]
+pub enum Severity {
+    /// The program can't be compiled.
+    Error,
+
+    /// A suggestion for how to fix the problem.
+    Help,
+
+    /// Extra context about the problem.
+    Note,
+
+    /// The program compiles, but is likely wrong.
+    Warning,
+}
+
+impl Severity {
+    /// The severity's name, uncolored.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Help => "help",
+            Self::Note => "note",
+            Self::Warning => "warning",
+        }
+    }
+
+    /// The color the severity and its underlines are rendered in.
+    #[must_use]
+    pub const fn color(self) -> Color {
+        match self {
+            Self::Error => Color::BrightRed,
+            Self::Help => Color::BrightCyan,
+            Self::Note => Color::BrightGreen,
+            Self::Warning => Color::BrightYellow,
+        }
+    }
+
+    /// The severity as it's shown before a diagnostic's message.
+    #[must_use]
+    pub fn display(self) -> String {
+        self.as_str().color(self.color()).bold().to_string()
+    }
+}
+
+/// How confident a [`Suggestion`] is that applying it fixes the diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "Emitters match on every applicability, so that adding one points out each of them."
+)]
+pub enum Applicability {
+    /// The suggestion contains placeholders, such as `...`, to fill in.
+    HasPlaceholders,
+
+    /// The suggestion is definitely what the user meant, and can be applied
+    /// automatically.
+    MachineApplicable,
+
+    /// The suggestion is probably right, but should be reviewed by the user.
+    MaybeIncorrect,
+}
+
+/// A message attached to a span, rendered inline next to its underline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Label {
+    /// The message shown after the underline, if any.
+    pub message: Option<String>,
+
+    /// The span that's underlined.
+    pub span: Span,
+}
+
+/// A note or help line, rendered beneath a diagnostic's snippets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SubDiagnostic {
+    /// The line's text.
+    pub message: String,
+
+    /// Whether the line is a note or a help.
+    pub severity: Severity,
+}
+
+/// An edit that replaces the source in `span` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Suggestion {
+    /// How confident the suggestion is.
+    pub applicability: Applicability,
+
+    /// What the edit does, shown above the fixed snippet.
+    pub message: String,
+
+    /// The text that replaces the span.
+    pub replacement: String,
+
+    /// The span that's replaced.
+    pub span: Span,
+}
+
+/// A problem found in a program, pointing at where in the source-text it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Diagnostic {
-    pub path: String,
+    /// Notes and helps shown beneath the snippets.
+    pub children: Vec<SubDiagnostic>,
+
+    /// The stable code identifying the kind of problem, if it has one.
+    pub code: Option<&'static str>,
+
+    /// The name of the lint that reported the problem, if a lint did.
+    pub lint: Option<&'static str>,
+
+    /// The message describing the problem.
     pub primary_err: String,
+
+    /// The label shown next to the primary span's underline, if any.
+    pub primary_label: Option<String>,
+
+    /// The span the problem is at.
     pub primary_span: Span,
-    pub secondary_messages: Vec<(Option<String>, Span)>,
+
+    /// Other spans relevant to the problem, each underlined in its own
+    /// snippet.
+    pub secondary_labels: Vec<Label>,
+
+    /// How serious the problem is.
+    pub severity: Severity,
+
+    /// Edits that might fix the problem.
+    pub suggestions: Vec<Suggestion>,
 }
 
-pub fn determine_line(line_starts: &[usize], pos: usize) -> usize {
-    match line_starts.binary_search(&pos) {
-        Ok(line) => line,
-        Err(line) => line.saturating_sub(1),
+impl Diagnostic {
+    /// The error code or, failing that, the lint name identifying the kind of
+    /// problem, for tools to group diagnostics by.
+    #[must_use]
+    pub const fn code_or_lint(&self) -> Option<&'static str> {
+        match self.code {
+            Some(code) => Some(code),
+            None => self.lint,
+        }
     }
-}
 
-pub fn create_src_ref(
-    output: &mut String,
-    digits_len: u32,
-    path: &str,
-    start_line: usize,
-    start_col: usize,
-    end_line: usize,
-    end_col: usize,
-    lines: &[&str],
-) {
-    use colored::Colorize;
-
-    output.push_str(&format!(
-        "{}{} {}\n",
-        " ".repeat(digits_len as usize + 1),
-        "┌──".cyan().bold(),
-        format!("{}:{}:{}", path, start_line + 1, start_col + 1).italic()
-    ));
-    output.push_str(&format!(
-        "{:w$} {}\n",
-        " ",
-        "│".cyan().bold(),
-        w = digits_len as usize
-    ));
-
-    for l_id in start_line..=end_line {
-        let ltxt = lines[l_id];
-        output.push_str(&format!(
-            "{:w$} {} {ltxt}\n",
-            (l_id + 1).to_string().cyan().bold(),
-            "│".cyan().bold(),
-            w = digits_len as usize
-        ));
-
-        let mut carets = String::new();
-        if l_id == start_line && l_id == end_line {
-            carets.push_str(&" ".repeat(start_col));
-            carets.push_str(
-                &"^".repeat(end_col - start_col)
-                    .bright_red()
-                    .bold()
-                    .to_string(),
-            );
-        } else if l_id == start_line {
-            carets.push_str(&" ".repeat(start_col));
-            carets.push_str(
-                &"^".repeat(ltxt.len() - start_col)
-                    .bright_red()
-                    .bold()
-                    .to_string(),
-            );
-        } else if l_id == end_line {
-            carets.push_str(&"^".repeat(end_col).bright_red().bold().to_string());
-        } else {
-            carets.push_str(&"^".repeat(ltxt.len()).bright_red().bold().to_string());
+    /// Renders the diagnostic for a terminal, with snippets of the files in
+    /// `sources` it points into.
+    #[must_use]
+    pub fn display(&self, sources: &SourceMap) -> String {
+        let mut output = String::new();
+
+        let mut annotations = vec![Annotation {
+            color: self.severity.color(),
+            is_primary: true,
+            label: self.primary_label.as_deref(),
+            marker: '^',
+            span: self.primary_span,
+        }];
+        annotations.extend(self.secondary_labels.iter().map(|label| Annotation {
+            color: Color::BrightBlue,
+            is_primary: false,
+            label: label.message.as_deref(),
+            marker: '-',
+            span: label.span,
+        }));
+        let gutter_width = render::gutter_width(sources, &annotations);
+
+        let header = self.code.map_or_else(
+            || self.severity.as_str().to_owned(),
+            |code| format!("{}[{code}]", self.severity.as_str()),
+        );
+        render::write(
+            &mut output,
+            format_args!(
+                "{}: {}\n",
+                header.color(self.severity.color()).bold(),
+                self.primary_err
+            ),
+        );
+        render::render_snippets(&mut output, sources, &annotations, gutter_width);
+
+        for suggestion in &self.suggestions {
+            render::render_suggestion(&mut output, suggestion, sources.file_of(suggestion.span));
         }
-        output.push_str(&format!(
-            "{:w$} {} {}\n",
-            " ",
-            "│".cyan().bold(),
-            carets,
-            w = digits_len as usize
-        ));
-    }
-
-    output.push_str(&format!(
-        "{:w$} {}\n",
-        " ",
-        "│".cyan().bold(),
-        w = digits_len as usize
-    ));
-}
 
-pub fn create_src_ref_without_path(
-    output: &mut String,
-    digits_len: u32,
-    start_line: usize,
-    start_col: usize,
-    end_line: usize,
-    end_col: usize,
-    lines: &[&str],
-) {
-    use colored::Colorize;
-
-    output.push_str(&format!(
-        "{:w$} {}\n",
-        " ",
-        "│".cyan().bold(),
-        w = digits_len as usize
-    ));
-
-    for l_id in start_line..=end_line {
-        let ltxt = lines[l_id];
-        output.push_str(&format!(
-            "{:w$} {} {ltxt}\n",
-            (l_id + 1).to_string().cyan().bold(),
-            "│".cyan().bold(),
-            w = digits_len as usize
-        ));
-
-        let mut carets = String::new();
-        if l_id == start_line && l_id == end_line {
-            carets.push_str(&" ".repeat(start_col));
-            carets.push_str(
-                &"^".repeat(end_col - start_col)
-                    .bright_red()
-                    .bold()
-                    .to_string(),
-            );
-        } else if l_id == start_line {
-            carets.push_str(&" ".repeat(start_col));
-            carets.push_str(
-                &"^".repeat(ltxt.len() - start_col)
-                    .bright_red()
-                    .bold()
-                    .to_string(),
+        for child in &self.children {
+            render::write(
+                &mut output,
+                format_args!(
+                    "{:w$} {} {}: {}\n",
+                    " ",
+                    "=".cyan().bold(),
+                    child.severity.as_str().bold(),
+                    child.message,
+                    w = gutter_width
+                ),
             );
-        } else if l_id == end_line {
-            carets.push_str(&"^".repeat(end_col).bright_red().bold().to_string());
-        } else {
-            carets.push_str(&"^".repeat(ltxt.len()).bright_red().bold().to_string());
         }
-        output.push_str(&format!(
-            "{:w$} {} {}\n",
-            " ",
-            "│".cyan().bold(),
-            carets,
-            w = digits_len as usize
-        ));
-    }
-
-    output.push_str(&format!(
-        "{:w$} {}\n",
-        " ",
-        "│".cyan().bold(),
-        w = digits_len as usize
-    ));
-}
 
-impl Diagnostic {
-    pub fn new<P: ToString, M: AsRef<str>>(
-        path: P,
-        primary_err: M,
-        primary_span: Span,
-    ) -> Diagnostic {
-        Diagnostic {
-            path: path.to_string(),
-            primary_err: primary_err.as_ref().to_string(),
+        output
+    }
+
+    /// Whether the diagnostic is an error, rather than a warning.
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Creates an error with the message `primary_err`, pointing at
+    /// `primary_span`.
+    #[must_use]
+    pub fn new<M: AsRef<str>>(primary_err: M, primary_span: Span) -> Self {
+        Self {
+            children: Vec::new(),
+            code: None,
+            lint: None,
+            primary_err: primary_err.as_ref().to_owned(),
+            primary_label: None,
             primary_span,
-            secondary_messages: Vec::new(),
+            secondary_labels: Vec::new(),
+            severity: Severity::Error,
+            suggestions: Vec::new(),
         }
     }
 
-    pub fn with_secondary_message<M: AsRef<str>>(
-        mut self,
-        msg: Option<M>,
-        span: Span,
-    ) -> Diagnostic {
-        self.secondary_messages
-            .push((msg.map(|m| m.as_ref().to_string()), span));
+    /// Adds a note or help line, shown beneath the snippets.
+    #[must_use]
+    pub fn with_child<M: AsRef<str>>(mut self, severity: Severity, msg: M) -> Self {
+        self.children.push(SubDiagnostic {
+            message: msg.as_ref().to_owned(),
+            severity,
+        });
         self
     }
 
-    pub fn display(&self, line_starts: &[usize], lines: &[&str]) -> String {
-        use colored::Colorize;
+    /// Sets the stable code identifying the kind of problem.
+    #[must_use]
+    pub const fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code.code);
+        self
+    }
 
-        let mut output = String::new();
+    /// Adds a help line, suggesting how to fix the problem.
+    #[must_use]
+    pub fn with_help<M: AsRef<str>>(self, msg: M) -> Self {
+        self.with_child(Severity::Help, msg)
+    }
 
-        let start_line = determine_line(&line_starts, self.primary_span.start());
-        let start_col = self.primary_span.start() - line_starts[start_line];
+    /// Sets the name of the lint that reported the problem.
+    #[must_use]
+    pub const fn with_lint(mut self, lint: &'static str) -> Self {
+        self.lint = Some(lint);
+        self
+    }
 
-        let end_line = determine_line(&line_starts, self.primary_span.end());
-        let end_col = self.primary_span.end() - line_starts[end_line];
+    /// Adds a note line, giving extra context about the problem.
+    #[must_use]
+    pub fn with_note<M: AsRef<str>>(self, msg: M) -> Self {
+        self.with_child(Severity::Note, msg)
+    }
 
-        let digits_len = (end_line + 1).ilog10() + 1;
+    /// Sets the label shown next to the primary span's underline.
+    #[must_use]
+    pub fn with_primary_label<M: AsRef<str>>(mut self, msg: M) -> Self {
+        self.primary_label = Some(msg.as_ref().to_owned());
+        self
+    }
 
-        output.push_str(&format!(
-            "{}: {}\n",
-            "error".bright_red().bold(),
-            self.primary_err
-        ));
-        create_src_ref(
-            &mut output,
-            digits_len,
-            &self.path,
-            start_line,
-            start_col,
-            end_line,
-            end_col,
-            lines,
-        );
+    /// Adds a secondary span, underlined in its own snippet below the primary
+    /// one, with `msg` shown next to it.
+    #[must_use]
+    pub fn with_secondary_label<M: AsRef<str>>(mut self, msg: Option<M>, span: Span) -> Self {
+        self.secondary_labels.push(Label {
+            message: msg.map(|msg| msg.as_ref().to_owned()),
+            span,
+        });
+        self
+    }
 
-        for (msg, span) in &self.secondary_messages {
-            let start_line = determine_line(&line_starts, span.start());
-            let start_col = span.start() - line_starts[start_line];
+    /// Sets how serious the problem is.
+    #[must_use]
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
 
-            let end_line = determine_line(&line_starts, span.start());
-            let end_col = span.end() - line_starts[end_line];
+    /// Adds an edit that replaces the source in `span` with `replacement`.
+    #[must_use]
+    pub fn with_suggestion<M: AsRef<str>, R: Into<String>>(
+        mut self,
+        msg: M,
+        span: Span,
+        replacement: R,
+        applicability: Applicability,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            applicability,
+            message: msg.as_ref().to_owned(),
+            replacement: replacement.into(),
+            span,
+        });
+        self
+    }
+}
 
-            let digits_len = (end_line + 1).ilog10() + 1;
+impl Suggestion {
+    /// Applies the given suggestions to `source`. Suggestions overlapping one
+    /// that starts earlier are skipped, and the number applied is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a suggestion's span isn't within `source`.
+    pub fn apply_all<'sugg>(
+        source: &str,
+        suggestions: impl IntoIterator<Item = &'sugg Self>,
+    ) -> (String, usize) {
+        let mut suggestions = suggestions.into_iter().collect::<Vec<_>>();
+        suggestions.sort_by_key(|suggestion| (suggestion.span.start(), suggestion.span.end()));
 
-            if let Some(m) = msg {
-                output.push_str(&format!("{m}\n"))
+        let mut output = String::with_capacity(source.len());
+        let mut applied: usize = 0;
+        let mut pos = 0;
+        for suggestion in suggestions {
+            if suggestion.span.start() < pos {
+                continue;
             }
-            create_src_ref_without_path(
-                &mut output,
-                digits_len,
-                start_line,
-                start_col,
-                end_line,
-                end_col,
-                lines,
+            output.push_str(
+                source
+                    .get(pos..suggestion.span.start())
+                    .expect("suggestions should be within the source"),
             );
+            output.push_str(&suggestion.replacement);
+            pos = suggestion.span.end();
+            applied = applied.strict_add(1);
         }
+        output.push_str(
+            source
+                .get(pos..)
+                .expect("suggestions should be within the source"),
+        );
 
-        output
+        (output, applied)
     }
 }
@@ -0,0 +1,73 @@
+//! The registry of stable [`ErrorCode`]s carried by diagnostics.
+//!
+//! Codes are never reused or renumbered, so they can be referred to from
+//! documentation and configuration even as the messages themselves change.
+
+/// Declares each code along with its explanation, which is read from
+/// `error_codes/<code>.md`.
+macro_rules! error_codes {
+    ($($code:ident: $title:literal,)*) => {
+        $(
+            #[doc = $title]
+            pub const $code: ErrorCode = ErrorCode {
+                code: stringify!($code),
+                explanation: include_str!(concat!("error_codes/", stringify!($code), ".md")),
+                title: $title,
+            };
+        )*
+
+        /// Every registered error code, in ascending order.
+        pub const ALL: &[ErrorCode] = &[$($code),*];
+    };
+}
+
+error_codes! {
+    E0001: "Unexpected token",
+    E0002: "Item is not allowed at the root level",
+    E0003: "Cannot find variable in scope",
+    E0004: "Mismatched types",
+    E0005: "Cannot find type",
+    E0006: "Operator cannot be applied to the given types",
+    E0007: "Invalid assignment target",
+    E0008: "Condition is not a boolean",
+    E0009: "Missing `else` clause",
+    E0010: "Parameter defined more than once",
+    E0011: "Wrong number of arguments",
+    E0012: "Called value is not a function",
+    E0013: "Invalid escape in string literal",
+    E0014: "Invalid interpolation in string literal",
+    E0015: "Value cannot be interpolated into a string",
+    E0016: "Invalid numeric literal",
+    E0017: "Misplaced doc comment",
+    E0018: "Unexpected character",
+    E0019: "Unterminated string literal",
+    E0020: "Unterminated block comment",
+}
+
+/// A stable identifier for a kind of diagnostic, with its long-form
+/// explanation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ErrorCode {
+    /// The code itself, e.g. `E0004`.
+    pub code: &'static str,
+
+    /// A markdown explanation of the error, with examples.
+    pub explanation: &'static str,
+
+    /// A short summary of the error.
+    pub title: &'static str,
+}
+
+/// Looks up an error code, ignoring case and allowing the leading `E` and
+/// zeroes to be left out, so that `e4` and `4` both find `E0004`.
+#[must_use]
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    let digits = code
+        .strip_prefix(['E', 'e'])
+        .unwrap_or(code)
+        .parse::<u16>()
+        .ok()?;
+    let normalized = format!("E{digits:04}");
+    ALL.iter().find(|error_code| error_code.code == normalized)
+}
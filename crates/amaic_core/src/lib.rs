@@ -6,7 +6,14 @@
 //! This API is completely-unstable and subject to change.
 
 mod diagnostic;
+pub mod error_codes;
+mod position;
+mod render;
+mod source_map;
 mod span;
 
 pub use diagnostic::*;
+pub use error_codes::ErrorCode;
+pub use position::{LineColumn, PositionEncoding};
+pub use source_map::{FileId, SourceFile, SourceMap};
 pub use span::Span;
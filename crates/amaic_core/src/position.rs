@@ -0,0 +1,53 @@
+//! See [`LineColumn`] and [`PositionEncoding`].
+
+/// The unit columns are counted in, which differs between tools: editors
+/// speaking the Language Server Protocol count UTF-16 code units by default,
+/// while humans count characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "These are the encodings the Language Server Protocol defines."
+)]
+pub enum PositionEncoding {
+    /// Unicode scalar values, or UTF-32 code units.
+    Char,
+
+    /// UTF-16 code units, so that characters outside of the Basic Multilingual
+    /// Plane count twice.
+    #[default]
+    Utf16,
+
+    /// Bytes of UTF-8.
+    Utf8,
+}
+
+impl PositionEncoding {
+    /// How many of the encoding's units `ch` takes up.
+    #[must_use]
+    pub const fn len_of(self, ch: char) -> usize {
+        match self {
+            Self::Char => 1,
+            Self::Utf16 => ch.len_utf16(),
+            Self::Utf8 => ch.len_utf8(),
+        }
+    }
+}
+
+/// A 0-based line and column within a [`SourceFile`](crate::SourceFile), with
+/// the column counted in the units of some [`PositionEncoding`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "Positions are ordered by their line before their column."
+)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "A position is only ever a line and a column."
+)]
+pub struct LineColumn {
+    /// The 0-based line.
+    pub line: usize,
+
+    /// The 0-based column.
+    pub column: usize,
+}
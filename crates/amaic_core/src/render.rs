@@ -0,0 +1,637 @@
+//! Renders the source snippets of a [`Diagnostic`](crate::Diagnostic).
+//!
+//! Columns are measured in terminal cells rather than bytes, so underlines stay
+//! aligned after tabs, wide characters and combining marks.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Write as _},
+    ops::Range,
+};
+
+use colored::{Color, Colorize as _};
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::{FileId, Severity, SourceFile, SourceMap, Span, Suggestion};
+
+/// Multi-line spans with more lines than this have their middle elided.
+const MAX_MULTILINE_CONTEXT: usize = 1;
+
+/// How many cells a tab is expanded to.
+const TAB_WIDTH: usize = 4;
+
+/// A span to underline, with the label to put next to it.
+#[derive(Debug, Clone, Copy)]
+pub struct Annotation<'label> {
+    /// The colour of the underline and label.
+    pub color: Color,
+
+    /// Whether this is the diagnostic's primary span, which is drawn over the
+    /// others where they overlap.
+    pub is_primary: bool,
+
+    /// The label to put next to the underline, if any.
+    pub label: Option<&'label str>,
+
+    /// The character the span is underlined with.
+    pub marker: char,
+
+    /// The span to underline.
+    pub span: Span,
+}
+
+/// A row of cells below a source line, for drawing underlines and connectors.
+#[derive(Default)]
+struct Canvas {
+    /// Each cell's character, and its colour if it's been drawn on.
+    cells: Vec<(char, Option<Color>)>,
+}
+
+impl Canvas {
+    /// Draws `ch` across `columns`.
+    fn fill(&mut self, columns: Range<usize>, ch: char, color: Color) {
+        for column in columns {
+            self.put(column, ch, color);
+        }
+    }
+
+    /// Draws `ch` at `column`, growing the row to reach it.
+    fn put(&mut self, column: usize, ch: char, color: Color) {
+        if self.cells.len() <= column {
+            self.cells.resize(column.strict_add(1), (' ', None));
+        }
+        if let Some(cell) = self.cells.get_mut(column) {
+            *cell = (ch, Some(color));
+        }
+    }
+
+    /// Renders the cells, colouring each run of same-coloured cells at once.
+    fn render(&self) -> String {
+        let mut output = String::new();
+        let mut cells = self.cells.iter().peekable();
+        while let Some(&(first, color)) = cells.next() {
+            let mut run = String::from(first);
+            while let Some(&(ch, _)) = cells.next_if(|&&(_, next)| next == color) {
+                run.push(ch);
+            }
+            match color {
+                Some(color) => output.push_str(&run.color(color).bold().to_string()),
+                None => output.push_str(&run),
+            }
+        }
+        output
+    }
+
+    /// Pads or truncates the row to `width` cells.
+    fn resize(&mut self, width: usize) {
+        self.cells.resize(width, (' ', None));
+    }
+}
+
+/// Writes the borders and line numbers around rendered rows.
+struct Gutter<'out> {
+    /// The rendered output so far.
+    output: &'out mut String,
+
+    /// The width of the line numbers.
+    width: usize,
+}
+
+impl Gutter<'_> {
+    /// Writes `canvas`, followed directly by `label`.
+    fn annotation(&mut self, canvas: &Canvas, label: Option<(&str, Color)>) {
+        let mut row = format!(
+            "{:w$} {} {}",
+            " ",
+            "│".cyan().bold(),
+            canvas.render(),
+            w = self.width
+        );
+        if let Some((label, color)) = label {
+            row.push_str(&label.color(color).bold().to_string());
+        }
+        self.output.push_str(row.trim_end());
+        self.output.push('\n');
+    }
+
+    /// Writes the marker for lines left out of a snippet.
+    fn elision(&mut self) {
+        write(self.output, format_args!("{}\n", "...".cyan().bold()));
+    }
+
+    /// Writes a border with nothing next to it.
+    fn empty(&mut self) {
+        write(
+            self.output,
+            format_args!("{:w$} {}\n", " ", "│".cyan().bold(), w = self.width),
+        );
+    }
+
+    /// Writes the top border, along with the location a snippet is from.
+    fn header(&mut self, location: &str) {
+        write(
+            self.output,
+            format_args!(
+                "{}{} {}\n",
+                " ".repeat(self.width.strict_add(1)),
+                "┌──".cyan().bold(),
+                location.italic()
+            ),
+        );
+        self.empty();
+    }
+
+    /// Writes the 0-based `line`, whose text is `text`.
+    fn source(&mut self, line: usize, text: &str) {
+        write(
+            self.output,
+            format_args!(
+                "{:>w$} {} {text}\n",
+                line.strict_add(1).to_string().cyan().bold(),
+                "│".cyan().bold(),
+                w = self.width
+            ),
+        );
+    }
+}
+
+/// A 0-based line, and a 0-based column in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    /// The 0-based column, in cells.
+    column: usize,
+
+    /// The 0-based line.
+    line: usize,
+}
+
+/// An annotation whose span has been mapped onto lines and columns. `end` is
+/// exclusive, and always at least one cell past `start` on single-line spans so
+/// that empty spans still get a marker.
+#[derive(Debug, Clone, Copy)]
+struct Resolved<'label> {
+    /// The annotation being drawn.
+    annotation: Annotation<'label>,
+
+    /// Where the annotation's span ends.
+    end: Position,
+
+    /// Where the annotation's span starts.
+    start: Position,
+}
+
+impl Resolved<'_> {
+    /// Whether the span covers more than one line.
+    const fn is_multiline(&self) -> bool {
+        self.start.line != self.end.line
+    }
+}
+
+/// Writes `args` to `output`.
+pub fn write(output: &mut String, args: fmt::Arguments<'_>) {
+    output
+        .write_fmt(args)
+        .expect("writing to a `String` never fails");
+}
+
+/// How many cells `ch` takes up when printed.
+fn char_width(ch: char) -> usize {
+    if ch == '\t' {
+        TAB_WIDTH
+    } else {
+        ch.width().unwrap_or(0)
+    }
+}
+
+/// How many cells `text` takes up when printed.
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// `line` with its tabs expanded, so it lines up with the computed columns.
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// The part of `line` before the byte-offset `offset`, which may point past the
+/// end of the line, onto its terminator.
+fn before(line: &str, offset: usize) -> &str {
+    line.get(..line.floor_char_boundary(offset))
+        .unwrap_or_default()
+}
+
+/// The byte-offset of `offset` from the start of the 0-based `line`.
+fn offset_in_line(file: &SourceFile, line: usize, offset: usize) -> usize {
+    let start = file.line_starts().get(line).copied().unwrap_or_default();
+    offset.strict_sub(start)
+}
+
+/// Maps the span of `annotation` onto lines and columns of `file`.
+fn resolve<'label>(file: &SourceFile, annotation: Annotation<'label>) -> Resolved<'label> {
+    let span = annotation.span;
+    let position = |offset: usize| {
+        let line = file.line_of(offset);
+        Position {
+            column: text_width(before(file.line(line), offset_in_line(file, line, offset))),
+            line,
+        }
+    };
+
+    let mut start = position(span.start());
+    // An empty span at the very end of a file that ends with a newline would
+    // otherwise point at a line that doesn't exist, so it points just past the
+    // last character instead.
+    if span.is_empty() && span.start() == file.source().len() && start.column == 0 && start.line > 0
+    {
+        start.line = start.line.strict_sub(1);
+        start.column = text_width(file.line(start.line));
+    }
+
+    let mut end = if span.end() > span.start() {
+        position(span.end())
+    } else {
+        start
+    };
+    // A span ending right after a newline ends on the line it covers, not on
+    // the start of the next one.
+    if end.line > start.line && file.line_starts().get(end.line) == Some(&span.end()) {
+        end.line = end.line.strict_sub(1);
+        end.column = text_width(file.line(end.line));
+    }
+    if end.line == start.line {
+        end.column = end.column.max(start.column.strict_add(1));
+    }
+
+    Resolved {
+        annotation,
+        end,
+        start,
+    }
+}
+
+/// The width of the line-number gutter needed to render `annotations`.
+pub fn gutter_width(sources: &SourceMap, annotations: &[Annotation]) -> usize {
+    let last_line = annotations
+        .iter()
+        .map(|&annotation| {
+            resolve(sources.file_of(annotation.span), annotation)
+                .end
+                .line
+        })
+        .max()
+        .unwrap_or(0);
+    digits(last_line.strict_add(1))
+}
+
+/// How many digits `number` is written with.
+fn digits(number: usize) -> usize {
+    (number.max(1).ilog10() as usize).strict_add(1)
+}
+
+/// Renders `annotations` as snippets, one per file in the order the files are
+/// first annotated. Annotations in the same file share a snippet, so that
+/// labels on the same line are drawn beneath it together.
+pub fn render_snippets(
+    output: &mut String,
+    sources: &SourceMap,
+    annotations: &[Annotation],
+    gutter_width: usize,
+) {
+    let mut files = Vec::<FileId>::new();
+    for annotation in annotations {
+        if !files.contains(&annotation.span.file()) {
+            files.push(annotation.span.file());
+        }
+    }
+
+    for file_id in files {
+        let file = sources.get(file_id);
+        let resolved = annotations
+            .iter()
+            .filter(|annotation| annotation.span.file() == file_id)
+            .map(|&annotation| resolve(file, annotation))
+            .collect::<Vec<_>>();
+        let Some(first) = resolved.first() else {
+            continue;
+        };
+
+        // The location is that of the first annotation as it's drawn, which
+        // isn't the line its offset is on for an empty span at the end of the
+        // file.
+        let line = first.start.line;
+        let offset = offset_in_line(file, line, first.annotation.span.start());
+        let column = before(file.line(line), offset).chars().count();
+        let mut gutter = Gutter {
+            output: &mut *output,
+            width: gutter_width,
+        };
+        gutter.header(&format!(
+            "{}:{}:{}",
+            file.path().display(),
+            line.strict_add(1),
+            column.strict_add(1)
+        ));
+        render_file(&mut gutter, file, &resolved);
+        gutter.empty();
+    }
+}
+
+/// Renders the lines of `file` that `annotations` touch, along with their
+/// underlines and labels.
+fn render_file(gutter: &mut Gutter, file: &SourceFile, annotations: &[Resolved]) {
+    let multiline = annotations
+        .iter()
+        .filter(|resolved| resolved.is_multiline())
+        .collect::<Vec<_>>();
+    // Multi-line spans are drawn in a margin to the left of the source, with
+    // two cells for each.
+    let margin = multiline.len().strict_mul(2);
+
+    let mut lines = Vec::new();
+    for resolved in annotations {
+        lines.push(resolved.start.line);
+        lines.push(resolved.end.line);
+        if resolved.is_multiline() {
+            let inner = resolved.start.line.strict_add(1)..resolved.end.line;
+            lines.extend(inner.clone().take(MAX_MULTILINE_CONTEXT));
+            lines.extend(inner.rev().take(MAX_MULTILINE_CONTEXT));
+        }
+    }
+    lines.sort_unstable();
+    lines.dedup();
+
+    // The slots of the multi-line spans that have started but not yet ended.
+    let mut open = HashSet::new();
+    let margin_canvas = |open_slots: &HashSet<usize>| {
+        let mut canvas = Canvas::default();
+        for (slot, resolved) in multiline.iter().enumerate() {
+            if open_slots.contains(&slot) {
+                canvas.put(slot.strict_mul(2), '│', resolved.annotation.color);
+            }
+        }
+        canvas
+    };
+
+    let mut previous = None;
+    for &line in &lines {
+        if previous.is_some_and(|previous: usize| line > previous.strict_add(1)) {
+            gutter.elision();
+        }
+        previous = Some(line);
+
+        let mut source = margin_canvas(&open);
+        source.resize(margin);
+        let mut source = source.render();
+        source.push_str(&expand_tabs(file.line(line)));
+        gutter.source(line, source.trim_end());
+
+        let single = annotations
+            .iter()
+            .filter(|resolved| !resolved.is_multiline() && resolved.start.line == line)
+            .collect::<Vec<_>>();
+        if !single.is_empty() {
+            render_single_line(gutter, &single, margin, &margin_canvas(&open));
+        }
+
+        for (slot, resolved) in multiline.iter().enumerate() {
+            if resolved.end.line == line {
+                let color = resolved.annotation.color;
+                let mut canvas = margin_canvas(&open);
+                canvas.put(slot.strict_mul(2), '╰', color);
+                let end = margin.strict_add(resolved.end.column.max(1)).strict_sub(1);
+                canvas.fill(slot.strict_mul(2).strict_add(1)..end, '─', color);
+                canvas.put(end, resolved.annotation.marker, color);
+                canvas.resize(end.strict_add(2));
+                gutter.annotation(
+                    &canvas,
+                    resolved.annotation.label.map(|label| (label, color)),
+                );
+                open.remove(&slot);
+            }
+        }
+        for (slot, resolved) in multiline.iter().enumerate() {
+            if resolved.start.line == line {
+                let color = resolved.annotation.color;
+                let mut canvas = margin_canvas(&open);
+                canvas.put(slot.strict_mul(2), '╭', color);
+                let start = margin.strict_add(resolved.start.column);
+                canvas.fill(slot.strict_mul(2).strict_add(1)..start, '─', color);
+                canvas.put(start, resolved.annotation.marker, color);
+                gutter.annotation(&canvas, None);
+                open.insert(slot);
+            }
+        }
+    }
+}
+
+/// Underlines the single-line annotations of a line. The rightmost label goes
+/// next to the underlines, while the rest hang below them, each connected to
+/// the start of its span.
+fn render_single_line(gutter: &mut Gutter, single: &[&Resolved], margin: usize, base: &Canvas) {
+    let mut single = single.to_vec();
+    single.sort_by_key(|resolved| (resolved.start.column, resolved.end.column));
+
+    let mut underline = Canvas {
+        cells: base.cells.clone(),
+    };
+    // The primary annotation is drawn last, so it wins where spans overlap.
+    for resolved in single
+        .iter()
+        .filter(|resolved| !resolved.annotation.is_primary)
+        .chain(
+            single
+                .iter()
+                .filter(|resolved| resolved.annotation.is_primary),
+        )
+    {
+        underline.fill(
+            margin.strict_add(resolved.start.column)..margin.strict_add(resolved.end.column),
+            resolved.annotation.marker,
+            resolved.annotation.color,
+        );
+    }
+
+    let Some((&last, rest)) = single.split_last() else {
+        return;
+    };
+    // The last annotation can only be labelled inline if no other underline
+    // extends past its own.
+    let max_end = single
+        .iter()
+        .map(|resolved| resolved.end.column)
+        .max()
+        .unwrap_or(0);
+    let inline = last.end.column == max_end && last.annotation.label.is_some();
+    underline.resize(margin.strict_add(max_end).strict_add(1));
+    gutter.annotation(
+        &underline,
+        inline.then_some(last).and_then(|last| {
+            last.annotation
+                .label
+                .map(|label| (label, last.annotation.color))
+        }),
+    );
+
+    let hanging = if inline { rest } else { single.as_slice() }
+        .iter()
+        .filter(|resolved| resolved.annotation.label.is_some())
+        .collect::<Vec<_>>();
+    if hanging.is_empty() {
+        return;
+    }
+
+    let connectors = |count: usize| {
+        let mut canvas = Canvas {
+            cells: base.cells.clone(),
+        };
+        for resolved in hanging.iter().take(count) {
+            canvas.put(
+                margin.strict_add(resolved.start.column),
+                '│',
+                resolved.annotation.color,
+            );
+        }
+        canvas
+    };
+    gutter.annotation(&connectors(hanging.len()), None);
+    for (i, resolved) in hanging.iter().enumerate().rev() {
+        let mut canvas = connectors(i);
+        canvas.resize(margin.strict_add(resolved.start.column));
+        gutter.annotation(
+            &canvas,
+            resolved
+                .annotation
+                .label
+                .map(|label| (label, resolved.annotation.color)),
+        );
+    }
+}
+
+/// Renders the lines `suggestion` touches as they'd read with it applied,
+/// marking the replacement with `~`, or `+` for pure insertions.
+pub fn render_suggestion(output: &mut String, suggestion: &Suggestion, file: &SourceFile) {
+    let start_line = file.line_of(suggestion.span.start());
+    let start_col = offset_in_line(file, start_line, suggestion.span.start());
+
+    let end_line = file.line_of(suggestion.span.end());
+    let end_col = offset_in_line(file, end_line, suggestion.span.end());
+
+    let prefix = before(file.line(start_line), start_col);
+    let suffix = file.line(end_line);
+    let suffix = suffix
+        .get(suffix.ceil_char_boundary(end_col)..)
+        .unwrap_or_default();
+    let fixed = format!("{prefix}{}{suffix}", suggestion.replacement);
+    let fixed_lines = fixed.lines().collect::<Vec<_>>();
+
+    let width = digits(start_line.strict_add(fixed_lines.len()));
+    let mut gutter = Gutter { output, width };
+
+    write(
+        gutter.output,
+        format_args!("{}: {}\n", Severity::Help.display(), suggestion.message),
+    );
+    gutter.empty();
+    for (i, line) in fixed_lines.iter().enumerate() {
+        gutter.source(start_line.strict_add(i), &expand_tabs(line));
+    }
+    if !suggestion.replacement.contains('\n') {
+        let marker = if suggestion.span.is_empty() { '+' } else { '~' };
+        let start = text_width(prefix);
+        let end = start.strict_add(text_width(&suggestion.replacement).max(1));
+        let mut canvas = Canvas::default();
+        canvas.fill(start..end, marker, Severity::Help.color());
+        gutter.annotation(&canvas, None);
+    }
+    gutter.empty();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use colored::control;
+    use insta::assert_snapshot;
+
+    use crate::Diagnostic;
+
+    use super::*;
+
+    /// Renders a diagnostic pointing at `primary` in `source`, with a
+    /// secondary label on each of `secondary`.
+    fn render(source: &str, primary: Range<usize>, secondary: &[(Range<usize>, &str)]) -> String {
+        control::set_override(false);
+        let mut sources = SourceMap::default();
+        let file = sources.add("test.amai", source);
+
+        let mut diagnostic = Diagnostic::new("message", Span::from_range(file, primary))
+            .with_primary_label("primary");
+        for &(ref range, label) in secondary {
+            diagnostic =
+                diagnostic.with_secondary_label(Some(label), Span::from_range(file, range.clone()));
+        }
+        diagnostic.display(&sources)
+    }
+
+    #[test]
+    fn tabs() {
+        let source = "\tlet x =\t1;";
+        assert_snapshot!(render(source, 5..6, &[(9..10, "after a tab")]));
+    }
+
+    #[test]
+    fn wide_chars() {
+        let source = "let 名前 = \"日本語\";";
+        let string = source.find('"').expect("the source should have a string");
+        let end = source.len().strict_sub(1);
+        assert_snapshot!(render(source, string..end, &[(4..10, "name")]));
+    }
+
+    #[test]
+    fn combining_chars() {
+        let source = "let e\u{301}x = e\u{301};";
+        let value = source.rfind('e').expect("the source should have a value");
+        let end = value.strict_add(3);
+        assert_snapshot!(render(source, value..end, &[(4..8, "declared")]));
+    }
+
+    #[test]
+    fn zero_width_span_at_eof() {
+        let source = "let x =";
+        assert_snapshot!(render(source, source.len()..source.len(), &[]));
+    }
+
+    #[test]
+    fn zero_width_span_at_eof_after_newline() {
+        let source = "let x = 1\n";
+        assert_snapshot!(render(source, source.len()..source.len(), &[]));
+    }
+
+    #[test]
+    fn overlapping_labels() {
+        let source = "let y = a + b * c;";
+        assert_snapshot!(render(source, 8..17, &[(12..17, "product"), (16..17, "c")]));
+    }
+
+    #[test]
+    fn multiline_span() {
+        let source = "let f() = {\n    1\n};";
+        assert_snapshot!(render(source, 10..19, &[]));
+    }
+
+    #[test]
+    fn multiline_span_with_elided_lines() {
+        let source = "let f() = {\n    1;\n    2;\n    3;\n    4\n};\nf();";
+        let end = source
+            .find(';')
+            .expect("the source should have a statement")
+            .strict_add(17);
+        assert_snapshot!(render(source, 10..end, &[(16..17, "first")]));
+    }
+
+    #[test]
+    fn labels_on_another_line_than_a_multiline_span() {
+        let source = "let f() = {\n    1\n};\nlet g = f();";
+        assert_snapshot!(render(source, 10..19, &[(29..32, "called here")]));
+    }
+}
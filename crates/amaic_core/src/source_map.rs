@@ -0,0 +1,276 @@
+//! See [`SourceMap`].
+
+use std::{
+    fs, io, iter, mem,
+    path::{Path, PathBuf},
+};
+
+use crate::{LineColumn, PositionEncoding, Span};
+
+/// Identifies a file loaded into a [`SourceMap`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+/// A file's source-text, along with the byte-offsets each of its lines start at.
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    /// The byte-offset of the start of each line, the first always being `0`.
+    line_starts: Vec<usize>,
+
+    /// The path the file was loaded from.
+    path: PathBuf,
+
+    /// The source-text of the file.
+    source: String,
+}
+
+impl SourceFile {
+    /// The text of the 0-based `line`, without its terminator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file has no such line.
+    #[must_use]
+    pub fn line(&self, line: usize) -> &str {
+        let start = *self
+            .line_starts
+            .get(line)
+            .expect("the line should be in the file");
+        match self.line_starts.get(line.strict_add(1)) {
+            Some(&next) => {
+                let text = self
+                    .source
+                    .get(start..next.strict_sub(1))
+                    .unwrap_or_default();
+                text.strip_suffix('\r').unwrap_or(text)
+            }
+            // the last line has no terminator, so a `\r` at its end is its own
+            None => self.source.get(start..).unwrap_or_default(),
+        }
+    }
+
+    /// The 0-based line and column of `offset`, with the column counted in the
+    /// units of `encoding`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the file, or isn't on a character
+    /// boundary.
+    #[must_use]
+    pub fn line_column(&self, offset: usize, encoding: PositionEncoding) -> LineColumn {
+        let line = self.line_of(offset);
+        let start = self.line_starts.get(line).copied().unwrap_or_default();
+        let column = self
+            .source
+            .get(start..offset)
+            .expect("the offset should be on a character boundary in the file")
+            .chars()
+            .map(|ch| encoding.len_of(ch))
+            .sum();
+        LineColumn { line, column }
+    }
+
+    /// The 0-based line that `offset` is on.
+    #[must_use]
+    pub fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line.saturating_sub(1),
+        }
+    }
+
+    /// The byte-offset of the start of each line.
+    #[must_use]
+    pub fn line_starts(&self) -> &[usize] {
+        &self.line_starts
+    }
+
+    /// The text of each line, without its terminator.
+    #[must_use]
+    pub fn lines(&self) -> Vec<&str> {
+        (0..self.line_starts.len())
+            .map(|line| self.line(line))
+            .collect()
+    }
+
+    /// Creates a file from its path and source-text, computing its line starts.
+    #[must_use]
+    pub fn new(path: PathBuf, source: String) -> Self {
+        let line_starts = iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i.strict_add(1)))
+            .collect();
+        Self {
+            line_starts,
+            path,
+            source,
+        }
+    }
+
+    /// The byte-offset of `position`, whose column is counted in the units of
+    /// `encoding`.
+    ///
+    /// Positions are clamped, since they often come from other tools: a line
+    /// past the end of the file is its end, a column past the end of its line
+    /// is the line's end, and a column inside a character is its start.
+    #[must_use]
+    pub fn offset(&self, position: LineColumn, encoding: PositionEncoding) -> usize {
+        let Some(&start) = self.line_starts.get(position.line) else {
+            return self.source.len();
+        };
+
+        let mut column = 0_usize;
+        for (i, ch) in self.line(position.line).char_indices() {
+            column = column.strict_add(encoding.len_of(ch));
+            if column > position.column {
+                return start.strict_add(i);
+            }
+        }
+        start.strict_add(self.line(position.line).len())
+    }
+
+    /// The path the file was loaded from.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The source-text of the file.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Owns every file loaded during a compilation, so that [`Span`]s from any of
+/// them can be resolved back into source-text.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    /// The loaded files, indexed by their [`FileId`].
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Adds a file from its path and source-text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`u32::MAX`] files are added.
+    pub fn add(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> FileId {
+        let id = FileId(u32::try_from(self.files.len()).expect("too many source files"));
+        self.files.push(SourceFile::new(path.into(), source.into()));
+        id
+    }
+
+    /// The file `span` points into.
+    #[must_use]
+    pub fn file_of(&self, span: Span) -> &SourceFile {
+        self.get(span.file())
+    }
+
+    /// The file `id` refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` came from another source map.
+    #[must_use]
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        self.files
+            .get(id.0 as usize)
+            .expect("the file should be in this source map")
+    }
+
+    /// Reads the file at `path` and adds it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the file couldn't be read.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        Ok(self.add(path, source))
+    }
+
+    /// Replaces the source-text of an already-loaded file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` came from another source map.
+    pub fn replace(&mut self, id: FileId, source: impl Into<String>) {
+        let file = self
+            .files
+            .get_mut(id.0 as usize)
+            .expect("the file should be in this source map");
+        *file = SourceFile::new(mem::take(&mut file.path), source.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const ENCODINGS: [PositionEncoding; 3] = [
+        PositionEncoding::Utf8,
+        PositionEncoding::Utf16,
+        PositionEncoding::Char,
+    ];
+
+    /// Source-text made up of any characters, or of those that are awkward to
+    /// count: line terminators, tabs, multi-byte, astral and combining ones.
+    fn source() -> impl Strategy<Value = String> {
+        prop_oneof![any::<String>(), "[ab\r\n\té日😀\u{301}\u{10FFFF}]{0,64}",]
+    }
+
+    /// The offsets a position can point at: every character boundary, except
+    /// between the `\r` and `\n` of a line terminator, which is part of no
+    /// line.
+    fn offsets(file: &SourceFile) -> impl Iterator<Item = usize> + '_ {
+        let source = file.source();
+        (0..=source.len()).filter(|&offset| {
+            source
+                .split_at_checked(offset)
+                .is_some_and(|(before, after)| !(before.ends_with('\r') && after.starts_with('\n')))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn offset_inverts_line_column(source in source()) {
+            let file = SourceFile::new(PathBuf::from("test.amai"), source);
+            for encoding in ENCODINGS {
+                for offset in offsets(&file) {
+                    let position = file.line_column(offset, encoding);
+                    prop_assert_eq!(
+                        file.offset(position, encoding),
+                        offset,
+                        "{:?} in {:?}",
+                        position,
+                        encoding
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn line_column_is_ordered_like_offsets(source in source()) {
+            let file = SourceFile::new(PathBuf::from("test.amai"), source);
+            for encoding in ENCODINGS {
+                let positions: Vec<_> = offsets(&file)
+                    .map(|offset| file.line_column(offset, encoding))
+                    .collect();
+                prop_assert!(positions.is_sorted_by(|earlier, later| earlier < later));
+            }
+        }
+
+        #[test]
+        fn offset_clamps_any_position(source in source(), line in 0..8_usize, column in 0..80_usize) {
+            let file = SourceFile::new(PathBuf::from("test.amai"), source);
+            for encoding in ENCODINGS {
+                let offset = file.offset(LineColumn { line, column }, encoding);
+                prop_assert!(offset <= file.source().len());
+                prop_assert!(file.source().is_char_boundary(offset));
+            }
+        }
+    }
+}
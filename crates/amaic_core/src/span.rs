@@ -4,7 +4,10 @@ use std::ops::Range;
 
 use derive_more::Constructor;
 
-/// A byte-range used for representing a position in source-code.
+use crate::{FileId, LineColumn, PositionEncoding, SourceMap};
+
+/// A byte-range used for representing a position in source-code, within the
+/// file of a [`SourceMap`](crate::SourceMap).
 #[derive(Clone, Copy, Constructor, Debug, Default, PartialEq, Eq)]
 #[allow(
     // `allow` used here in place of `expect`, because it doesn't play nice
@@ -13,6 +16,9 @@ use derive_more::Constructor;
     reason = "Intuitively, start comes first."
 )]
 pub struct Span {
+    /// The file the span points into.
+    file: FileId,
+
     /// The start of the span.
     start: usize,
 
@@ -21,16 +27,88 @@ pub struct Span {
 }
 
 impl Span {
+    /// Whether `offset` is within the span, which doesn't include its end.
+    #[must_use]
+    pub const fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
     /// The end of the byte-range.
     #[must_use]
     pub const fn end(&self) -> usize {
         self.end
     }
 
+    /// The file the span points into.
+    #[must_use]
+    pub const fn file(&self) -> FileId {
+        self.file
+    }
+
+    /// Creates a span in `file` covering `range`.
+    #[must_use]
+    pub const fn from_range(file: FileId, range: Range<usize>) -> Self {
+        Self::new(file, range.start, range.end)
+    }
+
+    /// Whether the span is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The length of the span, in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.end.strict_sub(self.start)
+    }
+
+    /// The line and column of the start and end of the span, with columns
+    /// counted in the units of `encoding`.
+    #[must_use]
+    pub fn line_columns(
+        &self,
+        sources: &SourceMap,
+        encoding: PositionEncoding,
+    ) -> (LineColumn, LineColumn) {
+        let file = sources.file_of(*self);
+        (
+            file.line_column(self.start, encoding),
+            file.line_column(self.end, encoding),
+        )
+    }
+
     /// Creates a new span with the bounds, `[self.start(), other.end())`.
     #[must_use]
     pub const fn merge(&self, other: &Self) -> Self {
-        Self::new(self.start(), other.end())
+        Self::new(self.file, self.start(), other.end())
+    }
+
+    /// Creates a new span moved `delta` bytes forwards, or backwards if it's
+    /// negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span would be moved before the start of its file.
+    #[must_use]
+    pub const fn shift(&self, delta: isize) -> Self {
+        Self::new(
+            self.file,
+            self.start.strict_add_signed(delta),
+            self.end.strict_add_signed(delta),
+        )
+    }
+
+    /// Creates an empty span at the end of this one.
+    #[must_use]
+    pub const fn shrink_to_hi(&self) -> Self {
+        Self::new(self.file, self.end, self.end)
+    }
+
+    /// Creates an empty span at the start of this one.
+    #[must_use]
+    pub const fn shrink_to_lo(&self) -> Self {
+        Self::new(self.file, self.start, self.start)
     }
 
     /// The start of the byte-range.
@@ -38,11 +116,12 @@ impl Span {
     pub const fn start(&self) -> usize {
         self.start
     }
-}
 
-impl From<Range<usize>> for Span {
-    fn from(value: Range<usize>) -> Self {
-        Self::new(value.start, value.end)
+    /// Whether the spans are in the same file and overlap or are next to each
+    /// other, so that a cursor just after a name still touches it.
+    #[must_use]
+    pub fn touches(&self, other: &Self) -> bool {
+        self.file == other.file && self.start.max(other.start) <= self.end.min(other.end)
     }
 }
 
@@ -51,3 +130,69 @@ impl From<Span> for Range<usize> {
         value.start()..value.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// A span in the default file.
+    fn span(start: usize, end: usize) -> Span {
+        Span::new(FileId::default(), start, end)
+    }
+
+    #[test]
+    fn contains_excludes_the_end() {
+        assert!(span(2, 5).contains(2));
+        assert!(span(2, 5).contains(4));
+        assert!(!span(2, 5).contains(5));
+        assert!(!span(2, 5).contains(1));
+        assert!(!span(3, 3).contains(3));
+    }
+
+    #[test]
+    fn touches_includes_adjacent_spans() {
+        assert!(span(2, 5).touches(&span(4, 8)));
+        assert!(span(2, 5).touches(&span(5, 8)));
+        assert!(span(2, 5).touches(&span(5, 5)));
+        assert!(span(2, 5).touches(&span(2, 2)));
+        assert!(!span(2, 5).touches(&span(6, 8)));
+
+        let mut sources = SourceMap::default();
+        let first = sources.add("first.amai", "");
+        let second = sources.add("second.amai", "");
+        assert!(!Span::new(first, 2, 5).touches(&Span::new(second, 2, 5)));
+    }
+
+    #[test]
+    fn shrinking_keeps_one_end() {
+        assert_eq!(span(2, 5).shrink_to_lo(), span(2, 2));
+        assert_eq!(span(2, 5).shrink_to_hi(), span(5, 5));
+        assert!(span(2, 5).shrink_to_lo().is_empty());
+        assert_eq!(span(2, 5).len(), 3);
+        assert_eq!(span(5, 5).len(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn touches_is_symmetric(lo in 0..16_usize, len in 0..16_usize, other_lo in 0..16_usize, other_len in 0..16_usize) {
+            let first = span(lo, lo.strict_add(len));
+            let second = span(other_lo, other_lo.strict_add(other_len));
+            prop_assert_eq!(first.touches(&second), second.touches(&first));
+            prop_assert!(first.touches(&first.shrink_to_lo()));
+            prop_assert!(first.touches(&first.shrink_to_hi()));
+            prop_assert_eq!(first.len(), len);
+        }
+
+        #[test]
+        fn touching_spans_share_an_offset(lo in 0..16_usize, len in 0..16_usize, offset in 0..40_usize) {
+            let first = span(lo, lo.strict_add(len));
+            let at = span(offset, offset);
+            prop_assert_eq!(
+                first.touches(&at),
+                first.contains(offset) || offset == first.end()
+            );
+        }
+    }
+}
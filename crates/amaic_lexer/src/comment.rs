@@ -0,0 +1,97 @@
+//! See [`doc_text`].
+
+use crate::{AmaicLexer, LexError};
+
+/// The text of a `///` or `//!` doc comment, without its marker or the single
+/// space that usually follows it.
+#[must_use]
+pub fn doc_text(comment: &str) -> &str {
+    let text = comment
+        .strip_prefix("///")
+        .or_else(|| comment.strip_prefix("//!"))
+        .unwrap_or(comment);
+    text.strip_prefix(' ').unwrap_or(text)
+}
+
+/// Lexes the rest of a block comment, after its opening `/*`. Block comments
+/// nest, so that code containing them can be commented out.
+pub fn lex_block(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    let rest = lex.remainder();
+    let mut depth = 1_usize;
+    let mut i = 0_usize;
+    // The comment is scanned byte by byte, since `i` may be in the middle of
+    // a multi-byte character, while the delimiters are ASCII.
+    while let Some(tail) = rest.as_bytes().get(i..).filter(|tail| !tail.is_empty()) {
+        if tail.starts_with(b"/*") {
+            depth = depth.strict_add(1);
+            i = i.strict_add(2);
+        } else if tail.starts_with(b"*/") {
+            depth = depth.strict_sub(1);
+            i = i.strict_add(2);
+            if depth == 0 {
+                lex.bump(i);
+                return Ok(());
+            }
+        } else {
+            i = i.strict_add(1);
+        }
+    }
+
+    let start = lex.span().start;
+    lex.bump(rest.len());
+    Err(LexError::UnterminatedComment {
+        span: start..start.strict_add(2),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use logos::Logos as _;
+
+    use super::*;
+    use crate::TokenKind;
+
+    /// The kind of each token in `source`, or the error lexing it.
+    fn lex(source: &str) -> Vec<Result<TokenKind, LexError>> {
+        TokenKind::lexer(source).collect()
+    }
+
+    #[test]
+    fn nests() {
+        assert_eq!(
+            lex("/* a /* ü */ b */ x"),
+            [Ok(TokenKind::Comment), Ok(TokenKind::Identifier)]
+        );
+        assert_eq!(
+            lex("/* a /* b */ x"),
+            [Err(LexError::UnterminatedComment { span: 0..2 })]
+        );
+    }
+
+    #[test]
+    fn reports_where_an_unterminated_comment_opens() {
+        assert_eq!(
+            lex("x /* é *"),
+            [
+                Ok(TokenKind::Identifier),
+                Err(LexError::UnterminatedComment { span: 2..4 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_non_ascii_text() {
+        assert_eq!(
+            lex("/* é */ x"),
+            [Ok(TokenKind::Comment), Ok(TokenKind::Identifier)]
+        );
+        assert_eq!(lex("/*日本*/"), [Ok(TokenKind::Comment)]);
+    }
+
+    #[test]
+    fn strips_doc_markers() {
+        assert_eq!(doc_text("/// Adds."), "Adds.");
+        assert_eq!(doc_text("//!  Indented."), " Indented.");
+        assert_eq!(doc_text("///"), "");
+    }
+}
@@ -2,31 +2,178 @@
 
 use crate::AmaicLexer;
 
+use std::ops::Range;
 use thiserror::Error;
 
 /// An error that occurs while lexing.
-#[derive(Clone, Copy, Debug, Default, Error, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, Error, PartialEq, Eq, Hash)]
 #[non_exhaustive] // non-exhaustive until language stabilizes
 pub enum LexError {
+    /// An error that occurs when there's nothing between the braces of an
+    /// interpolation.
+    #[error("Empty interpolation in string")]
+    EmptyInterpolation {
+        /// The span of the braces.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when an integer literal doesn't fit in an `int`.
+    #[error("Integer literal is out of range for `int`")]
+    IntegerOverflow {
+        /// The span of the literal.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a `\u{...}` escape is well-formed, but isn't
+    /// a valid unicode scalar value.
+    #[error("Invalid unicode codepoint in string")]
+    InvalidCodepoint {
+        /// The span of the escape-sequence.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a numeric literal contains a digit that's
+    /// invalid for its base.
+    #[error("Invalid digit for a base {radix} literal")]
+    InvalidDigit {
+        /// The base of the literal.
+        radix: u32,
+
+        /// The span of the digit.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a `\u{...}` escape isn't made up of one to six
+    /// hexadecimal digits between braces.
+    #[error("Invalid unicode escape in string")]
+    InvalidUnicodeEscape {
+        /// The span of the escape-sequence.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a prefixed integer literal, such as `0x`, has
+    /// no digits.
+    #[error("Integer literal has no digits")]
+    MissingDigits {
+        /// The span of the literal.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a float literal's exponent has no digits.
+    #[error("Float literal has an exponent with no digits")]
+    MissingExponent {
+        /// The span of the exponent.
+        span: Range<usize>,
+    },
+
     /// An error that occurs when something very unexpected happens.
     #[default]
     #[error("Un undefined error occurred")]
     Undefined,
 
-    /// An error that occurs when an unexpected byte is parsed.
-    #[error("An unexpected byte was found")]
-    UnexpectedByte,
+    /// An error that occurs when a character that can't start a token is
+    /// found.
+    #[error("Unexpected character {found:?} (U+{:04X})", u32::from(*found))]
+    UnexpectedChar {
+        /// The character itself.
+        found: char,
+
+        /// The span of the character.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a string contains a backslash that isn't
+    /// followed by a known escape-sequence.
+    #[error("Unknown escape-sequence in string")]
+    UnknownEscape {
+        /// The span of the backslash and the character after it.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a `}` in a string doesn't close an
+    /// interpolation.
+    #[error("Unmatched `}}` in string")]
+    UnmatchedBrace {
+        /// The span of the `}`.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a block comment isn't closed by a `*/`.
+    #[error("A block comment was found to not properly terminate")]
+    UnterminatedComment {
+        /// The span of the `/*` that opened the outermost comment.
+        span: Range<usize>,
+    },
+
+    /// An error that occurs when a `{` in a string isn't closed by a `}`.
+    #[error("Unterminated interpolation in string")]
+    UnterminatedInterpolation {
+        /// The span of the `{`.
+        span: Range<usize>,
+    },
 
     /// An error that occurs when a string was not properly terminated.
     #[error("A string was found to not properly terminate")]
-    UnterminatedString,
+    UnterminatedString {
+        /// The span of the string, up to where it was expected to terminate.
+        span: Range<usize>,
+    },
+}
+
+impl LexError {
+    /// Moves the error's span `delta` bytes forwards, or backwards if it's
+    /// negative, as when it was found lexing part of a larger source-text.
+    pub const fn shift(&mut self, delta: isize) {
+        match *self {
+            Self::Undefined => {}
+            Self::EmptyInterpolation { ref mut span }
+            | Self::IntegerOverflow { ref mut span }
+            | Self::InvalidCodepoint { ref mut span }
+            | Self::InvalidDigit { ref mut span, .. }
+            | Self::InvalidUnicodeEscape { ref mut span }
+            | Self::MissingDigits { ref mut span }
+            | Self::MissingExponent { ref mut span }
+            | Self::UnexpectedChar { ref mut span, .. }
+            | Self::UnknownEscape { ref mut span }
+            | Self::UnmatchedBrace { ref mut span }
+            | Self::UnterminatedComment { ref mut span }
+            | Self::UnterminatedInterpolation { ref mut span }
+            | Self::UnterminatedString { ref mut span } => {
+                span.start = span.start.strict_add_signed(delta);
+                span.end = span.end.strict_add_signed(delta);
+            }
+        }
+    }
+
+    /// The span of source-text the error occurred in, if known.
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match *self {
+            Self::Undefined => None,
+            Self::EmptyInterpolation { ref span }
+            | Self::IntegerOverflow { ref span }
+            | Self::InvalidCodepoint { ref span }
+            | Self::InvalidDigit { ref span, .. }
+            | Self::InvalidUnicodeEscape { ref span }
+            | Self::MissingDigits { ref span }
+            | Self::MissingExponent { ref span }
+            | Self::UnexpectedChar { ref span, .. }
+            | Self::UnknownEscape { ref span }
+            | Self::UnmatchedBrace { ref span }
+            | Self::UnterminatedComment { ref span }
+            | Self::UnterminatedInterpolation { ref span }
+            | Self::UnterminatedString { ref span } => Some(span.clone()),
+        }
+    }
 }
 
 impl From<&mut AmaicLexer<'_>> for LexError {
     fn from(value: &mut AmaicLexer) -> Self {
-        match *value.slice().as_bytes() {
-            [b'"', ..] => Self::UnterminatedString,
-            [_] => Self::UnexpectedByte,
+        let span = value.span();
+        let mut chars = value.slice().chars();
+        match (chars.next(), chars.next()) {
+            (Some('"'), _) => Self::UnterminatedString { span },
+            (Some(found), None) => Self::UnexpectedChar { found, span },
             _ => Self::Undefined,
         }
     }
@@ -0,0 +1,178 @@
+//! See [`normalize_ident`] and [`ascii_lookalike`].
+//!
+//! Identifiers follow [UAX #31]: they start with an `XID_Start` character or
+//! `_`, and continue with `XID_Continue` characters.
+//!
+//! [UAX #31]: https://www.unicode.org/reports/tr31/
+
+use std::borrow::Cow;
+
+use unicode_normalization::{IsNormalized, UnicodeNormalization as _, is_nfc_quick};
+
+/// Characters that look like ASCII, along with the ASCII character they look
+/// like, sorted by the former.
+const CONFUSABLES: &[(char, char)] = &[
+    ('\u{00A0}', ' '),  // No-break space
+    ('\u{00B4}', '\''), // Acute accent
+    ('\u{00B7}', '.'),  // Middle dot
+    ('\u{00D7}', 'x'),  // Multiplication sign
+    ('\u{01C0}', '|'),  // Latin letter dental click
+    ('\u{02BB}', '\''), // Modifier letter turned comma
+    ('\u{02BC}', '\''), // Modifier letter apostrophe
+    ('\u{037E}', ';'),  // Greek question mark
+    ('\u{0391}', 'A'),  // Greek capital letter alpha
+    ('\u{0392}', 'B'),  // Greek capital letter beta
+    ('\u{0395}', 'E'),  // Greek capital letter epsilon
+    ('\u{0396}', 'Z'),  // Greek capital letter zeta
+    ('\u{0397}', 'H'),  // Greek capital letter eta
+    ('\u{0399}', 'I'),  // Greek capital letter iota
+    ('\u{039A}', 'K'),  // Greek capital letter kappa
+    ('\u{039C}', 'M'),  // Greek capital letter mu
+    ('\u{039D}', 'N'),  // Greek capital letter nu
+    ('\u{039F}', 'O'),  // Greek capital letter omicron
+    ('\u{03A1}', 'P'),  // Greek capital letter rho
+    ('\u{03A4}', 'T'),  // Greek capital letter tau
+    ('\u{03A5}', 'Y'),  // Greek capital letter upsilon
+    ('\u{03A7}', 'X'),  // Greek capital letter chi
+    ('\u{03B1}', 'a'),  // Greek small letter alpha
+    ('\u{03B9}', 'i'),  // Greek small letter iota
+    ('\u{03BD}', 'v'),  // Greek small letter nu
+    ('\u{03BF}', 'o'),  // Greek small letter omicron
+    ('\u{03C1}', 'p'),  // Greek small letter rho
+    ('\u{03C5}', 'u'),  // Greek small letter upsilon
+    ('\u{0405}', 'S'),  // Cyrillic capital letter dze
+    ('\u{0406}', 'I'),  // Cyrillic capital letter byelorussian-ukrainian i
+    ('\u{0408}', 'J'),  // Cyrillic capital letter je
+    ('\u{0410}', 'A'),  // Cyrillic capital letter a
+    ('\u{0412}', 'B'),  // Cyrillic capital letter ve
+    ('\u{0415}', 'E'),  // Cyrillic capital letter ie
+    ('\u{041A}', 'K'),  // Cyrillic capital letter ka
+    ('\u{041C}', 'M'),  // Cyrillic capital letter em
+    ('\u{041D}', 'H'),  // Cyrillic capital letter en
+    ('\u{041E}', 'O'),  // Cyrillic capital letter o
+    ('\u{0420}', 'P'),  // Cyrillic capital letter er
+    ('\u{0421}', 'C'),  // Cyrillic capital letter es
+    ('\u{0422}', 'T'),  // Cyrillic capital letter te
+    ('\u{0425}', 'X'),  // Cyrillic capital letter ha
+    ('\u{0430}', 'a'),  // Cyrillic small letter a
+    ('\u{0435}', 'e'),  // Cyrillic small letter ie
+    ('\u{043E}', 'o'),  // Cyrillic small letter o
+    ('\u{0440}', 'p'),  // Cyrillic small letter er
+    ('\u{0441}', 'c'),  // Cyrillic small letter es
+    ('\u{0443}', 'y'),  // Cyrillic small letter u
+    ('\u{0445}', 'x'),  // Cyrillic small letter ha
+    ('\u{0455}', 's'),  // Cyrillic small letter dze
+    ('\u{0456}', 'i'),  // Cyrillic small letter byelorussian-ukrainian i
+    ('\u{0458}', 'j'),  // Cyrillic small letter je
+    ('\u{04BB}', 'h'),  // Cyrillic small letter shha
+    ('\u{0501}', 'd'),  // Cyrillic small letter komi de
+    ('\u{051B}', 'q'),  // Cyrillic small letter qa
+    ('\u{051D}', 'w'),  // Cyrillic small letter we
+    ('\u{2010}', '-'),  // Hyphen
+    ('\u{2011}', '-'),  // Non-breaking hyphen
+    ('\u{2012}', '-'),  // Figure dash
+    ('\u{2013}', '-'),  // En dash
+    ('\u{2018}', '\''), // Left single quotation mark
+    ('\u{2019}', '\''), // Right single quotation mark
+    ('\u{201C}', '"'),  // Left double quotation mark
+    ('\u{201D}', '"'),  // Right double quotation mark
+    ('\u{2044}', '/'),  // Fraction slash
+    ('\u{2212}', '-'),  // Minus sign
+    ('\u{2215}', '/'),  // Division slash
+    ('\u{2217}', '*'),  // Asterisk operator
+    ('\u{2223}', '|'),  // Divides
+    ('\u{2236}', ':'),  // Ratio
+    ('\u{3000}', ' '),  // Ideographic space
+];
+
+/// Normalizes an identifier to NFC.
+///
+/// That way identifiers which look the same but are written with different
+/// sequences of codepoints, such as `é` and `e` followed by a combining acute
+/// accent, are the same identifier.
+#[must_use]
+pub fn normalize_ident(ident: &str) -> Cow<'_, str> {
+    match is_nfc_quick(ident.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(ident),
+        IsNormalized::No | IsNormalized::Maybe => Cow::Owned(ident.nfc().collect()),
+    }
+}
+
+/// The ASCII character that `ch` is easily mistaken for, if any.
+#[must_use]
+pub fn confusable(ch: char) -> Option<char> {
+    match ch {
+        // Fullwidth forms are offset from ASCII by a constant.
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(ch).strict_sub(0xFEE0)),
+        _ => CONFUSABLES
+            .binary_search_by_key(&ch, |&(confusable, _)| confusable)
+            .ok()
+            .and_then(|index| CONFUSABLES.get(index))
+            .map(|&(_, ascii)| ascii),
+    }
+}
+
+/// The identifier that `ident` would be mistaken for if it's made up entirely
+/// of ASCII characters and characters [`confusable`] with them, such as `сount`
+/// written with a Cyrillic `с`.
+///
+/// Identifiers that also contain other non-ASCII characters aren't reported,
+/// since it's clear they aren't meant to be ASCII.
+#[must_use]
+pub fn ascii_lookalike(ident: &str) -> Option<String> {
+    if ident.is_ascii() {
+        return None;
+    }
+    ident
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii() {
+                Some(ch)
+            } else {
+                confusable(ch)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenKind, tokenize};
+
+    #[test]
+    fn composed_and_decomposed_idents_are_the_same() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_eq!(normalize_ident(decomposed), composed);
+        assert!(matches!(normalize_ident(composed), Cow::Borrowed(_)));
+        assert!(matches!(normalize_ident("cafe"), Cow::Borrowed(_)));
+
+        // the combining accent continues the identifier
+        let tokens = tokenize(decomposed).expect("the identifier should lex");
+        let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(kinds, [TokenKind::Identifier]);
+        assert_eq!(normalize_ident(tokens[0].slice), composed);
+    }
+
+    #[test]
+    fn lookalikes_are_mistaken_for_ascii() {
+        // the `а` is Cyrillic
+        assert_eq!(ascii_lookalike("\u{430}ge").as_deref(), Some("age"));
+        assert_eq!(ascii_lookalike("\u{430}\u{441}e").as_deref(), Some("ace"));
+        assert_eq!(ascii_lookalike("\u{FF41}ge").as_deref(), Some("age"));
+        assert_eq!(ascii_lookalike("age"), None);
+        // `ж` looks like nothing in ASCII, so the name is clearly Cyrillic
+        assert_eq!(ascii_lookalike("\u{430}\u{436}"), None);
+        assert_eq!(ascii_lookalike("caf\u{e9}"), None);
+    }
+
+    #[test]
+    fn confusables_are_sorted() {
+        assert!(CONFUSABLES.is_sorted_by_key(|&(confusable, _)| confusable));
+        assert_eq!(confusable('\u{430}'), Some('a'));
+        assert_eq!(confusable('\u{FF21}'), Some('A'));
+        assert_eq!(confusable('\u{436}'), None);
+        assert_eq!(confusable('a'), None);
+    }
+}
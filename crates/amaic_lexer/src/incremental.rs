@@ -0,0 +1,243 @@
+//! See [`LexedSource`].
+
+use std::ops::Range;
+
+use logos::Logos as _;
+
+use crate::{LexError, Token, TokenKind, token};
+
+/// A change to source-text, replacing the bytes in `range` with `replacement`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[expect(
+    clippy::exhaustive_structs,
+    reason = "An edit is wholly described by what it replaces and with what."
+)]
+pub struct TextEdit {
+    /// The byte-range being replaced, in the source-text before the edit.
+    pub range: Range<usize>,
+
+    /// The text replacing the range.
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Applies the edit to `source`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit's range is out of bounds of `source`, or doesn't lie
+    /// on character boundaries.
+    pub fn apply(&self, source: &mut String) {
+        source.replace_range(self.range.clone(), &self.replacement);
+    }
+
+    /// How far the text after the edit moves once it's applied.
+    #[must_use]
+    pub const fn delta(&self) -> isize {
+        let removed = self.range.end.strict_sub(self.range.start);
+        self.replacement
+            .len()
+            .cast_signed()
+            .strict_sub(removed.cast_signed())
+    }
+
+    /// Maps a byte-offset after the edited range, in the source-text before the
+    /// edit, to where it is after the edit.
+    #[must_use]
+    pub const fn shift(&self, offset: usize) -> usize {
+        offset.strict_add_signed(self.delta())
+    }
+}
+
+/// A lexed token, or the error that occurred while lexing it, along with its
+/// byte-range in the source-text.
+pub type SpannedToken = (Result<TokenKind, LexError>, Range<usize>);
+
+/// Source-text along with its tokens, which are kept up to date as the text is
+/// edited by relexing only the region around each edit.
+///
+/// Lexing a token never depends on the tokens before it, so once relexing
+/// produces a token that starts where one of the old tokens after the edit now
+/// starts, every token from there on is unchanged, and is reused.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LexedSource {
+    /// The source-text.
+    source: String,
+
+    /// The tokens, in source order.
+    tokens: Vec<SpannedToken>,
+}
+
+impl LexedSource {
+    /// Applies `edit` to the source-text and relexes the region around it,
+    /// returning the range of indices in [`tokens`](Self::tokens) that were
+    /// relexed. The tokens outside of it are the same as before the edit,
+    /// though those after it may have moved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit's range is out of bounds of the source-text, or
+    /// doesn't lie on character boundaries.
+    pub fn edit(&mut self, edit: &TextEdit) -> Range<usize> {
+        // An edit can merge the tokens before it into one, as with a `"` being
+        // added to the end of `"one two`, but never across a line unless the
+        // token already spans lines, so relexing starts from the first token
+        // that reaches the edit's line, such as an unterminated block comment.
+        let line_start = self
+            .source
+            .get(..edit.range.start)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |i| i.strict_add(1));
+        let first = self
+            .tokens
+            .iter()
+            .position(|token| token.1.end >= line_start)
+            .unwrap_or(self.tokens.len());
+        let start = self
+            .tokens
+            .get(first)
+            .map_or(line_start, |token| token.1.start.min(line_start));
+
+        edit.apply(&mut self.source);
+
+        let mut reused = first;
+        let mut relexed = Vec::new();
+        let mut lexer = TokenKind::lexer(self.source.get(start..).unwrap_or_default()).spanned();
+        loop {
+            let Some((mut kind, span)) = lexer.next() else {
+                // The lexers never lined up again, so none of the old tokens
+                // after the edit are reused.
+                reused = self.tokens.len();
+                break;
+            };
+            // The lexer only sees the source-text from `start`.
+            let span = start.strict_add(span.start)..start.strict_add(span.end);
+            if let Err(ref mut err) = kind {
+                err.shift(start.cast_signed());
+            }
+            while self.tokens.get(reused).is_some_and(|token| {
+                token.1.start < edit.range.end || edit.shift(token.1.start) < span.start
+            }) {
+                reused = reused.strict_add(1);
+            }
+            if self
+                .tokens
+                .get(reused)
+                .is_some_and(|token| edit.shift(token.1.start) == span.start)
+            {
+                break;
+            }
+            relexed.push((kind, span));
+        }
+        for &mut (ref mut kind, ref mut span) in self.tokens.get_mut(reused..).unwrap_or_default() {
+            *span = edit.shift(span.start)..edit.shift(span.end);
+            if let Err(ref mut err) = *kind {
+                err.shift(edit.delta());
+            }
+        }
+        let count = relexed.len();
+        self.tokens.splice(first..reused, relexed);
+
+        first..first.strict_add(count)
+    }
+
+    /// Lexes the whole of `source`.
+    #[must_use]
+    pub fn new(source: String) -> Self {
+        let tokens = TokenKind::lexer(&source).spanned().collect();
+        Self { source, tokens }
+    }
+
+    /// The tokens in `range` of indices into [`tokens`](Self::tokens) that
+    /// matter to the parser, as [`tokenize`](crate::tokenize) gives them.
+    ///
+    /// # Errors
+    ///
+    /// Errors with every token in `range` that couldn't be lexed, along with
+    /// its byte-range.
+    pub fn parser_tokens(
+        &self,
+        range: Range<usize>,
+    ) -> Result<Vec<Token<'_>>, Vec<(LexError, Range<usize>)>> {
+        token::parser_tokens(
+            &self.source,
+            self.tokens.get(range).unwrap_or_default().iter().cloned(),
+        )
+    }
+
+    /// The source-text.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The tokens, in source order.
+    pub fn tokens(&self) -> &[SpannedToken] {
+        &self.tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replaces the first `old` in `source` with `new`, checking that the
+    /// tokens are those of lexing the edited text from scratch, and returns
+    /// the edited source along with the range of tokens that were relexed.
+    fn relex(source: &str, old: &str, new: &str) -> (LexedSource, Range<usize>) {
+        let start = source.find(old).expect("the text should be in the source");
+        let edit = TextEdit {
+            range: start..start.strict_add(old.len()),
+            replacement: new.to_owned(),
+        };
+        let mut lexed = LexedSource::new(source.to_owned());
+        let relexed = lexed.edit(&edit);
+        assert_eq!(lexed, LexedSource::new(source.replacen(old, new, 1)));
+        (lexed, relexed)
+    }
+
+    #[test]
+    fn relexes_an_edit_inside_a_token() {
+        let (lexed, relexed) = relex("let a = 1;\nlet answer = 2;\nlet c = 3;\n", "sw", "xyz");
+        // only `let axyzer` on the edited line
+        assert_eq!(relexed, 5..7);
+        assert_eq!(lexed.tokens()[6].1, 15..22);
+    }
+
+    #[test]
+    fn relexes_an_edit_across_lines() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+        let (_, relexed) = relex(source, "1;\nlet b", "10 +\n2 * b");
+        // from the first line through `b`, leaving `= 2;` and the last line
+        assert_eq!(relexed, 0..8);
+    }
+
+    #[test]
+    fn relexes_to_the_end_of_an_opened_string() {
+        let source = "let a = 1;\nlet b = \"two\";\nlet c = 3;\n";
+        let (opened, relexed) = relex(source, "\"two\"", "\"two");
+        // strings end at the end of the line, so the last line is reused
+        assert_eq!(relexed, 5..9);
+        assert_eq!(
+            opened.tokens()[8],
+            (Err(LexError::UnterminatedString { span: 19..24 }), 19..24)
+        );
+
+        let (closed, closed_range) = relex("let b = \"two;\nlet c = 3;\n", "two", "two\"");
+        assert_eq!(closed_range, 0..5);
+        assert_eq!(closed.tokens()[3], (Ok(TokenKind::String), 8..13));
+    }
+
+    #[test]
+    fn relexes_to_the_end_of_an_opened_comment() {
+        let (opened, relexed) = relex("let a = 1;\nlet b = 2;\nlet c = 3;\n", "let b", "/* let b");
+        assert_eq!(relexed, 5..6);
+        assert_eq!(opened.tokens()[5].1, 11..36);
+
+        // the rest of the file was a single token, which can't be reused
+        let (closed, closed_range) =
+            relex("let a = 1;\n/* let b = 2;\nlet c = 3;\n", "2;", "2; */");
+        assert_eq!(closed_range, 5..11);
+        assert_eq!(closed.tokens()[5], (Ok(TokenKind::Comment), 11..27));
+    }
+}
@@ -1,10 +1,26 @@
 //! The crate responsible for converting source-text into more easily-
 //! interpretable [`Tokens`](TokenKind).
 
+mod comment;
 mod error;
+mod ident;
+mod incremental;
+mod lossless;
+mod number;
+mod operator;
+mod string;
+mod token;
 mod token_kind;
 
+pub use comment::doc_text;
 pub use error::LexError;
+pub use ident::{ascii_lookalike, confusable, normalize_ident};
+pub use incremental::{LexedSource, SpannedToken, TextEdit};
+pub use lossless::{LosslessStream, LosslessToken, Trivia, TriviaKind};
+pub use number::{parse_float, parse_int};
+pub use operator::Operator;
+pub use string::{StringPart, string_parts};
+pub use token::{Token, tokenize};
 pub use token_kind::TokenKind;
 
 /// The lexer for the Amai language.
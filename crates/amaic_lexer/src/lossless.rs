@@ -0,0 +1,283 @@
+//! See [`LosslessStream`].
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+use logos::Logos as _;
+
+use crate::{LexError, TokenKind};
+
+/// The kind of a piece of [`Trivia`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TriviaKind {
+    /// A comment, without the newline that ends it.
+    Comment,
+    /// A single `\n` or `\r\n`.
+    Newline,
+    /// A run of spaces and tabs.
+    Whitespace,
+}
+
+/// Source-text between tokens that doesn't affect the meaning of a program.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Trivia {
+    /// What the trivia is made of.
+    pub kind: TriviaKind,
+
+    /// The byte-range of the trivia in the source-text.
+    pub span: Range<usize>,
+}
+
+impl Trivia {
+    /// The text of the trivia within `source`.
+    #[must_use]
+    pub fn text<'src>(&self, source: &'src str) -> &'src str {
+        source.get(self.span.clone()).unwrap_or_default()
+    }
+}
+
+/// A token, along with the trivia surrounding it.
+///
+/// A token owns the trivia after it up to the end of its line as trailing
+/// trivia, and everything else before it as leading trivia, so that a comment
+/// on its own line belongs to the token below it, while one at the end of a
+/// line belongs to the token before it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LosslessToken {
+    /// The kind of the token, or the error that occurred while lexing it.
+    pub kind: Result<TokenKind, LexError>,
+
+    /// The trivia between the previous token's trailing trivia and this token.
+    pub leading: Vec<Trivia>,
+
+    /// The byte-range of the token in the source-text.
+    pub span: Range<usize>,
+
+    /// The trivia after this token, up to the end of its line.
+    pub trailing: Vec<Trivia>,
+}
+
+/// Source-text lexed without losing anything, so that it can be reconstructed
+/// byte-for-byte from its tokens and their trivia.
+///
+/// Lexing errors are kept as tokens rather than stopping the lexer, and
+/// [`Comment`](TokenKind::Comment)s become trivia.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LosslessStream<'src> {
+    /// The trivia after the last token's trailing trivia.
+    end_trivia: Vec<Trivia>,
+
+    /// The lexed source-text.
+    source: &'src str,
+
+    /// The tokens, in source order.
+    tokens: Vec<LosslessToken>,
+}
+
+impl<'src> LosslessStream<'src> {
+    /// The trivia after the last token's trailing trivia, such as the final
+    /// newline of a file.
+    #[must_use]
+    pub fn end_trivia(&self) -> &[Trivia] {
+        &self.end_trivia
+    }
+
+    /// Lexes `source`, keeping its whitespace and comments as trivia.
+    #[must_use]
+    pub fn new(source: &'src str) -> Self {
+        let mut tokens = Vec::new();
+        let mut pending = Vec::new();
+        let mut pos = 0;
+
+        for (kind, span) in TokenKind::lexer(source).spanned() {
+            split_whitespace(source, pos..span.start, &mut pending);
+            pos = span.end;
+
+            if kind == Ok(TokenKind::Comment) {
+                pending.push(Trivia {
+                    kind: TriviaKind::Comment,
+                    span,
+                });
+                continue;
+            }
+
+            let leading = attach_trailing(&mut tokens, pending);
+            pending = Vec::new();
+            tokens.push(LosslessToken {
+                kind,
+                leading,
+                span,
+                trailing: Vec::new(),
+            });
+        }
+        split_whitespace(source, pos..source.len(), &mut pending);
+        let end_trivia = attach_trailing(&mut tokens, pending);
+
+        Self {
+            end_trivia,
+            source,
+            tokens,
+        }
+    }
+
+    /// The lexed source-text.
+    #[must_use]
+    pub const fn source(&self) -> &'src str {
+        self.source
+    }
+
+    /// The text of `token` within the source-text.
+    #[must_use]
+    pub fn text(&self, token: &LosslessToken) -> &'src str {
+        self.source.get(token.span.clone()).unwrap_or_default()
+    }
+
+    /// The tokens, in source order.
+    #[must_use]
+    pub fn tokens(&self) -> &[LosslessToken] {
+        &self.tokens
+    }
+}
+
+impl Display for LosslessStream<'_> {
+    /// Reconstructs the source-text from the tokens and their trivia.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            for trivia in &token.leading {
+                f.write_str(trivia.text(self.source))?;
+            }
+            f.write_str(self.text(token))?;
+            for trivia in &token.trailing {
+                f.write_str(trivia.text(self.source))?;
+            }
+        }
+        for trivia in &self.end_trivia {
+            f.write_str(trivia.text(self.source))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves the trivia at the start of `pending` that's on the same line as the
+/// last token into its trailing trivia, returning the rest.
+fn attach_trailing(tokens: &mut [LosslessToken], mut pending: Vec<Trivia>) -> Vec<Trivia> {
+    let Some(last) = tokens.last_mut() else {
+        return pending;
+    };
+    let end_of_line = pending
+        .iter()
+        .position(|trivia| trivia.kind == TriviaKind::Newline)
+        .unwrap_or(pending.len());
+    let rest = pending.split_off(end_of_line);
+    last.trailing = pending;
+    rest
+}
+
+/// Splits the whitespace in `span` into runs of spaces and tabs, and newlines.
+fn split_whitespace(source: &str, span: Range<usize>, trivia: &mut Vec<Trivia>) {
+    let mut pos = span.start;
+    while pos < span.end {
+        let rest = source.get(pos..span.end).unwrap_or_default();
+        let (kind, len) = if rest.starts_with("\r\n") {
+            (TriviaKind::Newline, 2)
+        } else if rest.starts_with('\n') {
+            (TriviaKind::Newline, 1)
+        } else {
+            let len = rest.find(['\n', '\r']).unwrap_or(rest.len());
+            // A `\r` that isn't part of a `\r\n` is kept as whitespace.
+            (TriviaKind::Whitespace, len.max(1))
+        };
+        trivia.push(Trivia {
+            kind,
+            span: pos..pos.strict_add(len),
+        });
+        pos = pos.strict_add(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The kind and text of each piece of trivia in `trivia`.
+    fn describe<'src>(source: &'src str, trivia: &[Trivia]) -> Vec<(TriviaKind, &'src str)> {
+        trivia
+            .iter()
+            .map(|piece| (piece.kind, piece.text(source)))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_the_source_text() {
+        for source in [
+            "let a = 1;\r\nlet b = 2;\r\n",
+            "let a = 1;\rlet b = 2;\n",
+            "let a = 1; // one\n",
+            "// one\n/* two */\n\nlet a = 1;\n",
+            "let a = $;\nlet s = \"open\n/* never closed",
+            "let a = 1;",
+            "",
+        ] {
+            assert_eq!(LosslessStream::new(source).to_string(), source);
+        }
+    }
+
+    #[test]
+    fn keeps_a_lone_carriage_return_as_whitespace() {
+        let source = "a\r\nb\rc";
+        let stream = LosslessStream::new(source);
+        let tokens = stream.tokens();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(describe(source, &tokens[0].trailing), []);
+        assert_eq!(
+            describe(source, &tokens[1].leading),
+            [(TriviaKind::Newline, "\r\n")]
+        );
+        assert_eq!(
+            describe(source, &tokens[1].trailing),
+            [(TriviaKind::Whitespace, "\r")]
+        );
+        assert_eq!(describe(source, &tokens[2].leading), []);
+    }
+
+    #[test]
+    fn splits_trivia_into_trailing_and_leading() {
+        let source = "a // after a\n\n// before b\nb /* after b */\n";
+        let stream = LosslessStream::new(source);
+        let tokens = stream.tokens();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            describe(source, &tokens[0].trailing),
+            [
+                (TriviaKind::Whitespace, " "),
+                (TriviaKind::Comment, "// after a"),
+            ]
+        );
+        assert_eq!(
+            describe(source, &tokens[1].leading),
+            [
+                (TriviaKind::Newline, "\n"),
+                (TriviaKind::Newline, "\n"),
+                (TriviaKind::Comment, "// before b"),
+                (TriviaKind::Newline, "\n"),
+            ]
+        );
+        assert_eq!(
+            describe(source, &tokens[1].trailing),
+            [
+                (TriviaKind::Whitespace, " "),
+                (TriviaKind::Comment, "/* after b */"),
+            ]
+        );
+        assert_eq!(
+            describe(source, stream.end_trivia()),
+            [(TriviaKind::Newline, "\n")]
+        );
+    }
+}
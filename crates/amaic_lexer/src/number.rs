@@ -0,0 +1,296 @@
+//! See [`parse_int`] and [`parse_float`].
+
+use crate::{AmaicLexer, LexError};
+
+/// Decodes an integer literal into its magnitude.
+///
+/// The literal starts at the byte-offset `offset` in the source-text. It may
+/// have a `0x`, `0o` or `0b` prefix for bases other than 10, and `_`s anywhere
+/// after its first digit.
+///
+/// Literals are never negative, since `-` is parsed as an operator, so the
+/// magnitude may be one more than `i64::MAX` in order for `i64::MIN` to be
+/// written. Whether that's allowed is decided once it's known if the literal
+/// is negated.
+///
+/// # Errors
+///
+/// Errors if a digit is invalid for the literal's base, if there are no digits
+/// after the prefix, or if the magnitude is out of range for an `i64`.
+pub fn parse_int(literal: &str, offset: usize) -> Result<u64, LexError> {
+    let span = offset..offset.strict_add(literal.len());
+    let (radix, digits) = split_radix(literal);
+    let digits_offset = span.end.strict_sub(digits.len());
+
+    if let Some((index, ch)) = digits
+        .char_indices()
+        .find(|&(_, ch)| ch != '_' && !ch.is_digit(radix))
+    {
+        let start = digits_offset.strict_add(index);
+        return Err(LexError::InvalidDigit {
+            span: start..start.strict_add(ch.len_utf8()),
+            radix,
+        });
+    }
+    if !digits.contains(|ch| ch != '_') {
+        return Err(LexError::MissingDigits { span });
+    }
+
+    digits
+        .chars()
+        .filter_map(|ch| ch.to_digit(radix))
+        .try_fold(0_u64, |acc, digit| {
+            acc.checked_mul(u64::from(radix))?
+                .checked_add(u64::from(digit))
+        })
+        .filter(|&magnitude| magnitude <= i64::MIN.unsigned_abs())
+        .ok_or(LexError::IntegerOverflow { span })
+}
+
+/// Decodes a float literal, which starts at the byte-offset `offset` in the
+/// source-text.
+///
+/// Literals may have an exponent, and `_`s anywhere after their first digit.
+///
+/// # Errors
+///
+/// Errors if there's an exponent without any digits.
+///
+/// # Panics
+///
+/// Panics if `literal` isn't otherwise a valid float literal, which is
+/// guaranteed for the slices of [`TokenKind::Float`](crate::TokenKind::Float)
+/// tokens.
+pub fn parse_float(literal: &str, offset: usize) -> Result<f64, LexError> {
+    if let Some((mantissa, exponent)) = literal.split_once(['e', 'E'])
+        && !exponent
+            .trim_start_matches(['+', '-'])
+            .contains(|ch| ch != '_')
+    {
+        return Err(LexError::MissingExponent {
+            span: offset.strict_add(mantissa.len())..offset.strict_add(literal.len()),
+        });
+    }
+
+    Ok(literal
+        .replace('_', "")
+        .parse()
+        .expect("the float regexes only match valid floats"))
+}
+
+/// Splits the base off of an integer literal, returning it along with the
+/// digits after its prefix.
+fn split_radix(literal: &str) -> (u32, &str) {
+    let radix = match literal.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => return (10, literal),
+    };
+    (radix, literal.get(2..).unwrap_or_default())
+}
+
+/// Consumes the letters directly after a numeric literal, such as the `abc` in
+/// `123abc`, which would otherwise start an identifier, so that they're
+/// reported once as an invalid digit.
+fn reject_suffix(lex: &mut AmaicLexer, radix: u32) -> Result<(), LexError> {
+    let rest = lex.remainder();
+    let len = rest
+        .find(|ch: char| !ch.is_alphanumeric() && ch != '_')
+        .unwrap_or(rest.len());
+    let Some(first) = rest.chars().next().filter(|_| len > 0) else {
+        return Ok(());
+    };
+
+    let start = lex.span().end;
+    lex.bump(len);
+    Err(LexError::InvalidDigit {
+        span: start..start.strict_add(first.len_utf8()),
+        radix,
+    })
+}
+
+/// Validates a lexed integer literal.
+pub fn validate_int(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    parse_int(lex.slice(), lex.span().start)?;
+    let (radix, _) = split_radix(lex.slice());
+    reject_suffix(lex, radix)
+}
+
+/// Validates a lexed float literal.
+pub fn validate_float(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    parse_float(lex.slice(), lex.span().start)?;
+    reject_suffix(lex, 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use logos::Logos as _;
+
+    use super::*;
+    use crate::TokenKind;
+
+    /// The kind of each token in `source`, or the error lexing it.
+    fn lex(source: &str) -> Vec<Result<TokenKind, LexError>> {
+        TokenKind::lexer(source).collect()
+    }
+
+    #[test]
+    fn parses_every_base() {
+        assert_eq!(parse_int("0", 0), Ok(0));
+        assert_eq!(parse_int("1_000_000", 0), Ok(1_000_000));
+        assert_eq!(parse_int("0xff", 0), Ok(255));
+        assert_eq!(parse_int("0XFf_0", 0), Ok(0xff0));
+        assert_eq!(parse_int("0o17", 0), Ok(0o17));
+        assert_eq!(parse_int("0O7_7", 0), Ok(0o77));
+        assert_eq!(parse_int("0b1010", 0), Ok(0b1010));
+        assert_eq!(parse_int("0B_1", 0), Ok(1));
+    }
+
+    #[test]
+    fn allows_the_magnitude_of_i64_min() {
+        assert_eq!(
+            parse_int("9223372036854775808", 0),
+            Ok(i64::MIN.unsigned_abs())
+        );
+        assert_eq!(
+            parse_int("0x8000_0000_0000_0000", 0),
+            Ok(i64::MIN.unsigned_abs())
+        );
+        assert_eq!(
+            parse_int("9223372036854775809", 4),
+            Err(LexError::IntegerOverflow { span: 4..23 })
+        );
+        assert_eq!(
+            parse_int("18446744073709551616", 0),
+            Err(LexError::IntegerOverflow { span: 0..20 })
+        );
+    }
+
+    #[test]
+    fn rejects_digits_outside_the_base() {
+        assert_eq!(
+            parse_int("0b102", 10),
+            Err(LexError::InvalidDigit {
+                span: 14..15,
+                radix: 2
+            })
+        );
+        assert_eq!(
+            parse_int("0o8", 0),
+            Err(LexError::InvalidDigit {
+                span: 2..3,
+                radix: 8
+            })
+        );
+        assert_eq!(
+            parse_int("0xfg", 0),
+            Err(LexError::InvalidDigit {
+                span: 3..4,
+                radix: 16
+            })
+        );
+        assert_eq!(
+            parse_int("12a", 0),
+            Err(LexError::InvalidDigit {
+                span: 2..3,
+                radix: 10
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_prefixes_without_digits() {
+        assert_eq!(
+            parse_int("0x", 1),
+            Err(LexError::MissingDigits { span: 1..3 })
+        );
+        assert_eq!(
+            parse_int("0b__", 0),
+            Err(LexError::MissingDigits { span: 0..4 })
+        );
+    }
+
+    #[test]
+    fn parses_floats() {
+        assert_eq!(parse_float("1.5", 0), Ok(1.5_f64));
+        assert_eq!(parse_float("1_000.25", 0), Ok(1000.25_f64));
+        assert_eq!(parse_float("1e3", 0), Ok(1000.0_f64));
+        assert_eq!(parse_float("1.5E-3", 0), Ok(0.0015_f64));
+        assert_eq!(parse_float("2e+2", 0), Ok(200.0_f64));
+        assert_eq!(parse_float("1e1_0", 0), Ok(1e10_f64));
+    }
+
+    #[test]
+    fn rejects_exponents_without_digits() {
+        assert_eq!(
+            parse_float("1e", 0),
+            Err(LexError::MissingExponent { span: 1..2 })
+        );
+        assert_eq!(
+            parse_float("1.5e+", 2),
+            Err(LexError::MissingExponent { span: 5..7 })
+        );
+        assert_eq!(
+            parse_float("1E_", 0),
+            Err(LexError::MissingExponent { span: 1..3 })
+        );
+    }
+
+    #[test]
+    fn rejects_letters_after_a_literal() {
+        let invalid = |start: usize, radix| {
+            Err(LexError::InvalidDigit {
+                span: start..start.strict_add(1),
+                radix,
+            })
+        };
+        assert_eq!(lex("123abc"), [invalid(3, 10)]);
+        assert_eq!(lex("1.5x"), [invalid(3, 10)]);
+        assert_eq!(lex("1e5_f"), [invalid(4, 10)]);
+        assert_eq!(
+            lex("0x1g + 1"),
+            [invalid(3, 16), Ok(TokenKind::Plus), Ok(TokenKind::Int)]
+        );
+        assert_eq!(
+            lex("0ffé"),
+            [Err(LexError::InvalidDigit {
+                span: 1..2,
+                radix: 10
+            })]
+        );
+        assert_eq!(
+            lex("0xffé"),
+            [Err(LexError::InvalidDigit {
+                span: 4..6,
+                radix: 16
+            })]
+        );
+    }
+
+    #[test]
+    fn ends_literals_at_anything_else() {
+        assert_eq!(
+            lex("1..2"),
+            [
+                Ok(TokenKind::Int),
+                Ok(TokenKind::DotDot),
+                Ok(TokenKind::Int),
+            ]
+        );
+        assert_eq!(
+            lex("1.x"),
+            [
+                Ok(TokenKind::Int),
+                Ok(TokenKind::Dot),
+                Ok(TokenKind::Identifier),
+            ]
+        );
+        assert_eq!(
+            lex("1 abc"),
+            [Ok(TokenKind::Int), Ok(TokenKind::Identifier)]
+        );
+        assert_eq!(lex("2)"), [Ok(TokenKind::Int), Ok(TokenKind::ClosedParen)]);
+        assert_eq!(lex("1.5"), [Ok(TokenKind::Float)]);
+    }
+}
@@ -0,0 +1,196 @@
+//! See [`Operator`].
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::TokenKind;
+
+/// An operator in an expression, which is infix, prefix, or both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "Passes match on every operator, so that adding one points out each of them."
+)]
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "Operators are grouped by what they operate on."
+)]
+pub enum Operator {
+    /// `+`, infix or prefix.
+    Plus,
+    /// `-`, infix or prefix.
+    Minus,
+    /// `*`.
+    Star,
+    /// `/`.
+    Slash,
+    /// `%`.
+    Modulo,
+    /// `++`.
+    Concat,
+    /// `<<`.
+    Lsh,
+    /// `>>`.
+    Rsh,
+    /// `&`.
+    Ampersand,
+    /// `|`.
+    Pipe,
+    /// `^`.
+    Caret,
+    /// `~`, prefix only.
+    Tilde,
+    /// `!`, prefix only.
+    Bang,
+    /// `==`.
+    Eq,
+    /// `!=`.
+    Ne,
+    /// `<`.
+    Lt,
+    /// `>`.
+    Gt,
+    /// `<=`.
+    Le,
+    /// `>=`.
+    Ge,
+    /// `and`.
+    LogAnd,
+    /// `or`.
+    LogOr,
+    /// `..`.
+    Range,
+    /// `..=`.
+    RangeInclus,
+    /// `=`.
+    Assign,
+    /// `+=`.
+    PlusAssign,
+    /// `-=`.
+    MinusAssign,
+    /// `*=`.
+    StarAssign,
+    /// `/=`.
+    SlashAssign,
+    /// `%=`.
+    ModuloAssign,
+}
+
+impl Operator {
+    /// The operator `kind` is written as, if it's one.
+    #[must_use]
+    pub const fn from_token(kind: TokenKind) -> Option<Self> {
+        Some(match kind {
+            TokenKind::Plus => Self::Plus,
+            TokenKind::Minus => Self::Minus,
+            TokenKind::Star => Self::Star,
+            TokenKind::Slash => Self::Slash,
+            TokenKind::Percent => Self::Modulo,
+            TokenKind::PlusPlus => Self::Concat,
+            TokenKind::OpenAngleOpenAngle => Self::Lsh,
+            TokenKind::ClosedAngleClosedAngle => Self::Rsh,
+            TokenKind::Ampersand => Self::Ampersand,
+            TokenKind::Pipe => Self::Pipe,
+            TokenKind::Caret => Self::Caret,
+            TokenKind::Tilde => Self::Tilde,
+            TokenKind::Bang => Self::Bang,
+            TokenKind::EqualEqual => Self::Eq,
+            TokenKind::BangEqual => Self::Ne,
+            TokenKind::OpenAngle => Self::Lt,
+            TokenKind::ClosedAngle => Self::Gt,
+            TokenKind::OpenAngleEqual => Self::Le,
+            TokenKind::ClosedAngleEqual => Self::Ge,
+            TokenKind::And => Self::LogAnd,
+            TokenKind::Or => Self::LogOr,
+            TokenKind::DotDot => Self::Range,
+            TokenKind::DotDotEqual => Self::RangeInclus,
+            TokenKind::Equal => Self::Assign,
+            TokenKind::PlusEqual => Self::PlusAssign,
+            TokenKind::MinusEqual => Self::MinusAssign,
+            TokenKind::StarEqual => Self::StarAssign,
+            TokenKind::SlashEqual => Self::SlashAssign,
+            TokenKind::PercentEqual => Self::ModuloAssign,
+            _ => return None,
+        })
+    }
+
+    /// Whether the operator can go between two operands.
+    #[must_use]
+    pub const fn is_infix(self) -> bool {
+        !matches!(self, Self::Tilde | Self::Bang)
+    }
+
+    /// Whether the operator can go before an operand.
+    #[must_use]
+    pub const fn is_prefix(self) -> bool {
+        matches!(self, Self::Plus | Self::Minus | Self::Tilde | Self::Bang)
+    }
+
+    /// The left and right binding powers of the operator as an infix operator.
+    /// An operator binds more tightly than those with lower binding powers,
+    /// and the side with the higher one decides its associativity, so that
+    /// `a - b - c` is `(a - b) - c` while `a = b = c` is `a = (b = c)`.
+    ///
+    /// Prefix-only operators don't bind as infix operators, so both of theirs
+    /// are zero.
+    #[must_use]
+    pub const fn precedence(self) -> (u32, u32) {
+        match self {
+            Self::Assign
+            | Self::PlusAssign
+            | Self::MinusAssign
+            | Self::StarAssign
+            | Self::SlashAssign
+            | Self::ModuloAssign => (2, 1),
+            Self::LogOr => (3, 4),
+            Self::LogAnd => (5, 6),
+            Self::Eq | Self::Ne => (7, 8),
+            Self::Lt | Self::Gt | Self::Le | Self::Ge => (9, 10),
+            Self::Range | Self::RangeInclus => (11, 12),
+            Self::Pipe => (13, 14),
+            Self::Caret => (15, 16),
+            Self::Ampersand => (17, 18),
+            Self::Lsh | Self::Rsh => (19, 20),
+            Self::Plus | Self::Minus | Self::Concat => (21, 22),
+            Self::Star | Self::Slash | Self::Modulo => (23, 24),
+            Self::Tilde | Self::Bang => (0, 0),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let as_str = match *self {
+            Self::Ampersand => "&",
+            Self::Assign => "=",
+            Self::Bang => "!",
+            Self::Caret => "^",
+            Self::Concat => "++",
+            Self::Eq => "==",
+            Self::Ge => ">=",
+            Self::Gt => ">",
+            Self::Le => "<=",
+            Self::LogAnd => "and",
+            Self::LogOr => "or",
+            Self::Lsh => "<<",
+            Self::Lt => "<",
+            Self::Minus => "-",
+            Self::MinusAssign => "-=",
+            Self::Modulo => "%",
+            Self::ModuloAssign => "%=",
+            Self::Ne => "!=",
+            Self::Pipe => "|",
+            Self::Plus => "+",
+            Self::PlusAssign => "+=",
+            Self::Range => "..",
+            Self::RangeInclus => "..=",
+            Self::Rsh => ">>",
+            Self::Slash => "/",
+            Self::SlashAssign => "/=",
+            Self::Star => "*",
+            Self::StarAssign => "*=",
+            Self::Tilde => "~",
+        };
+
+        f.write_str(as_str)
+    }
+}
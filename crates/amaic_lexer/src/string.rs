@@ -0,0 +1,480 @@
+//! See [`string_parts`].
+
+use std::{mem, ops::Range};
+
+use crate::{AmaicLexer, LexError};
+
+/// A piece of a string literal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[expect(
+    clippy::exhaustive_enums,
+    reason = "A string literal is only ever made up of text and interpolations."
+)]
+pub enum StringPart<'src> {
+    /// An expression between braces, to be converted to a string and spliced
+    /// into the surrounding text.
+    Interpolation {
+        /// The source-text of the expression, without the braces.
+        source: &'src str,
+
+        /// The span of `source` in the source-text.
+        span: Range<usize>,
+    },
+
+    /// Text, with its escape-sequences decoded.
+    Text(String),
+}
+
+/// The parts of a string literal, as they're being decoded.
+#[derive(Default)]
+struct Parts<'src> {
+    /// The parts before the text being decoded.
+    parts: Vec<StringPart<'src>>,
+
+    /// The text decoded since the last interpolation.
+    text: String,
+}
+
+impl<'src> Parts<'src> {
+    /// Finishes decoding, returning every part.
+    fn finish(mut self) -> Vec<StringPart<'src>> {
+        if !self.text.is_empty() || self.parts.is_empty() {
+            self.parts.push(StringPart::Text(self.text));
+        }
+        self.parts
+    }
+}
+
+/// Splits a string literal, quotes and prefix included, which starts at the
+/// byte-offset `offset` in the source-text, into its text and interpolated
+/// expressions.
+///
+/// Literals come in three forms:
+/// - `"..."`, which may contain escape-sequences and interpolations.
+/// - `"""..."""`, which may also span several lines. A first or last line
+///   that's blank is dropped, and the indentation common to the remaining
+///   lines and the closing delimiter is stripped from each.
+/// - `r"..."`, `r#"..."#` and so on, which are taken verbatim, and may span
+///   several lines. Adding `#`s allows the literal to contain `"`.
+///
+/// A literal without interpolations yields a single [`StringPart::Text`], and
+/// text is never empty otherwise.
+///
+/// An interpolation ends at the first `}` after its `{`, so the expression in
+/// it can't contain braces or string literals of its own: `"{f({a})}"` is an
+/// interpolation of `f({a`, followed by `)` and an unmatched `}`, and a `"` in
+/// an interpolation ends the literal before the lexer ever gets here.
+///
+/// # Errors
+///
+/// Errors with the span of the first invalid escape-sequence or
+/// interpolation.
+pub fn string_parts(literal: &str, offset: usize) -> Result<Vec<StringPart<'_>>, LexError> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        // The quotes are between the `#`s, so the contents are never trimmed.
+        let quoted = raw.trim_matches('#');
+        let contents = quoted
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .unwrap_or(quoted);
+        return Ok(vec![StringPart::Text(contents.to_owned())]);
+    }
+
+    let mut parts = Parts::default();
+    if let Some(contents) = literal
+        .strip_prefix(r#"""""#)
+        .and_then(|literal| literal.strip_suffix(r#"""""#))
+    {
+        let lines = dedent(contents, offset.strict_add(3));
+        for (index, (line, line_offset)) in lines.into_iter().enumerate() {
+            if index > 0 {
+                parts.text.push('\n');
+            }
+            decode(line, line_offset, &mut parts)?;
+        }
+    } else {
+        let contents = literal
+            .strip_prefix('"')
+            .and_then(|literal| literal.strip_suffix('"'))
+            .unwrap_or(literal);
+        decode(contents, offset.strict_add(1), &mut parts)?;
+    }
+
+    Ok(parts.finish())
+}
+
+/// Splits the contents of a multi-line string into its lines, with their
+/// indentation stripped, along with the byte-offset each starts at.
+fn dedent(contents: &str, offset: usize) -> Vec<(&str, usize)> {
+    let indentation = |line: &str| {
+        line.len()
+            .strict_sub(line.trim_start_matches([' ', '\t']).len())
+    };
+    let is_blank = |line: &str| indentation(line) == line.len();
+
+    let mut lines = Vec::new();
+    let mut start = offset;
+    for line in contents.split('\n') {
+        lines.push((line.strip_suffix('\r').unwrap_or(line), start));
+        start = start.strict_add(line.len()).strict_add(1);
+    }
+
+    if lines.len() > 1 && lines.first().is_some_and(|&(line, _)| is_blank(line)) {
+        lines.remove(0);
+    }
+    let closing = match lines.last() {
+        Some(&(line, _)) if lines.len() > 1 && is_blank(line) => lines.pop(),
+        _ => None,
+    };
+
+    let indent = lines
+        .iter()
+        .map(|&(line, _)| line)
+        .filter(|line| !is_blank(line))
+        .chain(closing.map(|(line, _)| line))
+        .map(indentation)
+        .min()
+        .unwrap_or(0);
+    lines
+        .into_iter()
+        .map(|(line, line_offset)| {
+            // the indentation is ASCII, so `cut` is always a char boundary
+            let cut = indent.min(indentation(line));
+            (
+                line.get(cut..).unwrap_or_default(),
+                line_offset.strict_add(cut),
+            )
+        })
+        .collect()
+}
+
+/// Decodes the escape-sequences and interpolations of `contents`, which starts
+/// at the byte-offset `base` in the source-text.
+fn decode<'src>(contents: &'src str, base: usize, parts: &mut Parts<'src>) -> Result<(), LexError> {
+    let mut chars = contents.char_indices();
+    while let Some((start, ch)) = chars.next() {
+        let at = base.strict_add(start);
+        let decoded = match ch {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => '\n',
+                Some((_, 'r')) => '\r',
+                Some((_, 't')) => '\t',
+                Some((_, '0')) => '\0',
+                Some((_, '\\')) => '\\',
+                Some((_, '"')) => '"',
+                Some((_, '{')) => '{',
+                Some((_, '}')) => '}',
+                Some((_, 'u')) => {
+                    let (decoded, len) = unescape_unicode(chars.as_str(), at)?;
+                    // `len` covers `\u`, which has already been consumed.
+                    chars.nth(len.strict_sub(3));
+                    decoded
+                }
+                Some((index, escaped)) => {
+                    return Err(LexError::UnknownEscape {
+                        span: at..base.strict_add(index).strict_add(escaped.len_utf8()),
+                    });
+                }
+                None => {
+                    return Err(LexError::UnknownEscape {
+                        span: at..at.strict_add(1),
+                    });
+                }
+            },
+            '{' => {
+                let Some((source, _)) = chars.as_str().split_once('}') else {
+                    return Err(LexError::UnterminatedInterpolation {
+                        span: at..at.strict_add(1),
+                    });
+                };
+                let end = at.strict_add(1).strict_add(source.len());
+                if source.trim().is_empty() {
+                    return Err(LexError::EmptyInterpolation {
+                        span: at..end.strict_add(1),
+                    });
+                }
+
+                if !parts.text.is_empty() {
+                    let text = mem::take(&mut parts.text);
+                    parts.parts.push(StringPart::Text(text));
+                }
+                parts.parts.push(StringPart::Interpolation {
+                    source,
+                    span: at.strict_add(1)..end,
+                });
+                chars.nth(source.chars().count());
+                continue;
+            }
+            '}' => {
+                return Err(LexError::UnmatchedBrace {
+                    span: at..at.strict_add(1),
+                });
+            }
+            other => other,
+        };
+        parts.text.push(decoded);
+    }
+
+    Ok(())
+}
+
+/// Decodes a `\u{...}` escape, given the text after its `\u` and the
+/// byte-offset of its `\`, returning the decoded character and the length of
+/// the escape in bytes.
+fn unescape_unicode(rest: &str, offset: usize) -> Result<(char, usize), LexError> {
+    let invalid = |len: usize| LexError::InvalidUnicodeEscape {
+        span: offset..offset.strict_add(len),
+    };
+
+    let Some(braced) = rest.strip_prefix('{') else {
+        return Err(invalid(2));
+    };
+    let Some((digits, _)) = braced.split_once('}') else {
+        return Err(invalid(rest.len().strict_add(2)));
+    };
+    let len = digits.len().strict_add(4);
+
+    if digits.is_empty() || digits.len() > 6 || !digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return Err(invalid(len));
+    }
+
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|decoded| (decoded, len))
+        .ok_or_else(|| LexError::InvalidCodepoint {
+            span: offset..offset.strict_add(len),
+        })
+}
+
+/// Validates the escape-sequences and interpolations of a lexed string
+/// literal.
+pub fn validate(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    string_parts(lex.slice(), lex.span().start).map(drop)
+}
+
+/// Lexes the rest of a multi-line string, after its opening `"""`.
+pub fn lex_multiline(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    let rest = lex.remainder().as_bytes();
+    let mut i = 0_usize;
+    while let Some(tail) = rest.get(i..).filter(|tail| !tail.is_empty()) {
+        if tail.starts_with(b"\\") {
+            i = i.strict_add(2);
+        } else if tail.starts_with(br#"""""#) {
+            lex.bump(i.strict_add(3));
+            return validate(lex);
+        } else {
+            i = i.strict_add(1);
+        }
+    }
+    unterminated(lex)
+}
+
+/// Lexes the rest of a raw string, after its `r`, `#`s and opening quote.
+pub fn lex_raw(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    let hashes = lex.slice().len().strict_sub(2);
+    let closing = format!("\"{}", "#".repeat(hashes));
+    match lex.remainder().find(&closing) {
+        Some(end) => {
+            lex.bump(end.strict_add(closing.len()));
+            Ok(())
+        }
+        None => unterminated(lex),
+    }
+}
+
+/// Consumes the rest of the source-text, since a string that's never closed
+/// extends to its end.
+fn unterminated(lex: &mut AmaicLexer) -> Result<(), LexError> {
+    let start = lex.span().start;
+    lex.bump(lex.remainder().len());
+    Err(LexError::UnterminatedString {
+        span: start..lex.span().end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use logos::Logos as _;
+
+    use super::*;
+    use crate::TokenKind;
+
+    /// The kind of each token in `source`, or the error lexing it.
+    fn lex(source: &str) -> Vec<Result<TokenKind, LexError>> {
+        TokenKind::lexer(source).collect()
+    }
+
+    #[test]
+    fn decodes_escapes_and_interpolations() {
+        assert_eq!(
+            string_parts(r#""a\n\u{e9}\{b\} {x + 1}!""#, 0),
+            Ok(vec![
+                StringPart::Text("a\né{b} ".to_owned()),
+                StringPart::Interpolation {
+                    source: "x + 1",
+                    span: 17..22,
+                },
+                StringPart::Text("!".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_escapes() {
+        assert_eq!(
+            lex(r#""a\qb""#),
+            [Err(LexError::UnknownEscape { span: 2..4 })]
+        );
+        assert_eq!(
+            lex(r#""\é""#),
+            [Err(LexError::UnknownEscape { span: 1..4 })]
+        );
+        // the lexer never ends a literal on an escaped quote, so only a
+        // literal from elsewhere can end in a backslash
+        assert_eq!(
+            string_parts(r#""a\""#, 10),
+            Err(LexError::UnknownEscape { span: 12..13 })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_unicode_escapes() {
+        assert_eq!(
+            lex(r#""\u{}""#),
+            [Err(LexError::InvalidUnicodeEscape { span: 1..5 })]
+        );
+        assert_eq!(
+            lex(r#""\u{1234567}""#),
+            [Err(LexError::InvalidUnicodeEscape { span: 1..12 })]
+        );
+        assert_eq!(
+            lex(r#""\u{12g}""#),
+            [Err(LexError::InvalidUnicodeEscape { span: 1..8 })]
+        );
+        assert_eq!(
+            lex(r#""a\u41""#),
+            [Err(LexError::InvalidUnicodeEscape { span: 2..4 })]
+        );
+        assert_eq!(
+            lex(r#""\u{41""#),
+            [Err(LexError::InvalidUnicodeEscape { span: 1..6 })]
+        );
+    }
+
+    #[test]
+    fn rejects_codepoints_that_are_not_scalar_values() {
+        assert_eq!(
+            lex(r#""\u{110000}""#),
+            [Err(LexError::InvalidCodepoint { span: 1..11 })]
+        );
+        assert_eq!(
+            lex(r#""ab\u{D800}""#),
+            [Err(LexError::InvalidCodepoint { span: 3..11 })]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_interpolations() {
+        assert_eq!(
+            lex(r#""a}b""#),
+            [Err(LexError::UnmatchedBrace { span: 2..3 })]
+        );
+        assert_eq!(
+            lex(r#""{a""#),
+            [Err(LexError::UnterminatedInterpolation { span: 1..2 })]
+        );
+        assert_eq!(
+            lex(r#""{ }""#),
+            [Err(LexError::EmptyInterpolation { span: 1..4 })]
+        );
+        // interpolations can't nest, so the first `}` ends the expression
+        assert_eq!(
+            lex(r#""{f({a})}""#),
+            [Err(LexError::UnmatchedBrace { span: 8..9 })]
+        );
+    }
+
+    /// The text of a literal without interpolations.
+    fn text(literal: &str) -> Option<String> {
+        let parts = string_parts(literal, 0).ok()?;
+        match *parts.as_slice() {
+            [StringPart::Text(ref text)] => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn strips_the_common_indentation() {
+        assert_eq!(
+            text("\"\"\"\n    a\n      b\n    \"\"\"").as_deref(),
+            Some("a\n  b")
+        );
+        assert_eq!(
+            text("\"\"\"\n    a\n\n      b\n  \"\"\"").as_deref(),
+            Some("  a\n\n    b")
+        );
+        assert_eq!(
+            text("\"\"\"\n\ta\n\t\tb\n\t\"\"\"").as_deref(),
+            Some("a\n\tb")
+        );
+        assert_eq!(text("\"\"\"  a\n  b\"\"\"").as_deref(), Some("a\nb"));
+    }
+
+    #[test]
+    fn drops_a_blank_first_and_last_line() {
+        assert_eq!(text("\"\"\"\n  a\n  \"\"\"").as_deref(), Some("a"));
+        assert_eq!(text("\"\"\"  \n  a\"\"\"").as_deref(), Some("a"));
+        assert_eq!(text("\"\"\"a\n\"\"\"").as_deref(), Some("a"));
+        // only one blank line is dropped at each end, and the closing
+        // delimiter isn't indented, so neither is anything else
+        assert_eq!(text("\"\"\"\n\n  a\n\n\"\"\"").as_deref(), Some("\n  a\n"));
+    }
+
+    #[test]
+    fn normalizes_crlf_line_endings() {
+        assert_eq!(
+            text("\"\"\"\r\n  a\r\n    b\r\n  \"\"\"").as_deref(),
+            Some("a\n  b")
+        );
+    }
+
+    #[test]
+    fn takes_raw_strings_verbatim() {
+        assert_eq!(text(r#"r"a\n{b}""#).as_deref(), Some(r"a\n{b}"));
+        assert_eq!(text(r###"r##"a"#b"##"###).as_deref(), Some(r##"a"#b"##));
+        assert_eq!(text("r\"\n  a\n\"").as_deref(), Some("\n  a\n"));
+        assert_eq!(
+            lex(r###"r##"a"#b"## x"###),
+            [Ok(TokenKind::String), Ok(TokenKind::Identifier)]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_raw_and_multi_line_strings() {
+        assert_eq!(
+            lex(r##"x r##"a"#b"##),
+            [
+                Ok(TokenKind::Identifier),
+                Err(LexError::UnterminatedString { span: 2..10 }),
+            ]
+        );
+        assert_eq!(
+            lex("\"\"\"a\nb\" \\\"\"\""),
+            [Err(LexError::UnterminatedString { span: 0..12 })]
+        );
+    }
+
+    #[test]
+    fn reports_escape_errors_after_the_first_line() {
+        assert_eq!(
+            lex("\"\"\"\n  a\n  \\q\n  \"\"\""),
+            [Err(LexError::UnknownEscape { span: 10..12 })]
+        );
+        assert_eq!(
+            lex("\"\"\"\r\n  a\r\n  b}\r\n\"\"\""),
+            [Err(LexError::UnmatchedBrace { span: 13..14 })]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! See [`tokenize`].
+
+use std::ops::Range;
+
+use logos::Logos as _;
+
+use crate::{LexError, TokenKind};
+
+/// A token, along with where it is in the source-text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Token<'src> {
+    /// The kind of the token.
+    pub kind: TokenKind,
+
+    /// The text of the token.
+    pub slice: &'src str,
+
+    /// The byte-range of the token in the source-text.
+    pub span: Range<usize>,
+}
+
+/// Lexes `source` into the tokens that matter to the parser, dropping
+/// comments.
+///
+/// # Errors
+///
+/// Errors with every token that couldn't be lexed, along with its byte-range.
+/// The error's own span, if it has one, may point at a smaller part of it.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, Vec<(LexError, Range<usize>)>> {
+    parser_tokens(source, TokenKind::lexer(source).spanned())
+}
+
+/// Keeps the tokens of `source` that matter to the parser, as [`tokenize`]
+/// does, from tokens already lexed from it.
+pub fn parser_tokens(
+    source: &str,
+    lexed: impl IntoIterator<Item = (Result<TokenKind, LexError>, Range<usize>)>,
+) -> Result<Vec<Token<'_>>, Vec<(LexError, Range<usize>)>> {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    for (kind, span) in lexed {
+        match kind {
+            Ok(TokenKind::Comment) => {}
+            Ok(kind) => tokens.push(Token {
+                kind,
+                slice: source.get(span.clone()).unwrap_or_default(),
+                span,
+            }),
+            Err(err) => errors.push((err, span)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_comments() {
+        let tokens = tokenize("// docs\nx = 1 // comment\ny").expect("the source should lex");
+        let kinds: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.kind, token.slice))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (TokenKind::Identifier, "x"),
+                (TokenKind::Equal, "="),
+                (TokenKind::Int, "1"),
+                (TokenKind::Identifier, "y"),
+            ]
+        );
+        assert_eq!(tokens.first().map(|token| token.span.clone()), Some(8..9));
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let errors = tokenize("a @ b $").expect_err("the source shouldn't lex");
+        let spans: Vec<_> = errors.into_iter().map(|(_, span)| span).collect();
+        assert_eq!(spans, [2..3, 6..7]);
+    }
+}
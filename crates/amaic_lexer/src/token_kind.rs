@@ -1,6 +1,6 @@
 //! See [`TokenKind`].
 
-use crate::error::LexError;
+use crate::{comment, error::LexError, number, string};
 
 use logos::Logos;
 use std::fmt::{self, Display, Formatter};
@@ -60,6 +60,15 @@ pub enum TokenKind {
     /// Keyword: `match`.
     #[token("match")]
     Match,
+    /// Keyword: `let`.
+    #[token("let")]
+    Let,
+    /// Keyword: `then`.
+    #[token("then")]
+    Then,
+    /// Keyword: `do`.
+    #[token("do")]
+    Do,
 
     /// Delimiters: `(`.
     #[token("(")]
@@ -89,6 +98,9 @@ pub enum TokenKind {
     /// Symbol: `:`.
     #[token(":")]
     Colon,
+    /// Symbol: `;`.
+    #[token(";")]
+    Semicolon,
     /// Symbol: `,`.
     #[token(",")]
     Comma,
@@ -128,6 +140,9 @@ pub enum TokenKind {
     /// Symbol: `/`.
     #[token("/")]
     Slash,
+    /// Symbol: `~`.
+    #[token("~")]
+    Tilde,
 
     /// Combination-Symbol: `+=`.
     #[token("+=")]
@@ -171,32 +186,99 @@ pub enum TokenKind {
     /// Combination-Symbol: `::`.
     #[token("::")]
     ColonColon,
+    /// Combination-Symbol: `<<`.
+    #[token("<<")]
+    OpenAngleOpenAngle,
+    /// Combination-Symbol: `>>`.
+    #[token(">>")]
+    ClosedAngleClosedAngle,
+    /// Combination-Symbol: `++`.
+    #[token("++")]
+    PlusPlus,
 
-    /// Special: Identifies a given value.
-    #[regex("[a-zA-Z_][a-zA-Z0-9_]*")]
+    /// Special: Identifies a given value. Identifiers are compared after being
+    /// normalized with [`normalize_ident`](crate::normalize_ident).
+    #[regex(r"[\p{XID_Start}_]\p{XID_Continue}*")]
     Identifier,
-    /// Special: What you're reading right now.
+    /// Special: What you're reading right now, or a `/* ... */` block comment,
+    /// which may be nested.
     #[regex("//[^\r\n]*", allow_greedy = true)]
+    #[token("/*", comment::lex_block)]
     Comment,
+    /// Special: A `///` comment documenting the item after it. Like in Rust,
+    /// `////` starts a regular comment instead.
+    #[regex("///([^/\r\n][^\r\n]*)?", allow_greedy = true, priority = 10)]
+    DocComment,
+    /// Special: A `//!` comment documenting the module it's in.
+    #[regex("//![^\r\n]*", allow_greedy = true, priority = 10)]
+    ModuleDocComment,
 
     /// Literal: Integer-values; Comes in all the lovely bases of.
     /// - 16
     /// - 10
     /// - 8
     /// - 2
-    #[regex(r"-?[0-9][0-9_]*")]
-    #[regex(r"-?0[xX]_*[0-9a-fA-F]*")]
-    #[regex(r"-?0[oO]_*[0-8_]*")]
-    #[regex(r"-?0[bB]_*[01]*")]
+    ///
+    /// Prefixed literals match any alphanumeric digits, so that invalid ones
+    /// are reported by [`parse_int`](crate::parse_int) rather than starting a
+    /// new token.
+    ///
+    /// Literals are never negative; `-1` is lexed as a [`Minus`](Self::Minus)
+    /// followed by `1`, so that `a-1` is a subtraction.
+    #[regex(r"[0-9][0-9_]*", number::validate_int)]
+    #[regex(r"0[xX][0-9a-zA-Z_]*", number::validate_int)]
+    #[regex(r"0[oO][0-9a-zA-Z_]*", number::validate_int)]
+    #[regex(r"0[bB][0-9a-zA-Z_]*", number::validate_int)]
     Int,
-    /// Literal: floating-point number.
-    #[regex(r"-?[0-9][0-9_]*\._*[0-9_]*")]
+    /// Literal: floating-point number, with an optional exponent. A digit is
+    /// required after the `.`, so that `1..2` is a range.
+    #[regex(
+        r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9_]*)?",
+        number::validate_float
+    )]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9_]*", number::validate_float)]
     Float,
-    /// Literal: A collection of characters.
-    #[regex(r#""([^\r\n]|\\.)*""#)]
+    /// Literal: A collection of characters, which may be raw or span multiple
+    /// lines, as split by [`string_parts`](crate::string_parts).
+    #[regex(r#""([^"\\\r\n]|\\[^\r\n])*""#, string::validate)]
+    #[token(r#"""""#, string::lex_multiline)]
+    #[regex(r##"r#*""##, string::lex_raw)]
     String,
 }
 
+impl TokenKind {
+    /// Every keyword, in declaration order. Keywords can't be used as
+    /// identifiers, including those reserved for features the grammar doesn't
+    /// have yet.
+    pub const KEYWORDS: [Self; 19] = [
+        Self::Const,
+        Self::Var,
+        Self::If,
+        Self::Else,
+        Self::While,
+        Self::For,
+        Self::In,
+        Self::Return,
+        Self::True,
+        Self::False,
+        Self::Import,
+        Self::Func,
+        Self::SSelf,
+        Self::And,
+        Self::Or,
+        Self::Match,
+        Self::Let,
+        Self::Then,
+        Self::Do,
+    ];
+
+    /// Whether this token is a keyword.
+    #[must_use]
+    pub fn is_keyword(&self) -> bool {
+        Self::KEYWORDS.contains(self)
+    }
+}
+
 impl Display for TokenKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let as_str = match *self {
@@ -206,6 +288,7 @@ impl Display for TokenKind {
             Self::BangEqual => "!=",
             Self::Caret => "^",
             Self::ClosedAngle => ">",
+            Self::ClosedAngleClosedAngle => ">>",
             Self::ClosedAngleEqual => ">=",
             Self::ClosedBrace => "}",
             Self::ClosedBrack => "]",
@@ -216,6 +299,8 @@ impl Display for TokenKind {
             Self::Comment => "<comment>",
             Self::Const => "const",
             Self::DashClosedAngle => "->",
+            Self::Do => "do",
+            Self::DocComment => "<doc comment>",
             Self::Dot => ".",
             Self::DotDot => "..",
             Self::DotDotEqual => "..=",
@@ -231,12 +316,15 @@ impl Display for TokenKind {
             Self::If => "if",
             Self::Import => "import",
             Self::In => "in",
+            Self::Let => "let",
             Self::Int => "<integer literal>",
             Self::Match => "match",
             Self::Minus => "-",
             Self::MinusEqual => "-=",
+            Self::ModuleDocComment => "<module doc comment>",
             Self::OpenAngle => "<",
             Self::OpenAngleEqual => "<=",
+            Self::OpenAngleOpenAngle => "<<",
             Self::OpenBrace => "{",
             Self::OpenBrack => "[",
             Self::OpenParen => "(",
@@ -246,14 +334,18 @@ impl Display for TokenKind {
             Self::Pipe => "|",
             Self::Plus => "+",
             Self::PlusEqual => "+=",
+            Self::PlusPlus => "++",
             Self::Question => "?",
             Self::Return => "return",
             Self::SSelf => "self",
+            Self::Semicolon => ";",
             Self::Slash => "/",
             Self::SlashEqual => "/=",
             Self::Star => "*",
             Self::StarEqual => "*=",
             Self::String => "<string literal>",
+            Self::Then => "then",
+            Self::Tilde => "~",
             Self::True => "true",
             Self::Var => "var",
             Self::While => "while",
@@ -0,0 +1,212 @@
+//! See [`reparse`].
+
+use std::{mem, ops::Range};
+
+use amaic_ast::{ASTModule, ASTNode, FrontendType, VisitorMut, visit};
+use amaic_core::FileId;
+use amaic_lexer::{LexedSource, TextEdit};
+
+use crate::Parser;
+
+/// Moves every span in a tree by the same number of bytes.
+struct Shift(isize);
+
+impl VisitorMut for Shift {
+    fn visit_node_mut(&mut self, node: &mut ASTNode) {
+        node.span = node.span.shift(self.0);
+        visit::walk_node_mut(self, node);
+    }
+
+    fn visit_param_mut(&mut self, param: &mut visit::Param) {
+        param.2 = param.2.shift(self.0);
+        visit::walk_param_mut(self, param);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut FrontendType) {
+        ty.span = ty.span.shift(self.0);
+        visit::walk_type_mut(self, ty);
+    }
+}
+
+/// Applies `edit` to `lexed`, relexing only the region around it, then
+/// updates `module` by reparsing only the top-level items the relexed tokens
+/// touch.
+///
+/// The items before the edit are reused as they are, and those after it are
+/// reused with their spans moved. `lexed` is edited whether or not reparsing
+/// succeeds.
+///
+/// Returns the range of indices in `module.nodes` that were reparsed, or
+/// `None` if the items around the edit no longer parse on their own, in which
+/// case `module` is left untouched and the whole file should be reparsed,
+/// which also reports the errors.
+#[must_use]
+pub fn reparse(
+    module: &mut ASTModule,
+    file: FileId,
+    lexed: &mut LexedSource,
+    edit: &TextEdit,
+) -> Option<Range<usize>> {
+    let relexed = lexed.edit(edit);
+    let tokens = lexed.tokens();
+    let nodes = &module.nodes;
+
+    // The relexed tokens can reach past the edit, as when it opens a string
+    // or a comment, and everything they cover has changed.
+    let edited_end = edit.range.start.strict_add(edit.replacement.len());
+    let (changed_start, changed_end) = if relexed.is_empty() {
+        (edit.range.start, edited_end)
+    } else {
+        (
+            tokens[relexed.start].1.start.min(edit.range.start),
+            tokens[relexed.end.strict_sub(1)].1.end.max(edited_end),
+        )
+    };
+    // where the changed text ends in the source-text before the edit
+    let old_end = changed_end
+        .strict_sub(edited_end)
+        .strict_add(edit.range.end);
+
+    // Text added right after an item can extend it, and a change to the doc
+    // comments before an item changes the item, so the items touching the
+    // changed text are reparsed along with the first one after it.
+    let first = nodes
+        .iter()
+        .position(|node| node.span.end() >= changed_start)
+        .unwrap_or(nodes.len());
+    let last = nodes
+        .get(first..)
+        .unwrap_or_default()
+        .iter()
+        .position(|node| node.span.start() >= old_end)
+        .map_or(nodes.len(), |i| first.strict_add(i).strict_add(1));
+
+    // Every token between the items before and after the ones being reparsed
+    // is reparsed, so that none are lost.
+    let start = first
+        .checked_sub(1)
+        .and_then(|i| nodes.get(i))
+        .map_or(0, |node| node.span.end());
+    let end = nodes.get(last).map_or_else(
+        || lexed.source().len(),
+        |node| edit.shift(node.span.start()),
+    );
+    let indices = tokens.partition_point(|token| token.1.start < start)
+        ..tokens.partition_point(|token| token.1.start < end);
+
+    let to_parse = lexed.parser_tokens(indices).ok()?;
+    let reparsed = Parser::new(&module.path, file, &to_parse).parse().ok()?;
+    if start != 0 && reparsed.docs.is_some() {
+        // Module doc comments after an item are an error, which a full reparse
+        // reports.
+        return None;
+    }
+    if start == 0 {
+        module.docs = reparsed.docs;
+    }
+
+    let mut updated = mem::take(&mut module.nodes).into_vec();
+    let mut shift = Shift(edit.delta());
+    for node in updated.get_mut(last..).unwrap_or_default() {
+        shift.visit_node_mut(node);
+    }
+    let count = reparsed.nodes.len();
+    updated.splice(first..last, reparsed.nodes);
+    module.nodes = updated.into_boxed_slice();
+
+    Some(first..first.strict_add(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use amaic_lexer::tokenize;
+
+    use super::*;
+
+    /// Parses `source` from scratch.
+    fn parse(source: &str) -> ASTModule {
+        let tokens = tokenize(source).expect("the source should lex");
+        Parser::new("test.amai", FileId::default(), &tokens)
+            .parse()
+            .expect("the source should parse")
+    }
+
+    /// Replaces the first `old` in `source` with `new` and reparses, checking
+    /// that the module is the same as parsing the edited text from scratch,
+    /// and that the items outside the reparsed range are those from before.
+    fn edit(source: &str, old: &str, new: &str) -> Option<Range<usize>> {
+        let start = source.find(old).expect("the text should be in the source");
+        let edit = TextEdit {
+            range: start..start.strict_add(old.len()),
+            replacement: new.to_owned(),
+        };
+        let mut lexed = LexedSource::new(source.to_owned());
+        let before = parse(source);
+        let mut module = before.clone();
+
+        let reparsed = reparse(&mut module, FileId::default(), &mut lexed, &edit);
+        assert_eq!(lexed.source(), source.replacen(old, new, 1));
+        let Some(ref range) = reparsed else {
+            assert_eq!(
+                module.nodes, before.nodes,
+                "a failed reparse changed the module"
+            );
+            return None;
+        };
+        let expected = parse(lexed.source());
+        assert_eq!(module.docs, expected.docs);
+        assert_eq!(module.nodes, expected.nodes);
+
+        let after = module.nodes.len().strict_sub(range.end);
+        assert_eq!(module.nodes[..range.start], before.nodes[..range.start]);
+        for (reused, old_node) in module.nodes[range.end..]
+            .iter()
+            .zip(&before.nodes[before.nodes.len().strict_sub(after)..])
+        {
+            assert_eq!(reused.span, old_node.span.shift(edit.delta()));
+        }
+        reparsed
+    }
+
+    #[test]
+    fn reparses_an_edit_inside_a_token() {
+        let source = "let a = 1;\nlet answer = 2;\nlet c = 3;\nlet d = 4;\n";
+        // the edited item and the one after it
+        assert_eq!(edit(source, "sw", "xyz"), Some(1..3));
+        assert_eq!(edit(source, "4", "40"), Some(3..4));
+        assert_eq!(
+            edit("//! Docs.\nlet a = 1;\n", "Docs", "More docs"),
+            Some(0..1)
+        );
+    }
+
+    #[test]
+    fn reparses_an_edit_across_items() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = 4;\n";
+        assert_eq!(edit(source, "1;\nlet b = ", "1 + "), Some(0..2));
+        assert_eq!(
+            edit(source, "2;\nlet c = 3", "2;\nlet e = 5;\nlet c = 30"),
+            Some(1..5)
+        );
+        assert_eq!(edit(source, "2;\nlet c =", "2;\nlet c = ="), None);
+    }
+
+    #[test]
+    fn reparses_through_an_opened_comment() {
+        // the comment reaches past the edit, up to the `*/` after `d`
+        let source = "let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = 4; // */\nlet e = 5;\n";
+        assert_eq!(edit(source, "let b", "/* let b"), Some(1..2));
+        assert_eq!(edit(source, "let e", "/* let e"), None);
+
+        let commented = "let a = 1;\n/* let b = 2; */\nlet c = 3;\n";
+        assert_eq!(edit(commented, "/* let b = 2; */", "let b = 2;"), Some(1..3));
+    }
+
+    #[test]
+    fn reparses_an_opened_or_closed_string() {
+        let source = "let a = \"one\";\nlet b = 2;\nlet c = 3;\n";
+        assert_eq!(edit(source, "\"one\"", "\"one"), None);
+        assert_eq!(edit(source, "\"one\"", "\"one\" ++ \"two\""), Some(0..2));
+        assert_eq!(edit(source, "2", "\"2\""), Some(1..3));
+    }
+}
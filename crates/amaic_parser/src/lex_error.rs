@@ -0,0 +1,113 @@
+//! See [`lex_error`].
+
+use std::ops::Range;
+
+use amaic_core::{Applicability, Diagnostic, FileId, Span, error_codes};
+use amaic_lexer::{LexError, confusable};
+
+/// Converts an error from lexing into a diagnostic, preferring the span the
+/// error carries over the span of the token it occurred in.
+#[must_use]
+pub fn lex_error(file: FileId, err: &LexError, range: Range<usize>) -> Diagnostic {
+    let span = Span::from_range(file, err.span().unwrap_or(range));
+    let diagnostic = Diagnostic::new(err.to_string(), span);
+    match *err {
+        LexError::UnexpectedChar { found, .. } => {
+            let diagnostic = diagnostic.with_code(error_codes::E0018);
+            match confusable(found) {
+                Some(ascii) => diagnostic
+                    .with_primary_label(format!("this looks like {ascii:?}, but isn't"))
+                    .with_suggestion(
+                        format!("replace it with {ascii:?}"),
+                        span,
+                        ascii,
+                        Applicability::MaybeIncorrect,
+                    ),
+                None => diagnostic.with_primary_label("this character can't be used here"),
+            }
+        }
+        LexError::UnterminatedString { .. } => diagnostic
+            .with_code(error_codes::E0019)
+            .with_primary_label("this string is never closed")
+            .with_help("to write a quote inside a string, escape it with `\\\"`"),
+        LexError::UnknownEscape { .. } => diagnostic
+            .with_code(error_codes::E0013)
+            .with_primary_label("unknown escape-sequence")
+            .with_help("to write a backslash on its own, escape it with `\\\\`"),
+        LexError::InvalidUnicodeEscape { .. } => diagnostic
+            .with_code(error_codes::E0013)
+            .with_primary_label("expected one to six hexadecimal digits, as in `\\u{1F600}`"),
+        LexError::InvalidCodepoint { .. } => diagnostic
+            .with_code(error_codes::E0013)
+            .with_primary_label("not a unicode scalar value")
+            .with_note("surrogates and values above `10FFFF` aren't allowed"),
+        LexError::UnterminatedComment { .. } => diagnostic
+            .with_code(error_codes::E0020)
+            .with_primary_label("this comment is never closed")
+            .with_note("block comments nest, so each `/*` needs its own `*/`"),
+        LexError::InvalidDigit { radix, .. } => diagnostic
+            .with_code(error_codes::E0016)
+            .with_primary_label(format!("not a base {radix} digit")),
+        LexError::MissingDigits { .. } => diagnostic
+            .with_code(error_codes::E0016)
+            .with_primary_label("expected digits after the prefix"),
+        LexError::IntegerOverflow { .. } => diagnostic
+            .with_code(error_codes::E0016)
+            .with_primary_label("doesn't fit in an `int`")
+            .with_note(format!(
+                "`int` ranges from `{}` to `{}`",
+                i64::MIN,
+                i64::MAX
+            )),
+        LexError::MissingExponent { .. } => diagnostic
+            .with_code(error_codes::E0016)
+            .with_primary_label("expected digits after the exponent"),
+        LexError::UnterminatedInterpolation { .. } => diagnostic
+            .with_code(error_codes::E0014)
+            .with_primary_label("this `{` is never closed")
+            .with_help("to write a brace on its own, escape it with `\\{`"),
+        LexError::EmptyInterpolation { .. } => diagnostic
+            .with_code(error_codes::E0014)
+            .with_primary_label("expected an expression between the braces"),
+        LexError::UnmatchedBrace { .. } => diagnostic
+            .with_code(error_codes::E0014)
+            .with_primary_label("this `}` doesn't close an interpolation")
+            .with_help("to write a brace on its own, escape it with `\\}`"),
+        _ => diagnostic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amaic_core::SourceMap;
+    use amaic_lexer::tokenize;
+
+    use super::*;
+
+    #[test]
+    fn every_lex_error_has_a_code() {
+        for source in [
+            "$",
+            "\"abc",
+            r#""\q""#,
+            r#""\u{}""#,
+            r#""\u{D800}""#,
+            r#""{1""#,
+            r#""{}""#,
+            r#""}""#,
+            "0o8",
+            "0x",
+            "99999999999999999999",
+            "1e",
+            "/* abc",
+            "\u{2212}",
+        ] {
+            let file = SourceMap::default().add("test.amai", source);
+            let errors = tokenize(source).expect_err(source);
+            for (err, range) in errors {
+                let diagnostic = lex_error(file, &err, range);
+                assert!(diagnostic.code.is_some(), "{err:?} in {source:?}");
+            }
+        }
+    }
+}
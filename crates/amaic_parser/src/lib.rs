@@ -1,98 +1,225 @@
-use std::path::{Path, PathBuf};
-
-use amaic_ast::*;
-use amaic_core::{Diagnostic, Span};
-use amaic_lexer::{Operator, token::*};
-
-pub struct Parser<'p> {
+//! The parser, which turns tokens into an [`ASTModule`].
+
+mod incremental;
+mod lex_error;
+
+use std::{
+    mem,
+    path::{Path, PathBuf},
+};
+
+use amaic_ast::{
+    ASTModule, ASTNode, ASTNodeType, FrontendType, FrontendTypeType, InterpolationPart,
+};
+use amaic_core::{Diagnostic, FileId, Span, error_codes};
+use amaic_lexer::{
+    Operator, StringPart, Token, TokenKind, doc_text, normalize_ident, parse_float, parse_int,
+    string_parts, tokenize,
+};
+
+pub use incremental::reparse;
+pub use lex_error::lex_error;
+
+/// The keywords the grammar uses, which are those worth suggesting. The rest of
+/// [`TokenKind::KEYWORDS`] are only reserved.
+pub const KEYWORDS: [TokenKind; 10] = [
+    TokenKind::Let,
+    TokenKind::If,
+    TokenKind::Then,
+    TokenKind::Else,
+    TokenKind::While,
+    TokenKind::Do,
+    TokenKind::True,
+    TokenKind::False,
+    TokenKind::And,
+    TokenKind::Or,
+];
+
+/// A recursive-descent parser over a file's tokens.
+pub struct Parser<'src> {
+    /// The errors found in statements that parsing resumed after.
+    diagnostics: Vec<Diagnostic>,
+
+    /// The file the tokens were lexed from.
+    file: FileId,
+
+    /// The path of the file being parsed.
     path: PathBuf,
-    tokens: &'p Box<[Token<'p>]>,
+
+    /// The index of the next token.
     pos: usize,
+
+    /// The tokens of the file.
+    tokens: &'src [Token<'src>],
 }
 
-impl<'p> Parser<'p> {
-    pub fn new<P: AsRef<Path>>(path: P, tokens: &'p Box<[Token]>) -> Parser<'p> {
-        Parser {
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "Methods are ordered from the top of the grammar down."
+)]
+impl<'src> Parser<'src> {
+    /// Creates a parser over the `tokens` of `file`, which is at `path`.
+    pub fn new<P: AsRef<Path>>(path: P, file: FileId, tokens: &'src [Token<'src>]) -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            file,
             path: path.as_ref().to_path_buf(),
-            tokens,
             pos: 0,
+            tokens,
         }
     }
 
+    /// The span of `token`.
+    fn span(&self, token: &Token) -> Span {
+        Span::from_range(self.file, token.span.clone())
+    }
+
+    /// The span that errors at the end of input point at, which is that of the
+    /// last token.
+    fn end_span(&self) -> Span {
+        self.tokens
+            .last()
+            .map_or_else(|| Span::new(self.file, 0, 0), |token| self.span(token))
+    }
+
+    /// Parses the whole file.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the syntax errors found, if any.
     pub fn parse(&mut self) -> Result<ASTModule, Vec<Diagnostic>> {
         let mut module = Vec::new();
+        let docs = self.parse_docs(TokenKind::ModuleDocComment);
         if self.tokens.is_empty() {
             return Ok(ASTModule {
+                docs,
                 path: self.path.clone(),
                 nodes: module.into_boxed_slice(),
             });
         }
-        let mut diagnostics = Vec::new();
-
         while self.tokens.get(self.pos).is_some() {
-            let stmt = self.parse_stmt();
-            match stmt {
+            let start = self.pos;
+            match self.parse_stmt() {
                 Ok(node) => module.push(node),
                 Err(err) => {
-                    diagnostics.push(err);
-                    while let Some(token) = self.tokens.get(self.pos) {
-                        // syncronize
-                        if token.ty == TokenType::Semicolon
-                            || token.ty == TokenType::RCurly
-                            || token.ty == TokenType::RParen
-                            || token.ty == TokenType::RSquare
-                            || token.ty == TokenType::LCurly
-                        {
-                            break;
-                        }
-                        self.pos += 1;
+                    self.diagnostics.push(err);
+                    self.synchronize(start);
+                    if self.pos == start {
+                        // a stray closing delimiter, which no statement can start with
+                        self.pos = self.pos.strict_add(1);
                     }
-                    break;
                 }
             }
         }
 
-        if !diagnostics.is_empty() {
+        if !self.diagnostics.is_empty() {
+            let mut diagnostics = mem::take(&mut self.diagnostics);
+            diagnostics.sort_by_key(|diag| (diag.primary_span.start(), diag.primary_span.end()));
             return Err(diagnostics);
         }
         Ok(ASTModule {
+            docs,
             path: self.path.clone(),
             nodes: module.into_boxed_slice(),
         })
     }
 
+    /// Consumes a run of doc comments of the given kind, returning their text
+    /// joined by newlines.
+    fn parse_docs(&mut self, kind: TokenKind) -> Option<String> {
+        let mut lines = Vec::new();
+        while let Some(token) = self.tokens.get(self.pos).filter(|token| token.kind == kind) {
+            lines.push(doc_text(token.slice));
+            self.pos = self.pos.strict_add(1);
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Parses a declaration along with the `///` doc comments before it.
+    fn parse_documented(&mut self) -> Result<ASTNode, Diagnostic> {
+        let start = self.span(&self.tokens[self.pos]);
+        let docs = self.parse_docs(TokenKind::DocComment);
+        let docs_span = start.merge(&self.span(&self.tokens[self.pos.strict_sub(1)]));
+
+        if self
+            .tokens
+            .get(self.pos)
+            .is_none_or(|token| token.kind != TokenKind::Let)
+        {
+            return Err(
+                Diagnostic::new("Expected a declaration after doc comment", docs_span)
+                    .with_code(error_codes::E0017)
+                    .with_primary_label("this doc comment doesn't document anything")
+                    .with_help("use `//` for a regular comment"),
+            );
+        }
+
+        let mut node = self.parse_let()?;
+        if let ASTNodeType::LetDecl {
+            docs: ref mut node_docs,
+            ..
+        }
+        | ASTNodeType::FunDef {
+            docs: ref mut node_docs,
+            ..
+        } = node.ty
+        {
+            *node_docs = docs;
+        }
+        Ok(node)
+    }
+
+    /// Skips tokens until the end of the statement that started at `start`, so
+    /// that parsing can resume after an error. A `;` is consumed, while a `}` or
+    /// the start of a new statement is left for the caller.
+    fn synchronize(&mut self, start: usize) {
+        while let Some(token) = self.tokens.get(self.pos) {
+            match token.kind {
+                TokenKind::Semicolon => {
+                    self.pos = self.pos.strict_add(1);
+                    break;
+                }
+                TokenKind::ClosedBrace => break,
+                TokenKind::Let | TokenKind::If | TokenKind::While if self.pos > start => break,
+                _ => self.pos = self.pos.strict_add(1),
+            }
+        }
+    }
+
+    /// Parses an expression, along with the `;` after it if any.
     fn parse_stmt(&mut self) -> Result<ASTNode, Diagnostic> {
         let mut node = self.parse_expr(0)?;
 
         let mut advance = false;
-        if let Some(Token {
-            ty: TokenType::Semicolon,
-            span,
-            ..
-        }) = self.tokens.get(self.pos)
+        if let Some(semi) = self
+            .tokens
+            .get(self.pos)
+            .filter(|token| token.kind == TokenKind::Semicolon)
         {
             advance = true;
-            let span = node.span.merge(span);
+            let span = node.span.merge(&self.span(semi));
             node = ASTNode {
                 ty: ASTNodeType::Semi(Box::new(node)),
-                span: span.into(),
+                span,
             };
         }
 
         if advance {
-            self.pos += 1
+            self.pos = self.pos.strict_add(1);
         }
 
         Ok(node)
     }
 
+    /// Parses an expression whose infix operators bind at least as tightly as
+    /// `min_bp`.
     fn parse_expr(&mut self, min_bp: u32) -> Result<ASTNode, Diagnostic> {
         let mut lhs = self.parse_primary()?;
 
-        while let Some(Token {
-            ty: TokenType::Operator(op),
-            ..
-        }) = self.tokens.get(self.pos).cloned()
+        while let Some(op) = self
+            .tokens
+            .get(self.pos)
+            .and_then(|token| Operator::from_token(token.kind))
         {
             if !op.is_infix() {
                 break;
@@ -101,7 +228,7 @@ impl<'p> Parser<'p> {
             if lbp < min_bp {
                 break;
             }
-            self.pos += 1;
+            self.pos = self.pos.strict_add(1);
 
             let rhs = self.parse_expr(rbp)?;
 
@@ -113,154 +240,281 @@ impl<'p> Parser<'p> {
                     rhs: Box::new(rhs),
                     op_tys: None,
                 },
-                span: span.into(),
+                span,
             };
         }
 
         Ok(lhs)
     }
 
+    /// Parses an expression interpolated into a string literal, whose source
+    /// starts at the byte-offset `offset` of the file.
+    fn parse_interpolation(&self, source: &str, offset: usize) -> Result<ASTNode, Diagnostic> {
+        let mut tokens = tokenize(source).map_err(|errors| {
+            let (mut err, range) = errors
+                .into_iter()
+                .next()
+                .expect("lexing only fails with errors");
+            err.shift(offset.cast_signed());
+            lex_error(
+                self.file,
+                &err,
+                offset.strict_add(range.start)..offset.strict_add(range.end),
+            )
+        })?;
+        if tokens.is_empty() {
+            return Err(Diagnostic::new(
+                "Expected expression, found end of interpolation",
+                Span::new(self.file, offset, offset.strict_add(source.len())),
+            )
+            .with_code(error_codes::E0014)
+            .with_primary_label("expected an expression between the braces"));
+        }
+        for token in &mut tokens {
+            token.span = offset.strict_add(token.span.start)..offset.strict_add(token.span.end);
+        }
+
+        let mut parser = Parser::new(&self.path, self.file, &tokens);
+        let expr = parser.parse_expr(0)?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(Diagnostic::new(
+                format!("Expected `}}`, found `{}`", token.slice),
+                parser.span(token),
+            )
+            .with_code(error_codes::E0001)
+            .with_primary_label("only a single expression can be interpolated"));
+        }
+        Ok(expr)
+    }
+
+    /// Folds a `-` directly followed by a numeric literal into a negative
+    /// literal, which allows `i64::MIN` to be written. Doesn't consume anything
+    /// if the `-` isn't followed by a literal.
+    fn parse_negative_literal(&mut self, minus: &Token) -> Option<ASTNode> {
+        let token = self.tokens.get(self.pos)?;
+        let ty = match token.kind {
+            TokenKind::Int => ASTNodeType::IntLit(
+                0_i64.wrapping_sub_unsigned(
+                    parse_int(token.slice, token.span.start)
+                        .expect("integer literals are validated while lexing"),
+                ),
+            ),
+            TokenKind::Float => ASTNodeType::FloatLit(
+                -parse_float(token.slice, token.span.start)
+                    .expect("float literals are validated while lexing"),
+            ),
+            _ => return None,
+        };
+        let span = self.span(minus).merge(&self.span(token));
+        self.pos = self.pos.strict_add(1);
+        Some(ASTNode { ty, span })
+    }
+
+    /// Parses a literal, a prefix operation or a keyword-led expression.
+    #[expect(
+        clippy::too_many_lines,
+        reason = "Each kind of primary expression is handled in a single arm."
+    )]
     fn parse_primary(&mut self) -> Result<ASTNode, Diagnostic> {
-        let token = if let Some(token) = self.tokens.get(self.pos).cloned() {
-            token
-        } else {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
             return Err(Diagnostic::new(
-                self.path.display(),
                 "Expected expression, found end of input",
-                self.tokens.last().unwrap().span.clone(),
-            ));
+                self.end_span(),
+            )
+            .with_code(error_codes::E0001));
         };
+        let span = self.span(&token);
+
+        if let Some(op) = Operator::from_token(token.kind).filter(|op| op.is_prefix()) {
+            self.pos = self.pos.strict_add(1);
+            if op == Operator::Minus
+                && let Some(literal) = self.parse_negative_literal(&token)
+            {
+                return Ok(literal);
+            }
+            let operand = self.parse_primary()?;
+            let span = span.merge(&operand.span);
+            return Ok(ASTNode {
+                ty: ASTNodeType::UnaryOp {
+                    op,
+                    operand: Box::new(operand),
+                    op_ty: None,
+                },
+                span,
+            });
+        }
 
-        match token.ty {
-            TokenType::IntLit => {
-                self.pos += 1;
+        match token.kind {
+            TokenKind::Int => {
+                self.pos = self.pos.strict_add(1);
+                let magnitude = parse_int(token.slice, token.span.start)
+                    .expect("integer literals are validated while lexing");
+                let Ok(int) = i64::try_from(magnitude) else {
+                    return Err(
+                        Diagnostic::new("Integer literal is out of range for `int`", span)
+                            .with_code(error_codes::E0016)
+                            .with_primary_label("doesn't fit in an `int`")
+                            .with_note(format!(
+                                "`int` ranges from `{}` to `{}`",
+                                i64::MIN,
+                                i64::MAX
+                            )),
+                    );
+                };
                 Ok(ASTNode {
-                    ty: ASTNodeType::IntLit(unsafe { token.lit.unwrap().int_num }),
-                    span: token.span,
+                    ty: ASTNodeType::IntLit(int),
+                    span,
                 })
             }
-            TokenType::FloatLit => {
-                self.pos += 1;
+            TokenKind::Float => {
+                self.pos = self.pos.strict_add(1);
                 Ok(ASTNode {
-                    ty: ASTNodeType::FloatLit(unsafe { token.lit.unwrap().float_num }),
-                    span: token.span,
+                    ty: ASTNodeType::FloatLit(
+                        parse_float(token.slice, token.span.start)
+                            .expect("float literals are validated while lexing"),
+                    ),
+                    span,
                 })
             }
-            TokenType::StringLit => {
-                self.pos += 1;
+            TokenKind::String => {
+                self.pos = self.pos.strict_add(1);
+                let mut parts = string_parts(token.slice, token.span.start)
+                    .expect("string literals are validated while lexing");
+                if let [StringPart::Text(ref mut text)] = *parts.as_mut_slice() {
+                    return Ok(ASTNode {
+                        ty: ASTNodeType::StringLit(mem::take(text)),
+                        span,
+                    });
+                }
+
+                let mut interpolation = Vec::with_capacity(parts.len());
+                for part in parts {
+                    interpolation.push(match part {
+                        StringPart::Text(text) => InterpolationPart::Text(text),
+                        StringPart::Interpolation {
+                            source,
+                            span: source_span,
+                        } => InterpolationPart::Expr {
+                            expr: self.parse_interpolation(source, source_span.start)?,
+                            ty: None,
+                        },
+                    });
+                }
                 Ok(ASTNode {
-                    ty: ASTNodeType::StringLit(token.lex.to_string()),
-                    span: token.span,
+                    ty: ASTNodeType::Interpolation(interpolation),
+                    span,
                 })
             }
-            TokenType::True => {
-                self.pos += 1;
+            TokenKind::True => {
+                self.pos = self.pos.strict_add(1);
                 Ok(ASTNode {
                     ty: ASTNodeType::Boolean(true),
-                    span: token.span,
+                    span,
                 })
             }
-            TokenType::False => {
-                self.pos += 1;
+            TokenKind::False => {
+                self.pos = self.pos.strict_add(1);
                 Ok(ASTNode {
                     ty: ASTNodeType::Boolean(false),
-                    span: token.span,
+                    span,
                 })
             }
-            TokenType::Identifier => {
-                self.pos += 1;
-                if let Some(Token {
-                    ty: TokenType::LParen,
-                    ..
-                }) = self.tokens.get(self.pos)
+            TokenKind::Identifier => {
+                self.pos = self.pos.strict_add(1);
+                if self
+                    .tokens
+                    .get(self.pos)
+                    .is_some_and(|tok| tok.kind == TokenKind::OpenParen)
                 {
-                    self.pos += 1;
-                    let mut span = token.span;
+                    self.pos = self.pos.strict_add(1);
                     let mut args = Vec::new();
                     while let Some(tok) = self.tokens.get(self.pos) {
-                        if tok.ty == TokenType::RParen {
+                        if tok.kind == TokenKind::ClosedParen {
                             break;
                         }
                         let node = self.parse_expr(0)?;
                         args.push(node);
-                        if self.expect(TokenType::Comma).is_err() {
+                        if self.expect(TokenKind::Comma).is_err() {
                             break;
                         }
                     }
-                    let s = self.expect(TokenType::RParen)?;
-                    span = span.merge(&s.span);
+                    let close = self.expect(TokenKind::ClosedParen)?;
 
                     Ok(ASTNode {
                         ty: ASTNodeType::FunCall {
-                            callee: token.lex.to_string(),
+                            callee: normalize_ident(token.slice).into_owned(),
                             args,
                         },
-                        span,
+                        span: span.merge(&self.span(&close)),
                     })
                 } else {
                     Ok(ASTNode {
-                        ty: ASTNodeType::Identifier(token.lex.to_string()),
-                        span: token.span,
+                        ty: ASTNodeType::Identifier(normalize_ident(token.slice).into_owned()),
+                        span,
                     })
                 }
             }
-            TokenType::Operator(op) if op.is_prefix() => {
-                self.pos += 1;
-                let operand = self.parse_primary()?;
-                let span = token.span.merge(&operand.span);
-                Ok(ASTNode {
-                    ty: ASTNodeType::UnaryOp {
-                        op,
-                        operand: Box::new(operand),
-                        op_ty: None,
-                    },
-                    span: span.into(),
-                })
-            }
-            TokenType::LParen => {
-                self.pos += 1;
-                if let Some(Token {
-                    ty: TokenType::RParen,
-                    span,
-                    ..
-                }) = self.tokens.get(self.pos)
+            TokenKind::OpenParen => {
+                self.pos = self.pos.strict_add(1);
+                if let Some(close) = self
+                    .tokens
+                    .get(self.pos)
+                    .filter(|tok| tok.kind == TokenKind::ClosedParen)
                 {
-                    let span = token.span.merge(span);
+                    let span = span.merge(&self.span(close));
+                    self.pos = self.pos.strict_add(1);
                     Ok(ASTNode {
                         ty: ASTNodeType::Unit,
-                        span: span.into(),
+                        span,
                     })
                 } else {
-                    Ok(self.parse_expr(0)?)
+                    let expr = self.parse_expr(0)?;
+                    self.expect(TokenKind::ClosedParen)?;
+                    Ok(expr)
                 }
             }
-            TokenType::LCurly => self.parse_block(),
-            TokenType::Let => self.parse_let(),
-            TokenType::If => self.parse_if(),
-            TokenType::While => self.parse_while(),
-            _ => Err(Diagnostic::new(
-                self.path.display(),
-                format!("Expected expression, found {}", token.err_str()),
-                token.span,
-            )),
+            TokenKind::OpenBrace => self.parse_block(),
+            TokenKind::Let => self.parse_let(),
+            TokenKind::DocComment => self.parse_documented(),
+            TokenKind::ModuleDocComment => Err(Diagnostic::new(
+                "Module doc comments must come before anything else in a module",
+                span,
+            )
+            .with_code(error_codes::E0017)
+            .with_primary_label("this doc comment isn't at the start of the module")
+            .with_help("use `///` to document the declaration after it")),
+            TokenKind::If => self.parse_if(),
+            TokenKind::While => self.parse_while(),
+            _ => Err(
+                Diagnostic::new(format!("Expected expression, found {}", token.kind), span)
+                    .with_code(error_codes::E0001),
+            ),
         }
     }
 
+    /// Parses a `{ ... }` block.
     fn parse_block(&mut self) -> Result<ASTNode, Diagnostic> {
-        let mut stmt_span = self.tokens.get(self.pos).unwrap().span.clone();
-        self.pos += 1;
+        let mut stmt_span = self.span(&self.tokens[self.pos]);
+        self.pos = self.pos.strict_add(1);
 
         let mut stmts = Vec::new();
         while let Some(token) = self.tokens.get(self.pos) {
-            if token.ty == TokenType::RCurly {
+            if token.kind == TokenKind::ClosedBrace {
                 break;
             }
 
-            let stmt = self.parse_stmt()?;
-            stmts.push(stmt);
+            let start = self.pos;
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    self.diagnostics.push(err);
+                    self.synchronize(start);
+                }
+            }
         }
 
-        stmt_span = stmt_span.merge(&self.expect(TokenType::RCurly)?.span);
+        let closed_brace = self.expect(TokenKind::ClosedBrace)?;
+        stmt_span = stmt_span.merge(&self.span(&closed_brace));
 
         Ok(ASTNode {
             ty: ASTNodeType::Block(stmts),
@@ -268,43 +522,43 @@ impl<'p> Parser<'p> {
         })
     }
 
+    /// Parses a variable declaration or function definition.
     fn parse_let(&mut self) -> Result<ASTNode, Diagnostic> {
-        let mut stmt_span = self.tokens.get(self.pos).unwrap().span.clone();
-        self.pos += 1;
+        let mut stmt_span = self.span(&self.tokens[self.pos]);
+        self.pos = self.pos.strict_add(1);
 
-        let ident = self.expect(TokenType::Identifier)?;
-        let name = ident.lex.to_string();
-        stmt_span = stmt_span.merge(&ident.span);
+        let ident = self.expect(TokenKind::Identifier)?;
+        let name = normalize_ident(ident.slice).into_owned();
+        stmt_span = stmt_span.merge(&self.span(&ident));
 
-        if self.expect(TokenType::LParen).is_ok() {
+        if self.expect(TokenKind::OpenParen).is_ok() {
             let mut params = Vec::new();
             while let Some(tok) = self.tokens.get(self.pos) {
-                if tok.ty == TokenType::RParen {
+                if tok.kind == TokenKind::ClosedParen {
                     break;
                 }
-                let ident = self.expect(TokenType::Identifier)?;
-                let mut param_span = ident.span;
-                let name = ident.lex.to_string();
-                self.expect(TokenType::Colon)?;
+                let param = self.expect(TokenKind::Identifier)?;
+                self.expect(TokenKind::Colon)?;
                 let ty = self.parse_type()?;
-                param_span = param_span.merge(&ty.span);
-                params.push((name, ty, param_span));
-                if self.expect(TokenType::Comma).is_err() {
+                let param_span = self.span(&param).merge(&ty.span);
+                params.push((normalize_ident(param.slice).into_owned(), ty, param_span));
+                if self.expect(TokenKind::Comma).is_err() {
                     break;
                 }
             }
-            self.expect(TokenType::RParen)?;
-            let return_ty = if self.expect(TokenType::Colon).is_ok() {
+            self.expect(TokenKind::ClosedParen)?;
+            let return_ty = if self.expect(TokenKind::Colon).is_ok() {
                 Some(self.parse_type()?)
             } else {
                 None
             };
-            self.expect(TokenType::Operator(Operator::Assign))?;
+            self.expect(TokenKind::Equal)?;
             let body = self.parse_expr(0)?;
             stmt_span = stmt_span.merge(&body.span);
 
             return Ok(ASTNode {
                 ty: ASTNodeType::FunDef {
+                    docs: None,
                     name,
                     params,
                     return_ty,
@@ -314,7 +568,7 @@ impl<'p> Parser<'p> {
             });
         }
 
-        let ty = if self.expect(TokenType::Colon).is_ok() {
+        let ty = if self.expect(TokenKind::Colon).is_ok() {
             let expr = self.parse_type()?;
             stmt_span = stmt_span.merge(&expr.span);
             Some(expr)
@@ -322,7 +576,7 @@ impl<'p> Parser<'p> {
             None
         };
 
-        let init = if self.expect(TokenType::Operator(Operator::Assign)).is_ok() {
+        let init = if self.expect(TokenKind::Equal).is_ok() {
             let expr = self.parse_expr(0)?;
             stmt_span = stmt_span.merge(&expr.span);
             Some(expr)
@@ -332,25 +586,27 @@ impl<'p> Parser<'p> {
 
         Ok(ASTNode {
             ty: ASTNodeType::LetDecl {
+                docs: None,
                 name,
                 ty,
-                init: init.map(|expr| Box::new(expr)),
+                init: init.map(Box::new),
             },
             span: stmt_span,
         })
     }
 
+    /// Parses an `if ... then ... else ...` expression.
     fn parse_if(&mut self) -> Result<ASTNode, Diagnostic> {
-        let mut stmt_span = self.tokens.get(self.pos).unwrap().span.clone();
-        self.pos += 1;
+        let mut stmt_span = self.span(&self.tokens[self.pos]);
+        self.pos = self.pos.strict_add(1);
 
         let condition = self.parse_expr(0)?;
-        self.expect(TokenType::Then)?;
+        self.expect(TokenKind::Then)?;
 
         let then_body = self.parse_expr(0)?;
         stmt_span = stmt_span.merge(&then_body.span);
 
-        let else_body = if self.expect(TokenType::Else).is_ok() {
+        let else_body = if self.expect(TokenKind::Else).is_ok() {
             let expr = self.parse_expr(0)?;
             stmt_span = stmt_span.merge(&expr.span);
             Some(expr)
@@ -362,18 +618,19 @@ impl<'p> Parser<'p> {
             ty: ASTNodeType::If {
                 condition: Box::new(condition),
                 then_body: Box::new(then_body),
-                else_body: else_body.map(|expr| Box::new(expr)),
+                else_body: else_body.map(Box::new),
             },
             span: stmt_span,
         })
     }
 
+    /// Parses a `while ... do ...` loop.
     fn parse_while(&mut self) -> Result<ASTNode, Diagnostic> {
-        let mut stmt_span = self.tokens.get(self.pos).unwrap().span.clone();
-        self.pos += 1;
+        let mut stmt_span = self.span(&self.tokens[self.pos]);
+        self.pos = self.pos.strict_add(1);
 
         let condition = self.parse_expr(0)?;
-        self.expect(TokenType::Do)?;
+        self.expect(TokenKind::Do)?;
 
         let body = self.parse_expr(0)?;
         stmt_span = stmt_span.merge(&body.span);
@@ -387,56 +644,56 @@ impl<'p> Parser<'p> {
         })
     }
 
+    /// Parses a type.
     fn parse_type(&mut self) -> Result<FrontendType, Diagnostic> {
-        let token = if let Some(token) = self.tokens.get(self.pos).cloned() {
-            token
-        } else {
-            return Err(Diagnostic::new(
-                self.path.display(),
-                "Expected type, found end of input",
-                self.tokens.last().unwrap().span.clone(),
-            ));
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err(
+                Diagnostic::new("Expected type, found end of input", self.end_span())
+                    .with_code(error_codes::E0001),
+            );
         };
+        let span = self.span(&token);
 
-        match token.ty {
-            TokenType::Identifier => {
-                self.pos += 1;
+        match token.kind {
+            TokenKind::Identifier => {
+                self.pos = self.pos.strict_add(1);
                 Ok(FrontendType {
-                    ty: FrontendTypeType::Identifier(token.lex.to_string()),
-                    span: token.span,
+                    ty: FrontendTypeType::Identifier(normalize_ident(token.slice).into_owned()),
+                    span,
                 })
             }
-            TokenType::LParen => {
-                self.pos += 1;
-                if let Some(Token {
-                    ty: TokenType::RParen,
-                    span,
-                    ..
-                }) = self.tokens.get(self.pos)
+            TokenKind::OpenParen => {
+                self.pos = self.pos.strict_add(1);
+                if let Some(close) = self
+                    .tokens
+                    .get(self.pos)
+                    .filter(|tok| tok.kind == TokenKind::ClosedParen)
                 {
-                    let span = token.span.merge(span);
+                    let span = span.merge(&self.span(close));
+                    self.pos = self.pos.strict_add(1);
                     Ok(FrontendType {
                         ty: FrontendTypeType::Unit,
-                        span: span.into(),
+                        span,
                     })
                 } else {
-                    Ok(self.parse_type()?)
+                    let ty = self.parse_type()?;
+                    self.expect(TokenKind::ClosedParen)?;
+                    Ok(ty)
                 }
             }
-            TokenType::LSquare => {
-                self.pos += 1;
+            TokenKind::OpenBrack => {
+                self.pos = self.pos.strict_add(1);
                 let inner_ty = self.parse_type()?;
-                self.expect(TokenType::RSquare)?;
+                self.expect(TokenKind::ClosedBrack)?;
                 Ok(FrontendType {
                     ty: FrontendTypeType::Vector(Box::new(inner_ty)),
-                    span: token.span,
+                    span,
                 })
             }
-            _ => Err(Diagnostic::new(
-                self.path.display(),
-                format!("Expected type, found {}", token.err_str()),
-                self.tokens.last().unwrap().span.clone(),
-            )),
+            _ => Err(
+                Diagnostic::new(format!("Expected type, found {}", token.kind), span)
+                    .with_code(error_codes::E0001),
+            ),
         }
     }
 
@@ -449,43 +706,43 @@ impl<'p> Parser<'p> {
                 &self.path,
                 "Expected pattern, found end of input",
                 self.tokens.last().unwrap().span.clone()
-            ));
+            );
         };
 
-        match token.ty {
-            TokenType::IntLit => {
-                self.pos += 1;
+        match token.kind {
+            TokenKind::Int => {
+                self.pos = self.pos.strict_add(1);
                 Ok(Pattern {
                     ty: PatternType::Literal(PatternLiteral::Integer( unsafe { token.lit.unwrap().int_num } )),
-                    span: token.span,
+                    span,
                 })
             },
-            TokenType::FloatLit => {
-                self.pos += 1;
+            TokenKind::Float => {
+                self.pos = self.pos.strict_add(1);
                 Ok(Pattern {
                     ty: PatternType::Literal(PatternLiteral::Float( unsafe { token.lit.unwrap().float_num } )),
-                    span: token.span,
+                    span,
                 })
             },
-            TokenType::True => {
-                self.pos += 1;
+            TokenKind::True => {
+                self.pos = self.pos.strict_add(1);
                 Ok(Pattern {
                     ty: PatternType::Literal(PatternLiteral::Boolean(true)),
-                    span: token.span,
+                    span,
                 })
             },
-            TokenType::False => {
-                self.pos += 1;
+            TokenKind::False => {
+                self.pos = self.pos.strict_add(1);
                 Ok(Pattern {
                     ty: PatternType::Literal(PatternLiteral::Boolean(false)),
-                    span: token.span,
+                    span,
                 })
             },
-            TokenType::Identifier => {
-                self.pos += 1;
+            TokenKind::Identifier => {
+                self.pos = self.pos.strict_add(1);
                 Ok(Pattern {
-                    ty: PatternType::Identifier(token.lex.to_string()),
-                    span: token.span,
+                    ty: PatternType::Identifier(token.slice.to_owned()),
+                    span,
                 })
             },
             _ => Err(Diagnostic::new(
@@ -496,24 +753,215 @@ impl<'p> Parser<'p> {
         }
     }*/
 
-    fn expect(&mut self, expected: TokenType) -> Result<Token<'p>, Diagnostic> {
+    /// Consumes the next token if it's of the `expected` kind, and errors
+    /// otherwise.
+    fn expect(&mut self, expected: TokenKind) -> Result<Token<'src>, Diagnostic> {
         if let Some(token) = self.tokens.get(self.pos).cloned() {
-            if token.ty == expected {
-                self.pos += 1;
+            if token.kind == expected {
+                self.pos = self.pos.strict_add(1);
                 Ok(token)
             } else {
                 Err(Diagnostic::new(
-                    self.path.display(),
-                    format!("Expected {}, found {}", expected.err_str(), token.err_str()),
-                    token.span,
-                ))
+                    format!("Expected {}, found {}", expected, token.kind),
+                    self.span(&token),
+                )
+                .with_code(error_codes::E0001))
             }
         } else {
             Err(Diagnostic::new(
-                self.path.display(),
-                format!("Expected {}, found end of input", expected.err_str()),
-                self.tokens.last().unwrap().span.clone(),
-            ))
+                format!("Expected {expected}, found end of input"),
+                self.end_span(),
+            )
+            .with_code(error_codes::E0001))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amaic_lexer::tokenize;
+
+    use super::*;
+
+    fn parse(source: &str) -> Result<ASTModule, Vec<Diagnostic>> {
+        let tokens = tokenize(source).expect("the source should lex");
+        Parser::new("test.amai", FileId::default(), &tokens).parse()
+    }
+
+    /// Parses a single expression into an S-expression, which shows how it's
+    /// grouped.
+    fn sexpr(source: &str) -> String {
+        /// Writes a node and its children as an S-expression.
+        #[expect(clippy::panic, reason = "Tests only parse these kinds of node.")]
+        fn write(node: &ASTNode) -> String {
+            match node.ty {
+                ASTNodeType::BinaryOp {
+                    op,
+                    ref lhs,
+                    ref rhs,
+                    ..
+                } => format!("({op} {} {})", write(lhs), write(rhs)),
+                ASTNodeType::FloatLit(float) => format!("{float:?}"),
+                ASTNodeType::FunCall {
+                    ref args,
+                    ref callee,
+                } => {
+                    let args: Vec<_> = args.iter().map(write).collect();
+                    format!("{callee}({})", args.join(", "))
+                }
+                ASTNodeType::Identifier(ref name) => name.clone(),
+                ASTNodeType::IntLit(int) => int.to_string(),
+                ASTNodeType::UnaryOp {
+                    op, ref operand, ..
+                } => format!("({op} {})", write(operand)),
+                ref ty => panic!("unexpected {ty:?}"),
+            }
+        }
+
+        let module = parse(source).expect("the source should parse");
+        assert_eq!(module.nodes.len(), 1);
+        write(&module.nodes[0])
+    }
+
+    #[test]
+    fn attaches_doc_comments() {
+        let module = parse(
+            "//! The module.\n//! More.\n\n/// Adds.\n/// Twice.\nlet add(a: int): int = a + a;\n/// A.\nlet a = 1;",
+        )
+        .expect("the source should parse");
+        assert_eq!(module.docs.as_deref(), Some("The module.\nMore."));
+
+        let docs: Vec<_> = module
+            .nodes
+            .iter()
+            .filter_map(|node| match node.ty {
+                ASTNodeType::Semi(ref node) => match node.ty {
+                    ASTNodeType::FunDef { ref docs, .. }
+                    | ASTNodeType::LetDecl { ref docs, .. } => docs.as_deref(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(docs, ["Adds.\nTwice.", "A."]);
+    }
+
+    #[test]
+    fn rejects_misplaced_doc_comments() {
+        for source in ["/// Nothing.\n1 + 1", "let a = 1;\n//! Late.\nlet b = 2;"] {
+            let errors = parse(source).expect_err("the doc comment is misplaced");
+            assert_eq!(errors[0].code, Some(error_codes::E0017.code));
+        }
+    }
+
+    #[test]
+    fn subtracts_without_spaces() {
+        assert_eq!(sexpr("a-1"), "(- a 1)");
+        assert_eq!(sexpr("f(x)-2"), "(- f(x) 2)");
+        assert_eq!(sexpr("1-1"), "(- 1 1)");
+        assert_eq!(sexpr("a-1-1"), "(- (- a 1) 1)");
+        assert_eq!(sexpr("a - 1.5"), "(- a 1.5)");
+    }
+
+    #[test]
+    fn folds_negated_literals() {
+        assert_eq!(sexpr("a - -1"), "(- a -1)");
+        assert_eq!(sexpr("a--1"), "(- a -1)");
+        assert_eq!(sexpr("2 * -3"), "(* 2 -3)");
+        assert_eq!(sexpr("-1.5"), "-1.5");
+        assert_eq!(sexpr("-x"), "(- x)");
+        assert_eq!(sexpr("- -1"), "(- -1)");
+    }
+
+    #[test]
+    fn negated_literals_bind_tighter_than_operators() {
+        assert_eq!(sexpr("-1 * 2"), "(* -1 2)");
+        assert_eq!(sexpr("-2 - 1"), "(- -2 1)");
+    }
+
+    #[test]
+    fn allows_i64_min() {
+        assert_eq!(sexpr("-9223372036854775808"), i64::MIN.to_string());
+        assert_eq!(sexpr("-0x8000000000000000"), i64::MIN.to_string());
+        assert_eq!(
+            sexpr("a - -9223372036854775808"),
+            format!("(- a {})", i64::MIN)
+        );
+
+        for source in ["9223372036854775808", "- (9223372036854775808)"] {
+            let errors = parse(source).expect_err("the literal is out of range");
+            assert_eq!(errors[0].code, Some(error_codes::E0016.code));
+        }
+    }
+
+    #[test]
+    fn reports_lex_errors_in_interpolations() {
+        let source = r#"let s = "a{1 $ 2}";"#;
+        let errors = parse(source).expect_err("the interpolation shouldn't lex");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, Some(error_codes::E0018.code));
+        let span = errors[0].primary_span;
+        assert_eq!(source.get(span.start()..span.end()), Some("$"));
+    }
+
+    #[test]
+    #[expect(clippy::panic, reason = "The source only has functions in it.")]
+    fn consumes_closing_parens() {
+        assert_eq!(sexpr("(a - 1) * 2"), "(* (- a 1) 2)");
+        assert_eq!(sexpr("f((1), ((2)))"), "f(1, 2)");
+
+        let module = parse("let f(x: (int)): () = ();\nlet g(): ((())) = (());")
+            .expect("the source should parse");
+        assert_eq!(module.nodes.len(), 2);
+        for node in &module.nodes {
+            let ASTNodeType::Semi(ref node) = node.ty else {
+                panic!("expected a statement, found {node:?}");
+            };
+            let ASTNodeType::FunDef {
+                ref params,
+                return_ty: Some(ref return_ty),
+                ref body,
+                ..
+            } = node.ty
+            else {
+                panic!("expected a function, found {node:?}");
+            };
+            assert_eq!(return_ty.ty, FrontendTypeType::Unit);
+            assert_eq!(body.ty, ASTNodeType::Unit);
+            let int = FrontendTypeType::Identifier("int".to_owned());
+            assert!(params.iter().all(|param| param.1.ty == int));
+        }
+    }
+
+    #[test]
+    fn rejects_unclosed_parens() {
+        for source in ["(a - 1", "let f(): (int = 1;", "let f() = (;"] {
+            parse(source).expect_err("the paren is unclosed");
+        }
+    }
+
+    #[test]
+    fn keywords_are_those_the_grammar_uses() {
+        let source =
+            "let f(a: bool): int = if a and true or false then 1 else { while a do {}; 2 };";
+        parse(source).expect("the source should parse");
+        let mut used: Vec<_> = tokenize(source)
+            .expect("the source should lex")
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(TokenKind::is_keyword)
+            .collect();
+        used.sort_by_key(|kind| KEYWORDS.iter().position(|keyword| keyword == kind));
+        used.dedup();
+        assert_eq!(used, KEYWORDS);
+
+        for keyword in TokenKind::KEYWORDS {
+            if !KEYWORDS.contains(&keyword) {
+                assert!(
+                    parse(&format!("{keyword} x")).is_err(),
+                    "`{keyword}` is reserved but parses"
+                );
+            }
         }
     }
 }
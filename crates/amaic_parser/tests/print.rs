@@ -0,0 +1,371 @@
+//! Checks that parsing a printed syntax tree gives back the same tree.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use amaic_ast::*;
+use amaic_core::{FileId, SourceMap, Span};
+use amaic_lexer::{Operator, tokenize};
+use amaic_parser::Parser;
+use proptest::{option, prelude::*, sample::select};
+
+const INFIX: [Operator; 27] = [
+    Operator::Plus,
+    Operator::Minus,
+    Operator::Star,
+    Operator::Slash,
+    Operator::Modulo,
+    Operator::Concat,
+    Operator::Lsh,
+    Operator::Rsh,
+    Operator::Ampersand,
+    Operator::Pipe,
+    Operator::Caret,
+    Operator::Eq,
+    Operator::Ne,
+    Operator::Lt,
+    Operator::Gt,
+    Operator::Le,
+    Operator::Ge,
+    Operator::LogAnd,
+    Operator::LogOr,
+    Operator::Range,
+    Operator::RangeInclus,
+    Operator::Assign,
+    Operator::PlusAssign,
+    Operator::MinusAssign,
+    Operator::StarAssign,
+    Operator::SlashAssign,
+    Operator::ModuloAssign,
+];
+
+const PREFIX: [Operator; 4] = [
+    Operator::Plus,
+    Operator::Minus,
+    Operator::Tilde,
+    Operator::Bang,
+];
+
+fn node(ty: ASTNodeType) -> ASTNode {
+    ASTNode {
+        ty,
+        span: Span::default(),
+    }
+}
+
+fn name() -> impl Strategy<Value = String> {
+    select(["a", "b", "foo", "x1", "é"].as_slice()).prop_map(String::from)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    select(
+        [
+            "hi", "q\"uote", "br{ace}", "tab\t\\n", "\u{1}x", "héllo\n", "",
+        ]
+        .as_slice(),
+    )
+    .prop_map(String::from)
+}
+
+fn docs() -> impl Strategy<Value = Option<String>> {
+    option::of(select(["doc", "one\n\n  two /", " spaced"].as_slice()).prop_map(String::from))
+}
+
+fn ty() -> impl Strategy<Value = FrontendType> {
+    let leaf = prop_oneof![
+        name().prop_map(FrontendTypeType::Identifier),
+        Just(FrontendTypeType::Unit),
+    ];
+    leaf.prop_recursive(3, 4, 1, |inner| {
+        inner.prop_map(|ty| {
+            FrontendTypeType::Vector(Box::new(FrontendType {
+                ty,
+                span: Span::default(),
+            }))
+        })
+    })
+    .prop_map(|ty| FrontendType {
+        ty,
+        span: Span::default(),
+    })
+}
+
+/// Expressions that can be interpolated, which can't contain strings or
+/// blocks.
+fn interpolated() -> impl Strategy<Value = ASTNode> {
+    let leaf = prop_oneof![
+        name().prop_map(|name| node(ASTNodeType::Identifier(name))),
+        (-1000..1000_i64).prop_map(|int| node(ASTNodeType::IntLit(int))),
+    ];
+    leaf.prop_recursive(3, 8, 2, |inner| {
+        prop_oneof![
+            (select(&INFIX[..19]), inner.clone(), inner.clone()).prop_map(|(op, lhs, rhs)| {
+                node(ASTNodeType::BinaryOp {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    op_tys: None,
+                })
+            }),
+            (name(), prop::collection::vec(inner, 0..3))
+                .prop_map(|(callee, args)| node(ASTNodeType::FunCall { callee, args })),
+        ]
+    })
+}
+
+fn interpolation() -> impl Strategy<Value = ASTNode> {
+    let part = prop_oneof![
+        text().prop_map(InterpolationPart::Text),
+        interpolated().prop_map(|expr| InterpolationPart::Expr { expr, ty: None }),
+    ];
+    // adjacent text is a single part, and a string without any expressions is
+    // a plain string literal
+    (prop::collection::vec(part, 1..5), interpolated()).prop_map(|(parts, expr)| {
+        let mut merged = Vec::<InterpolationPart>::new();
+        for next in parts {
+            match (merged.last_mut(), next) {
+                (
+                    Some(&mut InterpolationPart::Text(ref mut last)),
+                    InterpolationPart::Text(text),
+                ) => last.push_str(&text),
+                (_, next) => merged.push(next),
+            }
+        }
+        if !merged
+            .iter()
+            .any(|merged_part| matches!(*merged_part, InterpolationPart::Expr { .. }))
+        {
+            merged.push(InterpolationPart::Expr { expr, ty: None });
+        }
+        merged.retain(|merged_part| {
+            !matches!(*merged_part, InterpolationPart::Text(ref text) if text.is_empty())
+        });
+        node(ASTNodeType::Interpolation(merged))
+    })
+}
+
+/// Statements, each but the last of which ends in a `;`.
+fn statements(expr: impl Strategy<Value = ASTNode>) -> impl Strategy<Value = Vec<ASTNode>> {
+    (prop::collection::vec(expr, 0..4), any::<bool>()).prop_map(|(exprs, last_semi)| {
+        let count = exprs.len();
+        exprs
+            .into_iter()
+            .enumerate()
+            .map(|(index, stmt)| {
+                if index.strict_add(1) < count || last_semi {
+                    node(ASTNodeType::Semi(Box::new(stmt)))
+                } else {
+                    stmt
+                }
+            })
+            .collect()
+    })
+}
+
+fn expr() -> impl Strategy<Value = ASTNode> {
+    let leaf = prop_oneof![
+        any::<i64>().prop_map(|int| node(ASTNodeType::IntLit(int))),
+        select(
+            [
+                0.5_f64,
+                -0.5_f64,
+                1e300_f64,
+                -2.5e-10_f64,
+                3.0_f64,
+                f64::INFINITY,
+                -0.0_f64
+            ]
+            .as_slice()
+        )
+        .prop_map(|float| node(ASTNodeType::FloatLit(float))),
+        text().prop_map(|text| node(ASTNodeType::StringLit(text))),
+        any::<bool>().prop_map(|bool| node(ASTNodeType::Boolean(bool))),
+        name().prop_map(|name| node(ASTNodeType::Identifier(name))),
+        Just(node(ASTNodeType::Unit)),
+        interpolation(),
+    ];
+    leaf.prop_recursive(5, 48, 3, |inner| {
+        let boxed = || inner.clone().prop_map(Box::new);
+        prop_oneof![
+            (select(INFIX.as_slice()), boxed(), boxed()).prop_map(|(op, lhs, rhs)| {
+                node(ASTNodeType::BinaryOp {
+                    op,
+                    lhs,
+                    rhs,
+                    op_tys: None,
+                })
+            }),
+            (select(PREFIX.as_slice()), boxed()).prop_map(|(op, operand)| {
+                node(ASTNodeType::UnaryOp {
+                    op,
+                    operand,
+                    op_ty: None,
+                })
+            }),
+            statements(inner.clone()).prop_map(|stmts| node(ASTNodeType::Block(stmts))),
+            (docs(), name(), option::of(ty()), option::of(boxed())).prop_map(
+                |(docs, name, ty, init)| node(ASTNodeType::LetDecl {
+                    docs,
+                    name,
+                    ty,
+                    init
+                })
+            ),
+            (boxed(), boxed(), option::of(boxed())).prop_map(
+                |(condition, then_body, else_body)| {
+                    node(ASTNodeType::If {
+                        condition,
+                        then_body,
+                        else_body,
+                    })
+                }
+            ),
+            (boxed(), boxed())
+                .prop_map(|(condition, body)| { node(ASTNodeType::While { condition, body }) }),
+            (
+                docs(),
+                name(),
+                prop::collection::vec((name(), ty()), 0..3),
+                option::of(ty()),
+                boxed(),
+            )
+                .prop_map(|(docs, name, params, return_ty, body)| {
+                    node(ASTNodeType::FunDef {
+                        docs,
+                        name,
+                        params: params
+                            .into_iter()
+                            .map(|(param, ty)| (param, ty, Span::default()))
+                            .collect(),
+                        return_ty,
+                        body,
+                    })
+                }),
+            (name(), prop::collection::vec(inner.clone(), 0..3))
+                .prop_map(|(callee, args)| node(ASTNodeType::FunCall { callee, args })),
+        ]
+    })
+}
+
+fn module() -> impl Strategy<Value = ASTModule> {
+    (docs(), statements(expr())).prop_map(|(docs, nodes)| ASTModule {
+        path: "test.amai".into(),
+        docs,
+        nodes: nodes.into_boxed_slice(),
+    })
+}
+
+/// Resets every span in `ty`.
+fn clear_type_spans(ty: &mut FrontendType) {
+    ty.span = Span::default();
+    if let FrontendTypeType::Vector(ref mut inner) = ty.ty {
+        clear_type_spans(inner);
+    }
+}
+
+/// Resets every span in `node`, so that trees can be compared regardless of
+/// where they were parsed from.
+fn clear_spans(node: &mut ASTNode) {
+    node.span = Span::default();
+    match node.ty {
+        ASTNodeType::Interpolation(ref mut parts) => {
+            for part in parts {
+                if let InterpolationPart::Expr { ref mut expr, .. } = *part {
+                    clear_spans(expr);
+                }
+            }
+        }
+        ASTNodeType::Semi(ref mut inner)
+        | ASTNodeType::UnaryOp {
+            operand: ref mut inner,
+            ..
+        } => clear_spans(inner),
+        ASTNodeType::Block(ref mut nodes)
+        | ASTNodeType::FunCall {
+            args: ref mut nodes,
+            ..
+        } => nodes.iter_mut().for_each(clear_spans),
+        ASTNodeType::BinaryOp {
+            ref mut lhs,
+            ref mut rhs,
+            ..
+        } => {
+            clear_spans(lhs);
+            clear_spans(rhs);
+        }
+        ASTNodeType::LetDecl {
+            ref mut ty,
+            ref mut init,
+            ..
+        } => {
+            if let Some(ref mut ty) = *ty {
+                clear_type_spans(ty);
+            }
+            if let Some(ref mut init) = *init {
+                clear_spans(init);
+            }
+        }
+        ASTNodeType::If {
+            ref mut condition,
+            ref mut then_body,
+            ref mut else_body,
+        } => {
+            clear_spans(condition);
+            clear_spans(then_body);
+            if let Some(ref mut else_body) = *else_body {
+                clear_spans(else_body);
+            }
+        }
+        ASTNodeType::While {
+            ref mut condition,
+            ref mut body,
+        } => {
+            clear_spans(condition);
+            clear_spans(body);
+        }
+        ASTNodeType::FunDef {
+            ref mut params,
+            ref mut return_ty,
+            ref mut body,
+            ..
+        } => {
+            for param in params {
+                clear_type_spans(&mut param.1);
+                param.2 = Span::default();
+            }
+            if let Some(ref mut return_ty) = *return_ty {
+                clear_type_spans(return_ty);
+            }
+            clear_spans(body);
+        }
+        ASTNodeType::Boolean(_)
+        | ASTNodeType::FloatLit(_)
+        | ASTNodeType::Identifier(_)
+        | ASTNodeType::IntLit(_)
+        | ASTNodeType::StringLit(_)
+        | ASTNodeType::Unit => {}
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn parsing_printed_trees_gives_them_back(module in module()) {
+        let printed = module.to_string();
+        let file: FileId = SourceMap::default().add("test.amai", printed.as_str());
+        let tokens = tokenize(&printed)
+            .map_err(|errors| TestCaseError::fail(format!("{errors:?} in:\n{printed}")))?;
+        let mut parsed = Parser::new("test.amai", file, &tokens)
+            .parse()
+            .map_err(|errors| {
+                TestCaseError::fail(format!("{} in:\n{printed}", errors[0].primary_err))
+            })?;
+
+        parsed.nodes.iter_mut().for_each(clear_spans);
+        prop_assert_eq!(&parsed.docs, &module.docs, "in:\n{}", printed);
+        prop_assert_eq!(&parsed.nodes, &module.nodes, "in:\n{}", printed);
+    }
+}
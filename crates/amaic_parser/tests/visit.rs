@@ -0,0 +1,129 @@
+//! Checks that the visitors reach every part of a parsed syntax tree.
+
+#![expect(
+    clippy::tests_outside_test_module,
+    reason = "Integration tests are compiled only for testing."
+)]
+
+use amaic_ast::{
+    ASTModule, ASTNode, ASTNodeType, FrontendType, InterpolationPart, Visitor, VisitorMut,
+    visit::{
+        Param, walk_interpolation_part, walk_interpolation_part_mut, walk_node, walk_node_mut,
+        walk_param, walk_param_mut, walk_type, walk_type_mut,
+    },
+};
+use amaic_core::FileId;
+use amaic_lexer::tokenize;
+use amaic_parser::Parser;
+
+const SOURCE: &str = "\
+let f(a: int, b: [int]): int = {
+    let s: string = \"x{a}y{-a}\";
+    if a > 1 then f(a, b) else 0
+};
+";
+
+/// How many of each part of a syntax tree were visited.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Counts {
+    /// The identifiers, in the order they were visited.
+    identifiers: Vec<String>,
+
+    /// The interpolation parts.
+    interpolation_parts: usize,
+
+    /// The nodes.
+    nodes: usize,
+
+    /// The parameters.
+    params: usize,
+
+    /// The written types.
+    types: usize,
+}
+
+impl<'ast> Visitor<'ast> for Counts {
+    fn visit_interpolation_part(&mut self, part: &'ast InterpolationPart) {
+        self.interpolation_parts = self.interpolation_parts.strict_add(1);
+        walk_interpolation_part(self, part);
+    }
+
+    fn visit_node(&mut self, node: &'ast ASTNode) {
+        self.nodes = self.nodes.strict_add(1);
+        if let ASTNodeType::Identifier(ref name) = node.ty {
+            self.identifiers.push(name.clone());
+        }
+        walk_node(self, node);
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.params = self.params.strict_add(1);
+        walk_param(self, param);
+    }
+
+    fn visit_type(&mut self, ty: &'ast FrontendType) {
+        self.types = self.types.strict_add(1);
+        walk_type(self, ty);
+    }
+}
+
+impl VisitorMut for Counts {
+    fn visit_interpolation_part_mut(&mut self, part: &mut InterpolationPart) {
+        self.interpolation_parts = self.interpolation_parts.strict_add(1);
+        walk_interpolation_part_mut(self, part);
+    }
+
+    fn visit_node_mut(&mut self, node: &mut ASTNode) {
+        self.nodes = self.nodes.strict_add(1);
+        if let ASTNodeType::Identifier(ref name) = node.ty {
+            self.identifiers.push(name.clone());
+        }
+        walk_node_mut(self, node);
+    }
+
+    fn visit_param_mut(&mut self, param: &mut Param) {
+        self.params = self.params.strict_add(1);
+        walk_param_mut(self, param);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut FrontendType) {
+        self.types = self.types.strict_add(1);
+        walk_type_mut(self, ty);
+    }
+}
+
+/// Parses [`SOURCE`].
+fn parse() -> ASTModule {
+    let tokens = tokenize(SOURCE).expect("the source should lex");
+    Parser::new("test.amai", FileId::default(), &tokens)
+        .parse()
+        .expect("the source should parse")
+}
+
+/// What visiting [`SOURCE`] should count.
+fn expected() -> Counts {
+    Counts {
+        identifiers: ["a", "a", "a", "a", "b"].map(str::to_owned).to_vec(),
+        // `x`, `{a}`, `y` and `{-a}`
+        interpolation_parts: 4,
+        // the definition and its `;`, the block, the `let` and its `;`, the
+        // string and the 3 nodes in it, and the 8 nodes of the `if`
+        nodes: 17,
+        params: 2,
+        // `int`, `[int]` and the `int` in it, the return type, and `string`
+        types: 5,
+    }
+}
+
+#[test]
+fn visitors_reach_every_part() {
+    let mut module = parse();
+
+    let mut counts = Counts::default();
+    counts.visit_module(&module);
+    assert_eq!(counts, expected());
+
+    let mut counts_mut = Counts::default();
+    counts_mut.visit_module_mut(&mut module);
+    assert_eq!(counts_mut, expected());
+}
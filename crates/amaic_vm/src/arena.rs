@@ -1,29 +1,45 @@
+//! See [`Arena`].
+
+/// A growable region of bytes that values are allocated into, addressed by
+/// their offset from its start.
+#[derive(Debug, Default)]
 pub struct Arena {
+    /// The allocated bytes.
     inner: Vec<u8>,
 }
 
 impl Arena {
-    pub fn new() -> Self {
-        Self {
-            inner: Vec::new(),
-        }
-    }
-
+    /// Allocates `size` zeroed bytes, aligned to `align`, which must be a power
+    /// of two, returning their address.
     pub fn alloc(&mut self, size: usize, align: usize) -> usize {
-        let current = self.inner.len();
-        let aligned_offset = (current + align - 1) & !(align - 1); // wtf is this formula
-
-        let to_be_allocated_len = aligned_offset + size;
-        self.inner.resize(to_be_allocated_len, 0);
+        let aligned_offset = self.inner.len().next_multiple_of(align);
+        self.inner.resize(aligned_offset.strict_add(size), 0);
 
         aligned_offset
     }
 
-    pub fn write(&mut self, addr: usize, data: &[u8]) {
-        self.inner[addr..(addr + data.len())].copy_from_slice(data);
+    /// The `size` bytes at `addr`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes aren't allocated.
+    #[must_use]
+    pub fn fetch(&self, addr: usize, size: usize) -> &[u8] {
+        &self.inner[addr..addr.strict_add(size)]
     }
 
-    pub fn fetch(&self, addr: usize, size: usize) -> &[u8] {
-        &self.inner[addr..(addr + size)]
+    /// Creates an empty arena.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { inner: Vec::new() }
     }
-}
\ No newline at end of file
+
+    /// Overwrites the bytes at `addr` with `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes aren't allocated.
+    pub fn write(&mut self, addr: usize, data: &[u8]) {
+        self.inner[addr..addr.strict_add(data.len())].copy_from_slice(data);
+    }
+}
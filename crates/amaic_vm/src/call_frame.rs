@@ -1,12 +1,25 @@
+//! See [`CallFrame`].
+
 use super::function::Function;
 use super::value::Value;
 use std::rc::Rc;
 
+/// The state of a function while it's being run.
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct CallFrame {
-    pub caller_args: Box<[Value]>,
+    /// The arguments pushed for the next call the function makes.
     pub callee_args: Vec<Value>,
+
+    /// The arguments the function was called with.
+    pub caller_args: Box<[Value]>,
+
+    /// The function being run.
     pub function: Rc<Function>,
-    pub registers: [Value; 64],
+
+    /// The index of the instruction being run.
     pub ip: usize,
-}
\ No newline at end of file
+
+    /// The function's registers.
+    pub registers: [Value; 64],
+}
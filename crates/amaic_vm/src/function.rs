@@ -1,6 +1,12 @@
+//! See [`Function`].
+
 use amaic_core::Span;
 
+/// A function's bytecode.
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct Function {
+    /// The function's instructions, each with the span of the source-text it
+    /// was compiled from.
     pub bytecode: Box<[(u32, Span)]>,
 }
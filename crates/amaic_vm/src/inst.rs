@@ -1,46 +1,102 @@
-pub const NOP: u8  = 0x00;
+//! The opcodes of the VM's instructions.
+//!
+//! An instruction is a `u32`, whose lowest byte is its opcode. Unless noted
+//! otherwise, the next byte is the register its result is written to, and the
+//! two after that are the registers of its operands.
+
+/// Does nothing.
+pub const NOP: u8 = 0x00;
+/// Loads the constant whose index is the upper two bytes.
 pub const LOAD: u8 = 0x01;
+/// Adds two integers, erroring on overflow.
 pub const IADD: u8 = 0x02;
+/// Subtracts two integers, erroring on overflow.
 pub const ISUB: u8 = 0x03;
+/// Multiplies two integers, erroring on overflow.
 pub const IMUL: u8 = 0x04;
+/// Divides two integers, erroring on overflow or division by zero.
 pub const IDIV: u8 = 0x05;
+/// Takes the remainder of two integers, erroring on overflow or division by
+/// zero.
 pub const IREM: u8 = 0x06;
+/// Adds two floats.
 pub const FADD: u8 = 0x07;
+/// Subtracts two floats.
 pub const FSUB: u8 = 0x08;
+/// Multiplies two floats.
 pub const FMUL: u8 = 0x09;
+/// Divides two floats, erroring on division by zero.
 pub const FDIV: u8 = 0x0A;
+/// Takes the remainder of two floats, erroring on division by zero.
 pub const FREM: u8 = 0x0B;
-pub const BOR: u8  = 0x0C;
+/// Bitwise-ors two integers.
+pub const BOR: u8 = 0x0C;
+/// Bitwise-ands two integers.
 pub const BAND: u8 = 0x0D;
+/// Bitwise-xors two integers.
 pub const BXOR: u8 = 0x0E;
+/// Bitwise-nots an integer.
 pub const BNOT: u8 = 0x0F;
-pub const LOR: u8  = 0x10;
+/// Logically-ors two booleans.
+pub const LOR: u8 = 0x10;
+/// Logically-ands two booleans.
 pub const LAND: u8 = 0x11;
+/// Logically-nots a boolean.
 pub const LNOT: u8 = 0x12;
+/// Compares two values for equality.
 pub const CMEQ: u8 = 0x13;
+/// Compares two values for inequality.
 pub const CMNE: u8 = 0x14;
+/// Compares whether an integer is greater than another.
 pub const ICGT: u8 = 0x15;
+/// Compares whether an integer is less than another.
 pub const ICLT: u8 = 0x16;
+/// Compares whether an integer is greater than or equal to another.
 pub const ICGE: u8 = 0x17;
+/// Compares whether an integer is less than or equal to another.
 pub const ICLE: u8 = 0x18;
+/// Compares whether a float is greater than another.
 pub const FCGT: u8 = 0x19;
+/// Compares whether a float is less than another.
 pub const FCLT: u8 = 0x1A;
+/// Compares whether a float is greater than or equal to another.
 pub const FCGE: u8 = 0x1B;
+/// Compares whether a float is less than or equal to another.
 pub const FCLE: u8 = 0x1C;
-pub const JUMP: u8 = 0x1D; // NOTE: the destination operand (register) will be read as an instruction index
-pub const JITR: u8 = 0x1E; // jump if true
-pub const JIFL: u8 = 0x1F; // jump if false
+/// Jumps by the signed offset in the two bytes after the opcode.
+pub const JUMP: u8 = 0x1D;
+/// Jumps like [`JUMP`] if the register in the upper byte is true.
+pub const JITR: u8 = 0x1E;
+/// Jumps like [`JUMP`] if the register in the upper byte is false.
+pub const JIFL: u8 = 0x1F;
+/// Calls the function whose id is in the register after the opcode, with the
+/// arguments pushed by [`PARG`].
 pub const CALL: u8 = 0x20;
+/// Returns from the current function.
 pub const RETN: u8 = 0x21;
+/// Negates an integer.
 pub const INEG: u8 = 0x22;
+/// Negates a float.
 pub const FNEG: u8 = 0x23;
+/// Copies a register.
 pub const MOVE: u8 = 0x24;
-pub const PARG: u8 = 0x25; // push arg (to caller args)
-pub const CARG: u8 = 0x26; // copy arg (from caller args)
-pub const CEXT: u8 = 0x27; // call external
+/// Pushes the register after the opcode onto the arguments of the next call.
+pub const PARG: u8 = 0x25;
+/// Copies the argument, whose index is the upper two bytes, that the current
+/// function was called with.
+pub const CARG: u8 = 0x26;
+/// Calls the external function whose id is the upper three bytes, with the
+/// arguments pushed by [`PARG`].
+pub const CEXT: u8 = 0x27;
+/// Shifts an integer left, erroring on overflow or a negative shift.
 pub const LSHF: u8 = 0x28;
+/// Shifts an integer right, erroring on overflow or a negative shift.
 pub const RSHF: u8 = 0x29;
+/// Concatenates two strings.
 pub const SCON: u8 = 0x2A;
+/// Compares two strings for equality.
 pub const SCEQ: u8 = 0x2B;
+/// Compares two strings for inequality.
 pub const SCNE: u8 = 0x2C;
-pub const HALT: u8 = 0xFF;
\ No newline at end of file
+/// Stops the VM.
+pub const HALT: u8 = 0xFF;
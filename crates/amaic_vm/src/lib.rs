@@ -1,3 +1,5 @@
+//! The virtual machine that runs Amai's bytecode.
+
 pub mod arena;
 pub mod call_frame;
 pub mod function;
@@ -8,399 +10,276 @@ use amaic_core::Span;
 use arena::Arena;
 use call_frame::CallFrame;
 use function::Function;
-use inst::*;
-use std::rc::Rc;
-use value::*;
-
+use inst::{
+    BAND, BNOT, BOR, BXOR, CALL, CARG, CEXT, CMEQ, CMNE, FADD, FCGE, FCGT, FCLE, FCLT, FDIV, FMUL,
+    FNEG, FREM, FSUB, HALT, IADD, ICGE, ICGT, ICLE, ICLT, IDIV, IMUL, INEG, IREM, ISUB, JIFL, JITR,
+    JUMP, LAND, LNOT, LOAD, LOR, LSHF, MOVE, NOP, PARG, RETN, RSHF, SCEQ, SCNE, SCON,
+};
+use std::{mem, rc::Rc};
+use value::Value;
+
+/// A function implemented outside of the VM, which is called with the VM and
+/// its arguments.
+pub type ExternalFunction = Rc<dyn Fn(&mut AmaiVM, &[Value])>;
+
+/// The virtual machine, along with the functions and constants it runs.
+#[non_exhaustive]
 pub struct AmaiVM {
-    pub frames: Vec<CallFrame>,
-    pub constants: Box<[Value]>,
-    pub running: bool,
-    pub functions: Vec<(Rc<Function>, [Value; 64])>,
+    /// Whether functions may have more instructions than jumps can reach.
     pub allow_large_bytecode: bool,
-    pub external_functions: Vec<Rc<dyn Fn(&mut AmaiVM, &[Value])>>,
+
+    /// The arena that strings are allocated into.
     pub arena: Arena,
+
+    /// The constants loaded by [`LOAD`].
+    pub constants: Box<[Value]>,
+
+    /// The functions called by [`CEXT`].
+    pub external_functions: Vec<ExternalFunction>,
+
+    /// The functions being run, innermost last.
+    pub frames: Vec<CallFrame>,
+
+    /// Every function, along with the initial values of its registers.
+    pub functions: Vec<(Rc<Function>, [Value; 64])>,
+
+    /// Whether the VM is running.
+    pub running: bool,
 }
 
 impl AmaiVM {
-    pub fn new(allow_large_bytecode: bool) -> Self {
-        Self {
-            frames: Vec::new(),
-            constants: Box::new([]),
-            running: false,
-            functions: Vec::new(),
-            allow_large_bytecode,
-            external_functions: Vec::new(),
-            arena: Arena::new(),
-        }
-    }
-
-    #[allow(unused)]
-    pub fn add_extern_fn<F: Fn(&mut AmaiVM, &[Value]) + 'static>(&mut self, f: F) -> u32 {
-        self.external_functions.push(Rc::new(f));
-        self.external_functions.len() as u32 - 1
+    /// Adds an external function, returning its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `u32::MAX` external functions.
+    pub fn add_extern_fn<F: Fn(&mut Self, &[Value]) + 'static>(&mut self, function: F) -> u32 {
+        self.external_functions.push(Rc::new(function));
+        u32::try_from(self.external_functions.len().strict_sub(1))
+            .expect("there should be at most `u32::MAX` external functions")
     }
 
-    #[inline(always)]
+    /// Adds a function, returning its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `registers` doesn't hold 64 values, or if there are more
+    /// instructions than jumps can reach and that isn't allowed.
+    #[inline]
     pub fn add_function(&mut self, bytecode: Box<[(u32, Span)]>, registers: &[Value]) -> usize {
         if !self.allow_large_bytecode {
             assert!(
-                bytecode.len() < 65536,
+                bytecode.len() < 0x0001_0000,
                 "Bytecode length is out of jump bounds"
             );
         }
 
         let func = Function { bytecode };
-        self.functions
-            .push((Rc::new(func), registers.try_into().unwrap()));
-        self.functions.len() - 1
+        self.functions.push((
+            Rc::new(func),
+            registers
+                .try_into()
+                .expect("a function should have 64 registers"),
+        ));
+        self.functions.len().strict_sub(1)
     }
 
-    #[inline(always)]
+    /// Calls the external function `id` with `caller_args`.
+    #[inline]
+    pub fn call_external(&mut self, id: usize, caller_args: &[Value]) {
+        let function = Rc::clone(&self.external_functions[id]);
+        function(self, caller_args);
+    }
+
+    /// Calls the function `id` with `caller_args`.
+    #[inline]
     pub fn call_function(&mut self, id: usize, caller_args: Box<[Value]>) {
         let (function, registers) = self.functions[id].clone();
         let new_frame = CallFrame {
-            caller_args,
             callee_args: Vec::new(),
+            caller_args,
             function,
-            registers,
             ip: 0,
+            registers,
         };
         self.frames.push(new_frame);
     }
-    #[inline(always)]
-    pub fn call_external(&mut self, id: usize, caller_args: &[Value]) {
-        let f = self.external_functions[id].clone();
-        f(self, caller_args);
-    }
 
-    #[inline(always)]
-    pub fn return_function(&mut self) {
-        if let Some(mut callee_frame) = self.frames.pop() {
-            if let Some(caller_frame) = self.frames.last_mut() {
-                caller_frame.registers[0] = callee_frame.callee_args.pop().unwrap_or(Value::nil());
-            }
-        }
-    }
-
-    pub fn run(&mut self) -> Result<(), (String, Span)> {
-        self.running = true;
-        while self.running {
-            unsafe { self.cycle()? }
-        }
-
-        Ok(())
-    }
-
-    #[inline(always)]
-    #[allow(unsafe_op_in_unsafe_fn)]
-    pub unsafe fn cycle(&mut self) -> Result<(), (String, Span)> {
-        if self.frames.is_empty() {
+    /// Runs the instruction at the instruction pointer of the innermost
+    /// function, and stops the VM once there's none left.
+    ///
+    /// # Errors
+    ///
+    /// Errors with a message and the span of the instruction if it fails, such
+    /// as on integer overflow, or if its opcode is unknown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction refers to a register, constant or argument
+    /// that doesn't exist.
+    #[inline]
+    #[expect(
+        clippy::too_many_lines,
+        reason = "Each opcode is handled in a single arm."
+    )]
+    pub fn cycle(&mut self) -> Result<(), (String, Span)> {
+        let Some(frame) = self.frames.last_mut() else {
             self.running = false;
             return Ok(());
-        }
-        let frame = self.frames.last_mut().unwrap() as *mut CallFrame;
-        let (inst, span) = if let Some(inst) = (&(*frame).function).bytecode.get((*frame).ip) {
-            inst
-        } else {
+        };
+        let Some(&(inst, span)) = frame.function.bytecode.get(frame.ip) else {
             self.running = false;
             return Ok(());
         };
-        let mut next_ip = (*frame).ip + 1;
-
-        let opcode = (inst & 0xFF) as u8;
-
-        match opcode {
-            NOP => {}
-            LOAD => {
-                let dest = ((inst >> 8) & 0xFF) as u8;
-                let id = ((inst >> 16) & 0xFFFF) as u16;
-                let constant = *self.constants.get_unchecked(id as usize);
-
-                (*frame).registers[dest as usize] = constant;
-            }
-            IADD => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.iadd(src2).map_err(|err| (err, *span))?;
-            }
-            ISUB => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.isub(src2).map_err(|err| (err, *span))?;
-            }
-            IMUL => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.imul(src2).map_err(|err| (err, *span))?;
-            }
-            IDIV => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.idiv(src2).map_err(|err| (err, *span))?;
-            }
-            IREM => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.irem(src2).map_err(|err| (err, *span))?;
-            }
-            FADD => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fadd(src2);
-            }
-            FSUB => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fsub(src2);
-            }
-            FMUL => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fmul(src2);
-            }
-            FDIV => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1
-                    .fdiv(src2)
-                    .ok_or(("Division by zero".to_string(), *span))?;
-            }
-            FREM => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1
-                    .frem(src2)
-                    .ok_or(("Division by zero".to_string(), *span))?;
-            }
-            BOR => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.bor(src2);
-            }
-            BAND => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.band(src2);
-            }
-            BXOR => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.bxor(src2);
-            }
-            BNOT => {
-                let src = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src.bnot();
-            }
-            LOR => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.lor(src2);
-            }
-            LAND => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.land(src2);
-            }
-            LNOT => {
-                let src = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src.lnot();
-            }
-            CMEQ => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.cmeq(src2);
-            }
-            CMNE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.cmne(src2);
-            }
-            ICGT => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.icgt(src2);
-            }
-            ICLT => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.iclt(src2);
-            }
-            ICGE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.icge(src2);
-            }
-            ICLE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.icle(src2);
-            }
-            FCGT => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fcgt(src2);
-            }
-            FCLT => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fclt(src2);
-            }
-            FCGE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fcge(src2);
-            }
-            FCLE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src1.fcle(src2);
-            }
+        let [opcode, byte1, byte2, byte3] = inst.to_le_bytes();
+        let (dest, src1, src2) = (usize::from(byte1), usize::from(byte2), usize::from(byte3));
+        let offset = i16::from_le_bytes([byte1, byte2]);
+        let mut next_ip = frame.ip.strict_add(1);
+        let fail = |err: String| (err, span);
+
+        let registers = &frame.registers;
+        let lhs = || registers[src1];
+        let rhs = || registers[src2];
+        let result = match opcode {
+            NOP => None,
+            LOAD => Some(self.constants[usize::from(u16::from_le_bytes([byte2, byte3]))]),
+            IADD => Some(lhs().iadd(rhs()).map_err(fail)?),
+            ISUB => Some(lhs().isub(rhs()).map_err(fail)?),
+            IMUL => Some(lhs().imul(rhs()).map_err(fail)?),
+            IDIV => Some(lhs().idiv(rhs()).map_err(fail)?),
+            IREM => Some(lhs().irem(rhs()).map_err(fail)?),
+            FADD => Some(lhs().fadd(rhs())),
+            FSUB => Some(lhs().fsub(rhs())),
+            FMUL => Some(lhs().fmul(rhs())),
+            FDIV => Some(
+                lhs()
+                    .fdiv(rhs())
+                    .ok_or_else(|| fail("Division by zero".to_owned()))?,
+            ),
+            FREM => Some(
+                lhs()
+                    .frem(rhs())
+                    .ok_or_else(|| fail("Division by zero".to_owned()))?,
+            ),
+            BOR => Some(lhs().bor(rhs())),
+            BAND => Some(lhs().band(rhs())),
+            BXOR => Some(lhs().bxor(rhs())),
+            BNOT => Some(lhs().bnot()),
+            LOR => Some(lhs().lor(rhs())),
+            LAND => Some(lhs().land(rhs())),
+            LNOT => Some(lhs().lnot()),
+            CMEQ => Some(lhs().cmeq(rhs())),
+            CMNE => Some(lhs().cmne(rhs())),
+            ICGT => Some(lhs().icgt(rhs())),
+            ICLT => Some(lhs().iclt(rhs())),
+            ICGE => Some(lhs().icge(rhs())),
+            ICLE => Some(lhs().icle(rhs())),
+            FCGT => Some(lhs().fcgt(rhs())),
+            FCLT => Some(lhs().fclt(rhs())),
+            FCGE => Some(lhs().fcge(rhs())),
+            FCLE => Some(lhs().fcle(rhs())),
             JUMP => {
-                let addr = ((inst >> 8) & 0xFFFF) as i16;
-
-                if addr >= 0 {
-                    next_ip = (*frame).ip + addr as usize;
-                } else {
-                    next_ip = (*frame).ip - addr.abs() as usize;
-                }
+                next_ip = frame.ip.strict_add_signed(isize::from(offset));
+                None
             }
             JITR => {
-                let addr = ((inst >> 8) & 0xFFFF) as i16;
-                let src = (*frame).registers[((inst >> 24) & 0xFF) as usize].to_bool();
-
-                if src {
-                    if addr >= 0 {
-                        next_ip = (*frame).ip + addr as usize;
-                    } else {
-                        next_ip = (*frame).ip - addr.abs() as usize;
-                    }
+                if rhs().to_bool() {
+                    next_ip = frame.ip.strict_add_signed(isize::from(offset));
                 }
+                None
             }
             JIFL => {
-                let addr = ((inst >> 8) & 0xFFFF) as i16;
-                let src = (*frame).registers[((inst >> 24) & 0xFF) as usize].to_bool();
-
-                if !src {
-                    if addr >= 0 {
-                        next_ip = (*frame).ip + addr as usize;
-                    } else {
-                        next_ip = (*frame).ip - addr.abs() as usize;
-                    }
+                if !rhs().to_bool() {
+                    next_ip = frame.ip.strict_add_signed(isize::from(offset));
                 }
+                None
             }
             CALL => {
-                let id = (inst >> 8) & 0xFF;
-                self.call_function(
-                    (*frame).registers[id as usize].to_ptr(),
-                    (*frame).callee_args.clone().into_boxed_slice(),
-                );
-                (*frame).callee_args.clear();
-            }
-            RETN => self.return_function(),
-            INEG => {
-                let src = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src.ineg();
-            }
-            FNEG => {
-                let src = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src.fneg();
-            }
-            MOVE => {
-                let src = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = src;
-            }
+                let id = frame.registers[dest].to_ptr();
+                let args = mem::take(&mut frame.callee_args).into_boxed_slice();
+                frame.ip = next_ip;
+                self.call_function(id, args);
+                return Ok(());
+            }
+            RETN => {
+                self.return_function();
+                return Ok(());
+            }
+            INEG => Some(lhs().ineg()),
+            FNEG => Some(lhs().fneg()),
+            MOVE => Some(lhs()),
             PARG => {
-                let src = (*frame).registers[((inst >> 8) & 0xFF) as usize];
-
-                (*frame).callee_args.push(src);
-            }
-            CARG => {
-                let arg_id = ((inst >> 16) & 0xFFFF) as usize;
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] = (*frame).caller_args[arg_id];
+                let arg = frame.registers[dest];
+                frame.callee_args.push(arg);
+                None
             }
+            CARG => Some(frame.caller_args[usize::from(u16::from_le_bytes([byte2, byte3]))]),
             CEXT => {
-                let id = (inst >> 8) & 0xFFFFFF;
-
-                self.call_external(id as usize, &(*frame).callee_args);
-                (*frame).callee_args.clear();
-            }
-            LSHF => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
-
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.lshf(src2).map_err(|err| (err, *span))?;
-            }
-            RSHF => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
+                let id = (inst >> 8_u32) as usize;
+                let args = mem::take(&mut frame.callee_args);
+                frame.ip = next_ip;
+                self.call_external(id, &args);
+                return Ok(());
+            }
+            LSHF => Some(lhs().lshf(rhs()).map_err(fail)?),
+            RSHF => Some(lhs().rshf(rhs()).map_err(fail)?),
+            SCON => Some(lhs().scon(rhs(), &mut self.arena)),
+            SCEQ => Some(lhs().sceq(rhs(), &self.arena)),
+            SCNE => Some(lhs().scne(rhs(), &self.arena)),
+            HALT => {
+                self.running = false;
+                None
+            }
+            _ => return Err(fail(format!("Unknown opcode: {opcode:#04X}"))),
+        };
 
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.rshf(src2).map_err(|err| (err, *span))?;
-            }
-            SCON => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
+        if let Some(result) = result {
+            frame.registers[dest] = result;
+        }
+        frame.ip = next_ip;
 
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.scon(src2, &mut self.arena);
-            }
-            SCEQ => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
+        Ok(())
+    }
 
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.sceq(src2, &mut self.arena);
-            }
-            SCNE => {
-                let src1 = (*frame).registers[((inst >> 16) & 0xFF) as usize];
-                let src2 = (*frame).registers[((inst >> 24) & 0xFF) as usize];
+    /// Creates a VM without any functions or constants.
+    #[must_use]
+    pub fn new(allow_large_bytecode: bool) -> Self {
+        Self {
+            allow_large_bytecode,
+            arena: Arena::new(),
+            constants: Box::new([]),
+            external_functions: Vec::new(),
+            frames: Vec::new(),
+            functions: Vec::new(),
+            running: false,
+        }
+    }
 
-                (*frame).registers[((inst >> 8) & 0xFF) as usize] =
-                    src1.scne(src2, &mut self.arena);
-            }
-            HALT => self.running = false,
-            _ => panic!("Unknown opcode: {opcode:#04X}"),
+    /// Returns from the innermost function, passing the last argument it pushed
+    /// to its caller as the result.
+    #[inline]
+    pub fn return_function(&mut self) {
+        if let Some(mut callee_frame) = self.frames.pop()
+            && let Some(caller_frame) = self.frames.last_mut()
+        {
+            caller_frame.registers[0] = callee_frame.callee_args.pop().unwrap_or_else(Value::nil);
         }
+    }
 
-        (*frame).ip = next_ip;
+    /// Runs the VM until it stops.
+    ///
+    /// # Errors
+    ///
+    /// Errors with a message and the span of the instruction that failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an instruction is invalid, as described by [`Self::cycle`].
+    pub fn run(&mut self) -> Result<(), (String, Span)> {
+        self.running = true;
+        while self.running {
+            self.cycle()?;
+        }
 
         Ok(())
     }
@@ -1,258 +1,425 @@
+//! See [`Value`].
+
 use super::arena::Arena;
 
+/// A value in a register, whose bits are interpreted by the instruction using
+/// it.
 #[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
+#[expect(clippy::exhaustive_structs, reason = "A value is only ever its bits.")]
 pub struct Value(pub u64);
 
-#[allow(unused)]
 impl Value {
-    #[inline(always)]
-    pub fn from_int(x: i64) -> Self {
-        Self(x as u64)
-    }
-    #[inline(always)]
-    pub fn to_int(&self) -> i64 {
-        self.0 as i64
+    /// Bitwise-ands two integers.
+    #[inline]
+    #[must_use]
+    pub const fn band(&self, other: Self) -> Self {
+        Self::from_int(self.to_int() & other.to_int())
     }
 
-    #[inline(always)]
-    pub fn from_bool(x: bool) -> Self {
-        Self(x as u64)
-    }
-    #[inline(always)]
-    pub fn to_bool(&self) -> bool {
-        self.0 != 0
+    /// Bitwise-nots an integer.
+    #[inline]
+    #[must_use]
+    pub const fn bnot(&self) -> Self {
+        Self::from_int(!self.to_int())
     }
 
-    #[inline(always)]
-    pub fn nil() -> Self {
-        Self(0x0)
+    /// Bitwise-ors two integers.
+    #[inline]
+    #[must_use]
+    pub const fn bor(&self, other: Self) -> Self {
+        Self::from_int(self.to_int() | other.to_int())
     }
 
-    #[inline(always)]
-    pub fn from_float(x: f64) -> Self {
-        Self(x.to_bits())
-    }
-    #[inline(always)]
-    pub fn to_float(&self) -> f64 {
-        f64::from_bits(self.0)
-    }
-    
-    #[inline(always)]
-    pub fn from_ptr(addr: usize) -> Self {
-        Self(addr as u64)
+    /// Bitwise-xors two integers.
+    #[inline]
+    #[must_use]
+    pub const fn bxor(&self, other: Self) -> Self {
+        Self::from_int(self.to_int() ^ other.to_int())
     }
-    #[inline(always)]
-    pub fn to_ptr(&self) -> usize {
-        self.0 as usize
+
+    /// Whether two values have the same bits.
+    #[inline]
+    #[must_use]
+    pub const fn cmeq(&self, other: Self) -> Self {
+        Self::from_bool(self.0 == other.0)
     }
 
-    #[inline(always)]
-    pub fn iadd(&self, other: Self) -> Result<Self, String> {
-        let lhs = self.to_int();
-        let rhs = other.to_int();
-        Ok(Self::from_int(lhs.checked_add(rhs).ok_or(format!("Addition overflow (left: {lhs}, right: {rhs})"))?))
+    /// Whether two values have different bits.
+    #[inline]
+    #[must_use]
+    pub const fn cmne(&self, other: Self) -> Self {
+        Self::from_bool(self.0 != other.0)
     }
-    #[inline(always)]
-    pub fn isub(&self, other: Self) -> Result<Self, String> {
-        let lhs = self.to_int();
-        let rhs = other.to_int();
-        Ok(Self::from_int(lhs.checked_sub(rhs).ok_or(format!("Subtraction overflow (left: {lhs}, right: {rhs})"))?))
+
+    /// Adds two floats.
+    #[inline]
+    #[must_use]
+    pub fn fadd(&self, other: Self) -> Self {
+        Self::from_float(self.to_float() + other.to_float())
     }
-    #[inline(always)]
-    pub fn imul(&self, other: Self) -> Result<Self, String> {
-        let lhs = self.to_int();
-        let rhs = other.to_int();
-        Ok(Self::from_int(self.to_int().checked_mul(other.to_int()).ok_or(format!("Multiplication overflow (left: {lhs}, right: {rhs})"))?))
+
+    /// Whether a float is greater than or equal to another.
+    #[inline]
+    #[must_use]
+    pub fn fcge(&self, other: Self) -> Self {
+        Self::from_bool(self.to_float() >= other.to_float())
     }
-    #[inline(always)]
-    pub fn idiv(&self, other: Self) -> Result<Self, String> {
-        let lhs = self.to_int();
-        let rhs = other.to_int();
-        if rhs == 0 { return Err(String::from("Division by zero (left: {lhs}, right: {rhs})")) }
-        Ok(Self::from_int(lhs.checked_div(rhs).ok_or(format!("Division overflow (left: {lhs}, right: {rhs})"))?))
+
+    /// Whether a float is greater than another.
+    #[inline]
+    #[must_use]
+    pub fn fcgt(&self, other: Self) -> Self {
+        Self::from_bool(self.to_float() > other.to_float())
     }
-    #[inline(always)]
-    pub fn irem(&self, other: Self) -> Result<Self, String> {
-        let lhs = self.to_int();
-        let rhs = other.to_int();
-        if rhs == 0 { return Err(format!("Division by zero (left: {lhs}, right: {rhs})")) }
-        Ok(Self::from_int(lhs.checked_rem(rhs).ok_or(format!("Remainder overflow (left: {lhs}, right: {rhs})"))?))
+
+    /// Whether a float is less than or equal to another.
+    #[inline]
+    #[must_use]
+    pub fn fcle(&self, other: Self) -> Self {
+        Self::from_bool(self.to_float() <= other.to_float())
     }
-    #[inline(always)]
-    pub fn fadd(&self, other: Self) -> Self {
-        Self::from_float(self.to_float() + other.to_float())
+
+    /// Whether a float is less than another.
+    #[inline]
+    #[must_use]
+    pub fn fclt(&self, other: Self) -> Self {
+        Self::from_bool(self.to_float() < other.to_float())
     }
-    #[inline(always)]
-    pub fn fsub(&self, other: Self) -> Self {
-        Self::from_float(self.to_float() - other.to_float())
+
+    /// Divides two floats, or returns `None` if `other` is zero.
+    #[inline]
+    #[must_use]
+    pub fn fdiv(&self, other: Self) -> Option<Self> {
+        let rhs = other.to_float();
+        (rhs != 0.0_f64).then(|| Self::from_float(self.to_float() / rhs))
     }
-    #[inline(always)]
+
+    /// Multiplies two floats.
+    #[inline]
+    #[must_use]
     pub fn fmul(&self, other: Self) -> Self {
         Self::from_float(self.to_float() * other.to_float())
     }
-    #[inline(always)]
-    pub fn fdiv(&self, other: Self) -> Option<Self> {
-        let o = other.to_float();
-        if o == 0.0 { return None }
-        Some(Self::from_float(self.to_float() / o))
+
+    /// Negates a float.
+    #[inline]
+    #[must_use]
+    pub fn fneg(&self) -> Self {
+        Self::from_float(-self.to_float())
     }
-    #[inline(always)]
+
+    /// Takes the remainder of two floats, or returns `None` if `other` is zero.
+    #[inline]
+    #[must_use]
     pub fn frem(&self, other: Self) -> Option<Self> {
-        let o = other.to_float();
-        if o == 0.0 { return None }
-        Some(Self::from_float(self.to_float() % o))
-    }
-    #[inline(always)]
-    pub fn bor(&self, other: Self) -> Self {
-        Self::from_int(self.to_int() | other.to_int())
-    }
-    #[inline(always)]
-    pub fn band(&self, other: Self) -> Self {
-        Self::from_int(self.to_int() & other.to_int())
+        let rhs = other.to_float();
+        (rhs != 0.0_f64).then(|| Self::from_float(self.to_float() % rhs))
     }
-    #[inline(always)]
-    pub fn bxor(&self, other: Self) -> Self {
-        Self::from_int(self.to_int() ^ other.to_int())
+
+    /// Creates a boolean.
+    #[inline]
+    #[must_use]
+    pub const fn from_bool(boolean: bool) -> Self {
+        Self(boolean as u64)
     }
-    #[inline(always)]
-    pub fn bnot(&self) -> Self {
-        Self::from_int(!self.to_int())
+
+    /// Creates a float.
+    #[inline]
+    #[must_use]
+    pub const fn from_float(float: f64) -> Self {
+        Self(float.to_bits())
     }
-    #[inline(always)]
-    pub fn lor(&self, other: Self) -> Self {
-        Self::from_bool(self.to_bool() || other.to_bool())
+
+    /// Creates an integer.
+    #[inline]
+    #[must_use]
+    #[expect(
+        clippy::cast_sign_loss,
+        reason = "The integer's bits are kept as they are."
+    )]
+    pub const fn from_int(int: i64) -> Self {
+        Self(int as u64)
     }
-    #[inline(always)]
-    pub fn land(&self, other: Self) -> Self {
-        Self::from_bool(self.to_bool() && other.to_bool())
+
+    /// Creates a pointer to `addr`.
+    #[inline]
+    #[must_use]
+    pub const fn from_ptr(addr: usize) -> Self {
+        Self(addr as u64)
     }
-    #[inline(always)]
-    pub fn lnot(&self) -> Self {
-        Self::from_bool(!self.to_bool())
+
+    /// Subtracts two floats.
+    #[inline]
+    #[must_use]
+    pub fn fsub(&self, other: Self) -> Self {
+        Self::from_float(self.to_float() - other.to_float())
     }
-    #[inline(always)]
-    pub fn cmeq(&self, other: Self) -> Self {
-        Self::from_bool(self.0 == other.0)
+
+    /// Adds two integers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the addition overflows.
+    #[inline]
+    pub fn iadd(&self, other: Self) -> Result<Self, String> {
+        let lhs = self.to_int();
+        let rhs = other.to_int();
+        lhs.checked_add(rhs)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Addition overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
-    pub fn cmne(&self, other: Self) -> Self {
-        Self::from_bool(self.0 != other.0)
+
+    /// Whether an integer is greater than or equal to another.
+    #[inline]
+    #[must_use]
+    pub const fn icge(&self, other: Self) -> Self {
+        Self::from_bool(self.to_int() >= other.to_int())
     }
-    #[inline(always)]
-    pub fn icgt(&self, other: Self) -> Self {
+
+    /// Whether an integer is greater than another.
+    #[inline]
+    #[must_use]
+    pub const fn icgt(&self, other: Self) -> Self {
         Self::from_bool(self.to_int() > other.to_int())
     }
-    #[inline(always)]
-    pub fn iclt(&self, other: Self) -> Self {
+
+    /// Whether an integer is less than or equal to another.
+    #[inline]
+    #[must_use]
+    pub const fn icle(&self, other: Self) -> Self {
+        Self::from_bool(self.to_int() <= other.to_int())
+    }
+
+    /// Whether an integer is less than another.
+    #[inline]
+    #[must_use]
+    pub const fn iclt(&self, other: Self) -> Self {
         Self::from_bool(self.to_int() < other.to_int())
     }
-    #[inline(always)]
-    pub fn icge(&self, other: Self) -> Self {
-        Self::from_bool(self.to_int() >= other.to_int())
+
+    /// Divides two integers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `other` is zero, or the division overflows.
+    #[inline]
+    pub fn idiv(&self, other: Self) -> Result<Self, String> {
+        let lhs = self.to_int();
+        let rhs = other.to_int();
+        if rhs == 0 {
+            return Err(format!("Division by zero (left: {lhs}, right: {rhs})"));
+        }
+        lhs.checked_div(rhs)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Division overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
-    pub fn icle(&self, other: Self) -> Self {
-        Self::from_bool(self.to_int() <= other.to_int())
+
+    /// Multiplies two integers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the multiplication overflows.
+    #[inline]
+    pub fn imul(&self, other: Self) -> Result<Self, String> {
+        let lhs = self.to_int();
+        let rhs = other.to_int();
+        lhs.checked_mul(rhs)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Multiplication overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
-    pub fn fcgt(&self, other: Self) -> Self {
-        Self::from_bool(self.to_float() > other.to_float())
+
+    /// Negates an integer, wrapping on overflow.
+    #[inline]
+    #[must_use]
+    pub const fn ineg(&self) -> Self {
+        Self::from_int(self.to_int().wrapping_neg())
     }
-    #[inline(always)]
-    pub fn fclt(&self, other: Self) -> Self {
-        Self::from_bool(self.to_float() < other.to_float())
+
+    /// Takes the remainder of two integers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `other` is zero, or the division overflows.
+    #[inline]
+    pub fn irem(&self, other: Self) -> Result<Self, String> {
+        let lhs = self.to_int();
+        let rhs = other.to_int();
+        if rhs == 0 {
+            return Err(format!("Division by zero (left: {lhs}, right: {rhs})"));
+        }
+        lhs.checked_rem(rhs)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Remainder overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
-    pub fn fcge(&self, other: Self) -> Self {
-        Self::from_bool(self.to_float() >= other.to_float())
+
+    /// Subtracts two integers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the subtraction overflows.
+    #[inline]
+    pub fn isub(&self, other: Self) -> Result<Self, String> {
+        let lhs = self.to_int();
+        let rhs = other.to_int();
+        lhs.checked_sub(rhs)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Subtraction overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
-    pub fn fcle(&self, other: Self) -> Self {
-        Self::from_bool(self.to_float() <= other.to_float())
+
+    /// Logically-ands two booleans.
+    #[inline]
+    #[must_use]
+    pub const fn land(&self, other: Self) -> Self {
+        Self::from_bool(self.to_bool() && other.to_bool())
     }
-    #[inline(always)]
-    pub fn ineg(&self) -> Self {
-        Self::from_int(-self.to_int())
+
+    /// Logically-nots a boolean.
+    #[inline]
+    #[must_use]
+    pub const fn lnot(&self) -> Self {
+        Self::from_bool(!self.to_bool())
     }
-    #[inline(always)]
-    pub fn fneg(&self) -> Self {
-        Self::from_float(-self.to_float())
+
+    /// Logically-ors two booleans.
+    #[inline]
+    #[must_use]
+    pub const fn lor(&self, other: Self) -> Self {
+        Self::from_bool(self.to_bool() || other.to_bool())
     }
-    #[inline(always)]
+
+    /// Shifts an integer left.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `other` is negative, or shifts every bit out.
+    #[inline]
     pub fn lshf(&self, other: Self) -> Result<Self, String> {
         let lhs = self.to_int();
         let rhs = other.to_int();
-        if rhs < 0 {
-            return Err(format!("Shift left by negative integer (left: {lhs}, right: {rhs})"))
-        }
-        Ok(Self::from_int(lhs.checked_shl(rhs as u32).ok_or(format!("Shift left overflow (left: {lhs}, right: {rhs})"))?))
+        let Ok(shift) = u32::try_from(rhs) else {
+            return Err(format!(
+                "Shift left by negative integer (left: {lhs}, right: {rhs})"
+            ));
+        };
+        lhs.checked_shl(shift)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Shift left overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
+
+    /// The value that's returned when there's nothing else to return.
+    #[inline]
+    #[must_use]
+    pub const fn nil() -> Self {
+        Self(0x0)
+    }
+
+    /// Shifts an integer right.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `other` is negative, or shifts every bit out.
+    #[inline]
     pub fn rshf(&self, other: Self) -> Result<Self, String> {
         let lhs = self.to_int();
         let rhs = other.to_int();
-        if rhs < 0 {
-            return Err(format!("Shift right by negative integer (left: {lhs}, right: {rhs})"))
-        }
-        Ok(Self::from_int(lhs.checked_shr(rhs as u32).ok_or(format!("Shift right overflow (left: {lhs}, right: {rhs})"))?))
+        let Ok(shift) = u32::try_from(rhs) else {
+            return Err(format!(
+                "Shift right by negative integer (left: {lhs}, right: {rhs})"
+            ));
+        };
+        lhs.checked_shr(shift)
+            .map(Self::from_int)
+            .ok_or_else(|| format!("Shift right overflow (left: {lhs}, right: {rhs})"))
     }
-    #[inline(always)]
+
+    /// Whether two strings in `arena` are equal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either string isn't allocated in `arena`.
+    #[inline]
+    #[must_use]
+    pub fn sceq(&self, other: Self, arena: &Arena) -> Self {
+        Self::from_bool(self.string(arena) == other.string(arena))
+    }
+
+    /// Whether two strings in `arena` are unequal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either string isn't allocated in `arena`.
+    #[inline]
+    #[must_use]
+    pub fn scne(&self, other: Self, arena: &Arena) -> Self {
+        Self::from_bool(self.string(arena) != other.string(arena))
+    }
+
+    /// Concatenates two strings in `arena` into a new one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either string isn't allocated in `arena`, or the new string is
+    /// 4 GiB or longer.
+    #[inline]
+    #[must_use]
     pub fn scon(&self, other: Self, arena: &mut Arena) -> Self {
-        let lhs_addr = self.0 as usize;
-        let rhs_addr = other.0 as usize;
-        let lhs_size = u32::from_le_bytes(
-            arena.fetch(lhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let rhs_size = u32::from_le_bytes(
-            arena.fetch(rhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let new_len = lhs_size + rhs_size;
-        let addr = arena.alloc(new_len + 4, 1);
-        arena.write(addr, &new_len.to_le_bytes());
-        let lhs_data = arena.fetch(lhs_addr + 4, lhs_size).to_vec();
-        arena.write(addr + 4, &lhs_data);
-        let rhs_data = arena.fetch(rhs_addr + 4, rhs_size).to_vec();
-        arena.write(addr + 4 + lhs_size, &rhs_data);
+        let lhs_data = self.string(arena).to_vec();
+        let rhs_data = other.string(arena).to_vec();
+        let new_len = lhs_data.len().strict_add(rhs_data.len());
+        let addr = arena.alloc(new_len.strict_add(4), 1);
+        let header = u32::try_from(new_len).expect("strings should be shorter than 4 GiB");
+        arena.write(addr, &header.to_le_bytes());
+        arena.write(addr.strict_add(4), &lhs_data);
+        arena.write(addr.strict_add(4).strict_add(lhs_data.len()), &rhs_data);
         Self::from_ptr(addr)
     }
-    #[inline(always)]
-    pub fn sceq(&self, other: Self, arena: &mut Arena) -> Self {
-        let lhs_addr = self.0 as usize;
-        let rhs_addr = other.0 as usize;
-        let lhs_size = u32::from_le_bytes(
-            arena.fetch(lhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let rhs_size = u32::from_le_bytes(
-            arena.fetch(rhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let lhs_data = arena.fetch(lhs_addr + 4, lhs_size);
-        let rhs_data = arena.fetch(rhs_addr + 4, rhs_size);
-        Self::from_bool(lhs_data == rhs_data)
-    }
-    #[inline(always)]
-    pub fn scne(&self, other: Self, arena: &mut Arena) -> Self {
-        let lhs_addr = self.0 as usize;
-        let rhs_addr = other.0 as usize;
-        let lhs_size = u32::from_le_bytes(
-            arena.fetch(lhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let rhs_size = u32::from_le_bytes(
-            arena.fetch(rhs_addr, 4)
-            .try_into()
-            .unwrap()
-        ) as usize;
-        let lhs_data = arena.fetch(lhs_addr + 4, lhs_size);
-        let rhs_data = arena.fetch(rhs_addr + 4, rhs_size);
-        Self::from_bool(lhs_data != rhs_data)
-    }
-}
\ No newline at end of file
+
+    /// The bytes of the string in `arena` this points to, which are preceded by
+    /// their length as a `u32`.
+    fn string(self, arena: &Arena) -> &[u8] {
+        let addr = self.to_ptr();
+        let len = u32::from_le_bytes(
+            arena
+                .fetch(addr, 4)
+                .try_into()
+                .expect("four bytes should be fetched"),
+        );
+        arena.fetch(addr.strict_add(4), len as usize)
+    }
+
+    /// The value as a boolean.
+    #[inline]
+    #[must_use]
+    pub const fn to_bool(&self) -> bool {
+        self.0 != 0
+    }
+
+    /// The value as a float.
+    #[inline]
+    #[must_use]
+    pub const fn to_float(&self) -> f64 {
+        f64::from_bits(self.0)
+    }
+
+    /// The value as an integer.
+    #[inline]
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_wrap,
+        reason = "The integer's bits are kept as they are."
+    )]
+    pub const fn to_int(&self) -> i64 {
+        self.0 as i64
+    }
+
+    /// The value as a pointer.
+    #[inline]
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Pointers are only ever created from a `usize`."
+    )]
+    pub const fn to_ptr(&self) -> usize {
+        self.0 as usize
+    }
+}